@@ -149,6 +149,138 @@ impl<A: Accessor> CompleteAccessor<A> {
         .with_operation(op)
     }
 
+    /// Check list args against the capability, since services will ignore
+    /// args they don't support silently.
+    fn check_list_args(&self, args: &OpList) -> Result<()> {
+        let capability = self.meta.full_capability();
+        if args.name_prefix().is_some() && !capability.list_with_name_prefix {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation list with name prefix",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::List));
+        }
+        if args.entry_mode().is_some() && !capability.list_with_entry_mode {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation list with entry mode",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::List));
+        }
+        if args.soft_deleted() && !capability.list_with_soft_deleted {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation list with soft deleted",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::List));
+        }
+
+        Ok(())
+    }
+
+    /// Check read args against the capability, since services will ignore
+    /// args they don't support silently.
+    fn check_read_args(&self, args: &OpRead) -> Result<()> {
+        let capability = self.meta.full_capability();
+        if args.if_modified_since().is_some() && !capability.read_with_if_modified_since {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation read with if modified since",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::Read));
+        }
+        if args.identity_encoding() && !capability.read_with_identity_encoding {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation read with identity encoding",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::Read));
+        }
+
+        Ok(())
+    }
+
+    /// Check copy args against the capability, since services will ignore
+    /// args they don't support silently.
+    fn check_copy_args(&self, args: &OpCopy) -> Result<()> {
+        let capability = self.meta.full_capability();
+        if args.content_type().is_some() && !capability.copy_with_content_type {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation copy with content type",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::Copy));
+        }
+        if args.cache_control().is_some() && !capability.copy_with_cache_control {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation copy with cache control",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::Copy));
+        }
+        if args.source_version().is_some() && !capability.copy_with_source_version {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation copy with source version",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::Copy));
+        }
+        if args.reset_metadata() && !capability.copy_with_reset_metadata {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation copy with reset metadata",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::Copy));
+        }
+
+        Ok(())
+    }
+
+    /// Check rename args against the capability, since services will
+    /// overwrite the destination silently if they can't avoid it.
+    fn check_rename_args(&self, args: &OpRename) -> Result<()> {
+        let capability = self.meta.full_capability();
+        if !args.overwrite() && !capability.rename_without_overwrite {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation rename without overwrite",
+                    self.meta.scheme()
+                ),
+            )
+            .with_operation(Operation::Rename));
+        }
+
+        Ok(())
+    }
+
     async fn complete_reader(
         &self,
         path: &str,
@@ -158,14 +290,23 @@ impl<A: Accessor> CompleteAccessor<A> {
         if !capability.read {
             return Err(self.new_unsupported_error(Operation::Read));
         }
+        self.check_read_args(&args)?;
 
         let seekable = capability.read_can_seek;
         let streamable = capability.read_can_next;
 
+        // Readers are lazy, unless users want the reply of read which is
+        // only available after the read request is sent.
         match (seekable, streamable) {
             (true, true) => {
-                let r = LazyReader::new(self.inner.clone(), path, args);
-                Ok((RpRead::new(), CompleteReader::AlreadyComplete(r)))
+                let mut rp = RpRead::new();
+                let mut r = LazyReader::new(self.inner.clone(), path, args.clone());
+                if args.return_metadata() {
+                    let (inner_rp, inner_r) = self.inner.read(path, args).await?;
+                    rp = inner_rp;
+                    r = r.with_reader(inner_r);
+                }
+                Ok((rp, CompleteReader::AlreadyComplete(r)))
             }
             (true, false) => {
                 let r = FileReader::new(self.inner.clone(), path, args);
@@ -173,13 +314,22 @@ impl<A: Accessor> CompleteAccessor<A> {
                 Ok((RpRead::new(), CompleteReader::NeedStreamable(r)))
             }
             _ => {
-                let r = RangeReader::new(self.inner.clone(), path, args);
+                let mut rp = RpRead::new();
+                let mut r = RangeReader::new(self.inner.clone(), path, args.clone());
+                // Tailing ranges like `..size` need stat to know the offset,
+                // so they are always lazy.
+                let tailing = args.range().offset().is_none() && args.range().size().is_some();
+                if args.return_metadata() && !tailing {
+                    let (inner_rp, inner_r) = self.inner.read(path, args).await?;
+                    r = r.with_reader(&inner_rp, inner_r);
+                    rp = inner_rp;
+                }
 
                 if streamable {
-                    Ok((RpRead::new(), CompleteReader::NeedSeekable(r)))
+                    Ok((rp, CompleteReader::NeedSeekable(r)))
                 } else {
                     let r = oio::into_streamable_read(r, 256 * 1024);
-                    Ok((RpRead::new(), CompleteReader::NeedBoth(r)))
+                    Ok((rp, CompleteReader::NeedBoth(r)))
                 }
             }
         }
@@ -194,6 +344,7 @@ impl<A: Accessor> CompleteAccessor<A> {
         if !capability.read || !capability.blocking {
             return Err(self.new_unsupported_error(Operation::BlockingRead));
         }
+        self.check_read_args(&args)?;
 
         let seekable = capability.read_can_seek;
         let streamable = capability.read_can_next;
@@ -360,6 +511,15 @@ impl<A: Accessor> LayeredAccessor for CompleteAccessor<A> {
                 ),
             ));
         }
+        if args.if_match().is_some() && !capability.write_with_if_match {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation write with if match",
+                    self.info().scheme()
+                ),
+            ));
+        }
 
         // Calculate buffer size.
         let buffer_size = args.buffer().map(|mut size| {
@@ -394,6 +554,7 @@ impl<A: Accessor> LayeredAccessor for CompleteAccessor<A> {
         if !capability.copy {
             return Err(self.new_unsupported_error(Operation::Copy));
         }
+        self.check_copy_args(&args)?;
 
         self.inner().copy(from, to, args).await
     }
@@ -403,6 +564,7 @@ impl<A: Accessor> LayeredAccessor for CompleteAccessor<A> {
         if !capability.rename {
             return Err(self.new_unsupported_error(Operation::Rename));
         }
+        self.check_rename_args(&args)?;
 
         self.inner().rename(from, to, args).await
     }
@@ -435,6 +597,7 @@ impl<A: Accessor> LayeredAccessor for CompleteAccessor<A> {
         if !capability.list {
             return Err(self.new_unsupported_error(Operation::List));
         }
+        self.check_list_args(&args)?;
 
         self.complete_list(path, args).await
     }
@@ -485,6 +648,15 @@ impl<A: Accessor> LayeredAccessor for CompleteAccessor<A> {
                 ),
             ));
         }
+        if args.if_match().is_some() && !capability.write_with_if_match {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation write with if match",
+                    self.info().scheme()
+                ),
+            ));
+        }
 
         self.inner
             .blocking_write(path, args)
@@ -496,6 +668,7 @@ impl<A: Accessor> LayeredAccessor for CompleteAccessor<A> {
         if !capability.copy || !capability.blocking {
             return Err(self.new_unsupported_error(Operation::BlockingCopy));
         }
+        self.check_copy_args(&args)?;
 
         self.inner().blocking_copy(from, to, args)
     }
@@ -505,6 +678,7 @@ impl<A: Accessor> LayeredAccessor for CompleteAccessor<A> {
         if !capability.rename || !capability.blocking {
             return Err(self.new_unsupported_error(Operation::BlockingRename));
         }
+        self.check_rename_args(&args)?;
 
         self.inner().blocking_rename(from, to, args)
     }
@@ -537,6 +711,7 @@ impl<A: Accessor> LayeredAccessor for CompleteAccessor<A> {
         if !capability.list || !capability.blocking {
             return Err(self.new_unsupported_error(Operation::BlockingList));
         }
+        self.check_list_args(&args)?;
 
         self.complete_blocking_list(path, args)
     }
@@ -754,6 +929,7 @@ mod tests {
     use std::time::Duration;
 
     use async_trait::async_trait;
+    use chrono::Utc;
     use http::HeaderMap;
     use http::Method as HttpMethod;
 
@@ -839,6 +1015,28 @@ mod tests {
             ..Default::default()
         });
         let res = op.read("path").await;
+        assert!(res.is_ok());
+
+        let res = op.read_with("path").if_modified_since(Utc::now()).await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            read: true,
+            read_with_if_modified_since: true,
+            ..Default::default()
+        });
+        let res = op.read_with("path").if_modified_since(Utc::now()).await;
+        assert!(res.is_ok());
+
+        let res = op.read_with("path").identity_encoding(true).await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            read: true,
+            read_with_identity_encoding: true,
+            ..Default::default()
+        });
+        let res = op.read_with("path").identity_encoding(true).await;
         assert!(res.is_ok())
     }
 
@@ -869,6 +1067,18 @@ mod tests {
             ..Default::default()
         });
         let res = op.writer("path").await;
+        assert!(res.is_ok());
+
+        let res = op.writer_with("path").if_match("etag").await;
+        assert!(res.is_err());
+        assert_eq!(res.err().unwrap().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            write: true,
+            write_with_if_match: true,
+            ..Default::default()
+        });
+        let res = op.writer_with("path").if_match("etag").await;
         assert!(res.is_ok())
     }
 
@@ -914,6 +1124,52 @@ mod tests {
             ..Default::default()
         });
         let res = op.copy("path_a", "path_b").await;
+        assert!(res.is_ok());
+
+        let res = op
+            .copy_with("path_a", "path_b")
+            .content_type("text/plain")
+            .await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+        let res = op
+            .copy_with("path_a", "path_b")
+            .cache_control("no-cache")
+            .await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            copy: true,
+            copy_with_content_type: true,
+            copy_with_cache_control: true,
+            ..Default::default()
+        });
+        let res = op
+            .copy_with("path_a", "path_b")
+            .content_type("text/plain")
+            .cache_control("no-cache")
+            .await;
+        assert!(res.is_ok());
+
+        let res = op.copy_with("path_a", "path_b").source_version("1").await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            copy: true,
+            copy_with_source_version: true,
+            ..Default::default()
+        });
+        let res = op.copy_with("path_a", "path_b").source_version("1").await;
+        assert!(res.is_ok());
+
+        let res = op.copy_with("path_a", "path_b").reset_metadata(true).await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            copy: true,
+            copy_with_reset_metadata: true,
+            ..Default::default()
+        });
+        let res = op.copy_with("path_a", "path_b").reset_metadata(true).await;
         assert!(res.is_ok())
     }
 
@@ -929,6 +1185,17 @@ mod tests {
             ..Default::default()
         });
         let res = op.rename("path_a", "path_b").await;
+        assert!(res.is_ok());
+
+        let res = op.rename_with("path_a", "path_b").overwrite(false).await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            rename: true,
+            rename_without_overwrite: true,
+            ..Default::default()
+        });
+        let res = op.rename_with("path_a", "path_b").overwrite(false).await;
         assert!(res.is_ok())
     }
 
@@ -945,6 +1212,42 @@ mod tests {
             ..Default::default()
         });
         let res = op.list("path/").await;
+        assert!(res.is_ok());
+
+        let res = op.list_with("path/").name_prefix("a").await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            list: true,
+            list_with_recursive: true,
+            list_with_name_prefix: true,
+            ..Default::default()
+        });
+        let res = op.list_with("path/").name_prefix("a").await;
+        assert!(res.is_ok());
+
+        let res = op.list_with("path/").entry_mode(EntryMode::FILE).await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            list: true,
+            list_with_recursive: true,
+            list_with_entry_mode: true,
+            ..Default::default()
+        });
+        let res = op.list_with("path/").entry_mode(EntryMode::FILE).await;
+        assert!(res.is_ok());
+
+        let res = op.list_with("path/").soft_deleted(true).await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let op = new_test_operator(Capability {
+            list: true,
+            list_with_recursive: true,
+            list_with_soft_deleted: true,
+            ..Default::default()
+        });
+        let res = op.list_with("path/").soft_deleted(true).await;
         assert!(res.is_ok())
     }
 
@@ -277,6 +277,17 @@ impl<A: Accessor, I: RetryInterceptor> Debug for RetryAccessor<A, I> {
     }
 }
 
+impl<A: Accessor, I: RetryInterceptor> RetryAccessor<A, I> {
+    /// Build the backoff for an operation, whose `max_retries` overrides the
+    /// layer's `max_times` if set.
+    fn backoff(&self, max_retries: Option<usize>) -> ExponentialBuilder {
+        match max_retries {
+            Some(v) => self.builder.clone().with_max_times(v),
+            None => self.builder.clone(),
+        }
+    }
+}
+
 #[async_trait]
 impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
     type Inner = A;
@@ -310,8 +321,9 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let builder = self.backoff(args.max_retries());
         { || self.inner.read(path, args.clone()) }
-            .retry(&self.builder)
+            .retry(&builder)
             .when(|e| e.is_temporary())
             .notify(|err, dur| {
                 self.notify.intercept(
@@ -324,7 +336,7 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                 v.map(|(rp, r)| {
                     (
                         rp,
-                        RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
+                        RetryWrapper::new(r, self.notify.clone(), path, builder.clone()),
                     )
                 })
                 .map_err(|e| e.set_persistent())
@@ -336,8 +348,9 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
     ///
     /// Allowing users to retry the write request from upper logic.
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let builder = self.backoff(args.max_retries());
         { || self.inner.write(path, args.clone()) }
-            .retry(&self.builder)
+            .retry(&builder)
             .when(|e| e.is_temporary())
             .notify(|err, dur| {
                 self.notify.intercept(
@@ -353,7 +366,7 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                 v.map(|(rp, r)| {
                     (
                         rp,
-                        RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
+                        RetryWrapper::new(r, self.notify.clone(), path, builder.clone()),
                     )
                 })
                 .map_err(|e| e.set_persistent())
@@ -362,8 +375,9 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
     }
 
     async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let builder = self.backoff(args.max_retries());
         { || self.inner.stat(path, args.clone()) }
-            .retry(&self.builder)
+            .retry(&builder)
             .when(|e| e.is_temporary())
             .notify(|err, dur| {
                 self.notify.intercept(
@@ -501,8 +515,9 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
     }
 
     fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let builder = self.backoff(args.max_retries());
         { || self.inner.blocking_read(path, args.clone()) }
-            .retry(&self.builder)
+            .retry(&builder)
             .when(|e| e.is_temporary())
             .notify(|err, dur| {
                 self.notify.intercept(
@@ -518,15 +533,16 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
             .map(|(rp, r)| {
                 (
                     rp,
-                    RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
+                    RetryWrapper::new(r, self.notify.clone(), path, builder.clone()),
                 )
             })
             .map_err(|e| e.set_persistent())
     }
 
     fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let builder = self.backoff(args.max_retries());
         { || self.inner.blocking_write(path, args.clone()) }
-            .retry(&self.builder)
+            .retry(&builder)
             .when(|e| e.is_temporary())
             .notify(|err, dur| {
                 self.notify.intercept(
@@ -542,15 +558,16 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
             .map(|(rp, r)| {
                 (
                     rp,
-                    RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
+                    RetryWrapper::new(r, self.notify.clone(), path, builder.clone()),
                 )
             })
             .map_err(|e| e.set_persistent())
     }
 
     fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let builder = self.backoff(args.max_retries());
         { || self.inner.blocking_stat(path, args.clone()) }
-            .retry(&self.builder)
+            .retry(&builder)
             .when(|e| e.is_temporary())
             .notify(|err, dur| {
                 self.notify.intercept(
@@ -55,7 +55,16 @@ impl HttpClient {
     }
 
     /// Build a new http client in async context.
-    pub fn build(mut builder: reqwest::ClientBuilder) -> Result<Self> {
+    pub fn build(builder: reqwest::ClientBuilder) -> Result<Self> {
+        Self::build_with_connect_timeout(builder, DEFAULT_CONNECT_TIMEOUT)
+    }
+
+    /// Build a new http client in async context with given connect timeout
+    /// instead of the default one.
+    pub fn build_with_connect_timeout(
+        mut builder: reqwest::ClientBuilder,
+        connect_timeout: Duration,
+    ) -> Result<Self> {
         // Make sure we don't enable auto gzip decompress.
         builder = builder.no_gzip();
         // Make sure we don't enable auto brotli decompress.
@@ -63,7 +72,7 @@ impl HttpClient {
         // Make sure we don't enable auto deflate decompress.
         builder = builder.no_deflate();
         // Make sure we don't wait a connection establishment forever.
-        builder = builder.connect_timeout(DEFAULT_CONNECT_TIMEOUT);
+        builder = builder.connect_timeout(connect_timeout);
 
         #[cfg(feature = "trust-dns")]
         let builder = builder.trust_dns(true);
@@ -97,15 +97,23 @@ impl<T: Part> Multipart<T> {
             .set_source(err)
         })?;
 
-        let parts = s
-            .split(format!("--{}", self.boundary).as_str())
-            .collect::<Vec<&str>>();
-
-        for part in parts {
-            if part.is_empty() || part.starts_with("--") {
-                continue;
+        // The first item is the preamble before the first boundary, which
+        // should be ignored.
+        for part in s.split(format!("--{}", self.boundary).as_str()).skip(1) {
+            // The close delimiter, everything after it is epilogue.
+            if part.starts_with("--") {
+                break;
             }
 
+            // Skip the rest of boundary line (including transport padding)
+            // and the line break before next boundary. Both CRLF and LF are
+            // accepted here.
+            let part = part.split_once('\n').map(|(_, v)| v).unwrap_or("");
+            let part = part
+                .strip_suffix("\r\n")
+                .or_else(|| part.strip_suffix('\n'))
+                .unwrap_or(part);
+
             self.parts.push(T::parse(part)?);
         }
 
@@ -586,43 +594,25 @@ impl Part for MixedPart {
         )
     }
 
-    /// TODO
+    /// Parse a mixed part from the content between two boundaries.
     ///
-    /// This is a simple implementation and have a lot of space to improve.
+    /// Both CRLF and LF line endings are accepted, and whitespaces around
+    /// header names and values are ignored.
     fn parse(s: &str) -> Result<Self> {
-        let parts = s.splitn(2, "\r\n\r\n").collect::<Vec<&str>>();
-        let part_headers_content = parts[0];
-        let http_response = parts.get(1).unwrap_or(&"");
-
-        let mut part_headers = HeaderMap::new();
-        for line in part_headers_content.lines() {
-            let parts = line.splitn(2, ": ").collect::<Vec<&str>>();
-            if parts.len() == 2 {
-                let header_name = HeaderName::from_str(parts[0]).map_err(|err| {
-                    Error::new(
-                        ErrorKind::Unexpected,
-                        "multipart response contains invalid part header name",
-                    )
-                    .set_source(err)
-                })?;
-                let header_value = parts[1].parse().map_err(|err| {
-                    Error::new(
-                        ErrorKind::Unexpected,
-                        "multipart response contains invalid part header value",
-                    )
-                    .set_source(err)
-                })?;
-
-                part_headers.insert(header_name, header_value);
-            }
-        }
-
-        let parts = http_response.split("\r\n\r\n").collect::<Vec<&str>>();
-        let headers_content = parts[0];
-        let body_content = parts.get(1).unwrap_or(&"");
+        let (part_headers_content, http_response) = split_headers(s);
+        let part_headers = parse_headers(part_headers_content.lines())?;
+
+        let (headers_content, body_content) = split_headers(http_response);
+        // The embedded http response ends with a line break of its own, strip
+        // only that one so that the trailing line breaks of body are kept.
+        let body_content = body_content
+            .strip_suffix("\r\n")
+            .or_else(|| body_content.strip_suffix('\n'))
+            .unwrap_or(body_content);
         let body_bytes = Bytes::from(body_content.to_string());
 
-        let status_line = headers_content.lines().next().unwrap_or("");
+        let mut lines = headers_content.lines();
+        let status_line = lines.next().unwrap_or("");
         let status_code = status_line
             .split_whitespace()
             .nth(1)
@@ -630,28 +620,7 @@ impl Part for MixedPart {
             .parse::<u16>()
             .unwrap_or(200);
 
-        let mut headers = HeaderMap::new();
-        for line in headers_content.lines().skip(1) {
-            let parts = line.splitn(2, ": ").collect::<Vec<&str>>();
-            if parts.len() == 2 {
-                let header_name = HeaderName::from_str(parts[0]).map_err(|err| {
-                    Error::new(
-                        ErrorKind::Unexpected,
-                        "multipart response contains invalid part header name",
-                    )
-                    .set_source(err)
-                })?;
-                let header_value = parts[1].parse().map_err(|err| {
-                    Error::new(
-                        ErrorKind::Unexpected,
-                        "multipart response contains invalid part header value",
-                    )
-                    .set_source(err)
-                })?;
-
-                headers.insert(header_name, header_value);
-            }
-        }
+        let headers = parse_headers(lines)?;
 
         Ok(Self {
             part_headers,
@@ -674,6 +643,50 @@ impl Part for MixedPart {
     }
 }
 
+/// Split the input at the first empty line into headers and the rest.
+///
+/// Lines that only contain whitespaces are treated as empty lines too.
+fn split_headers(s: &str) -> (&str, &str) {
+    let mut offset = 0;
+    for line in s.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            return (&s[..offset], &s[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+
+    (s, "")
+}
+
+/// Parse header lines like `Content-Type: application/http` into HeaderMap.
+fn parse_headers<'a>(lines: impl Iterator<Item = &'a str>) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        let header_name = HeaderName::from_str(name.trim()).map_err(|err| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "multipart response contains invalid part header name",
+            )
+            .set_source(err)
+        })?;
+        let header_value = value.trim().parse().map_err(|err| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "multipart response contains invalid part header value",
+            )
+            .set_source(err)
+        })?;
+
+        headers.insert(header_name, header_value);
+    }
+
+    Ok(headers)
+}
+
 pub struct MixedPartStream {
     /// Including headers and the first `b\r\n`
     pre_content: Option<Bytes>,
@@ -1223,4 +1236,102 @@ Content-Length: 846
             Some(StatusCode::from_u16(200).unwrap())
         );
     }
+
+    #[test]
+    fn test_multipart_mixed_parse_line_endings() {
+        // Proxies may rewrite line endings, add preamble or transport padding
+        // and change whitespaces around header values.
+        let response = r#"This is the preamble, should be ignored.
+--batch_boundary  
+Content-Type:application/http
+Content-ID:   <response-1>  
+
+HTTP/1.1 204 No Content
+Content-Length:  0
+
+--batch_boundary
+Content-Type: application/http
+Content-ID: <response-2>
+
+HTTP/1.1 404 Not Found
+Content-Type: application/json; charset=UTF-8
+
+{"error": {"code": 404}}
+
+--batch_boundary--
+This is the epilogue, should be ignored."#;
+
+        for response in [response.to_string(), response.replace('\n', "\r\n")] {
+            let multipart: Multipart<MixedPart> = Multipart::new()
+                .with_boundary("batch_boundary")
+                .parse(Bytes::from(response))
+                .unwrap();
+
+            assert_eq!(multipart.parts.len(), 2);
+
+            assert_eq!(multipart.parts[0].part_headers, {
+                let mut h = HeaderMap::new();
+                h.insert("Content-Type", "application/http".parse().unwrap());
+                h.insert("Content-ID", "<response-1>".parse().unwrap());
+
+                h
+            });
+            assert_eq!(multipart.parts[0].status_code, Some(StatusCode::NO_CONTENT));
+            assert_eq!(multipart.parts[0].headers, {
+                let mut h = HeaderMap::new();
+                h.insert("Content-Length", "0".parse().unwrap());
+
+                h
+            });
+            assert_eq!(multipart.parts[0].content_length, 0);
+
+            assert_eq!(multipart.parts[1].part_headers, {
+                let mut h = HeaderMap::new();
+                h.insert("Content-Type", "application/http".parse().unwrap());
+                h.insert("Content-ID", "<response-2>".parse().unwrap());
+
+                h
+            });
+            assert_eq!(multipart.parts[1].status_code, Some(StatusCode::NOT_FOUND));
+            assert_eq!(multipart.parts[1].headers, {
+                let mut h = HeaderMap::new();
+                h.insert(
+                    "Content-Type",
+                    "application/json; charset=UTF-8".parse().unwrap(),
+                );
+
+                h
+            });
+            assert_eq!(
+                multipart.parts[1].content_length,
+                r#"{"error": {"code": 404}}"#.len() as u64
+            );
+        }
+    }
+
+    #[test]
+    fn test_multipart_mixed_parse_keeps_trailing_line_breaks() {
+        let response = r#"--batch_boundary
+Content-Type: application/http
+Content-ID: <response-1>
+
+HTTP/1.1 200 OK
+Content-Type: text/plain
+
+line
+
+
+--batch_boundary--
+"#
+        .replace('\n', "\r\n");
+
+        let multipart: Multipart<MixedPart> = Multipart::new()
+            .with_boundary("batch_boundary")
+            .parse(Bytes::from(response))
+            .unwrap();
+
+        assert_eq!(multipart.parts.len(), 1);
+        assert_eq!(multipart.parts[0].status_code, Some(StatusCode::OK));
+        assert_eq!(multipart.parts[0].content_length, "line\r\n".len() as u64);
+    }
 }
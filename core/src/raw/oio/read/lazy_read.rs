@@ -58,6 +58,13 @@ where
             state: State::<R>::Idle,
         }
     }
+
+    /// Start with the reader already opened by `acc.read` with the same op,
+    /// so that the reply of read could be returned at once.
+    pub fn with_reader(mut self, r: R) -> Self {
+        self.state = State::Read(r);
+        self
+    }
 }
 
 impl<A, R> LazyReader<A, R>
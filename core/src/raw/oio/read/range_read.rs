@@ -96,6 +96,29 @@ where
         }
     }
 
+    /// Start with the reader already opened by `acc.read` with the same op,
+    /// so that the reply of read could be returned at once.
+    ///
+    /// # Notes
+    ///
+    /// Tailing ranges like `..size` must not use this, since the offset is
+    /// unknown before stat.
+    pub fn with_reader(mut self, rp: &RpRead, r: R) -> Self {
+        debug_assert!(
+            self.offset.is_some(),
+            "tailing range reader can't be opened before stat"
+        );
+
+        // Set size if read returns size hint.
+        if let Some(size) = rp.size() {
+            if size != 0 && self.size.is_none() {
+                self.size = Some(size);
+            }
+        }
+        self.state = State::Read(r);
+        self
+    }
+
     /// Fill current reader's range by total_size.
     fn fill_range(&mut self, total_size: u64) -> Result<()> {
         (self.offset, self.size) = match (self.offset, self.size) {
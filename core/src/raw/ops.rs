@@ -21,9 +21,12 @@
 
 use std::time::Duration;
 
+use chrono::DateTime;
+use chrono::Utc;
 use flagset::FlagSet;
 
 use crate::raw::*;
+use crate::EntryMode;
 use crate::Metakey;
 
 /// Args for `create` operation.
@@ -92,6 +95,21 @@ pub struct OpList {
     /// - `Some(v)` means exist.
     /// - `None` means services doesn't have this meta.
     metakey: FlagSet<Metakey>,
+    /// The soft_deleted is used to list soft deleted entries only.
+    ///
+    /// Default to `false`.
+    soft_deleted: bool,
+    /// The name_prefix is used to only return entries whose name starts with it.
+    ///
+    /// Services that don't support filtering natively will filter the entries
+    /// while listing. Only services with `list_with_name_prefix` support it.
+    name_prefix: Option<String>,
+    /// The entry_mode is used to only return entries of this mode, like
+    /// files only or dirs only.
+    ///
+    /// Default to `None`, which returns entries of all modes. Only services
+    /// with `list_with_entry_mode` support it.
+    entry_mode: Option<EntryMode>,
 }
 
 impl Default for OpList {
@@ -102,6 +120,9 @@ impl Default for OpList {
             recursive: false,
             // By default, we want to know what's the mode of this entry.
             metakey: Metakey::Mode.into(),
+            soft_deleted: false,
+            name_prefix: None,
+            entry_mode: None,
         }
     }
 }
@@ -162,6 +183,46 @@ impl OpList {
     pub fn metakey(&self) -> FlagSet<Metakey> {
         self.metakey
     }
+
+    /// Change the soft_deleted of this list operation.
+    ///
+    /// If `true`, list operation will only list soft deleted entries.
+    pub fn with_soft_deleted(mut self, soft_deleted: bool) -> Self {
+        self.soft_deleted = soft_deleted;
+        self
+    }
+
+    /// Get the current soft_deleted.
+    pub fn soft_deleted(&self) -> bool {
+        self.soft_deleted
+    }
+
+    /// Change the name_prefix of this list operation.
+    ///
+    /// Only entries whose name starts with `name_prefix` will be returned.
+    pub fn with_name_prefix(mut self, name_prefix: &str) -> Self {
+        self.name_prefix = Some(name_prefix.into());
+        self
+    }
+
+    /// Change the entry_mode of this list operation.
+    ///
+    /// Only entries of `entry_mode` will be returned, for example
+    /// `EntryMode::FILE` for files only and `EntryMode::DIR` for dirs only.
+    pub fn with_entry_mode(mut self, entry_mode: EntryMode) -> Self {
+        self.entry_mode = Some(entry_mode);
+        self
+    }
+
+    /// Get the current entry_mode.
+    pub fn entry_mode(&self) -> Option<EntryMode> {
+        self.entry_mode
+    }
+
+    /// Get the current name_prefix.
+    pub fn name_prefix(&self) -> Option<&str> {
+        self.name_prefix.as_deref()
+    }
 }
 
 /// Args for `presign` operation.
@@ -277,10 +338,14 @@ pub struct OpRead {
     br: BytesRange,
     if_match: Option<String>,
     if_none_match: Option<String>,
+    if_modified_since: Option<DateTime<Utc>>,
     override_content_type: Option<String>,
     override_cache_control: Option<String>,
     override_content_disposition: Option<String>,
     version: Option<String>,
+    max_retries: Option<usize>,
+    identity_encoding: bool,
+    return_metadata: bool,
 }
 
 impl OpRead {
@@ -302,6 +367,7 @@ impl OpRead {
         Self {
             if_match: None,
             if_none_match: None,
+            if_modified_since: None,
             ..self
         }
     }
@@ -373,6 +439,17 @@ impl OpRead {
         self.if_none_match.as_deref()
     }
 
+    /// Set the If-Modified-Since of the option
+    pub fn with_if_modified_since(mut self, v: DateTime<Utc>) -> Self {
+        self.if_modified_since = Some(v);
+        self
+    }
+
+    /// Get If-Modified-Since from option
+    pub fn if_modified_since(&self) -> Option<DateTime<Utc>> {
+        self.if_modified_since
+    }
+
     /// Set the version of the option
     pub fn with_version(mut self, version: &str) -> Self {
         self.version = Some(version.to_string());
@@ -383,6 +460,44 @@ impl OpRead {
     pub fn version(&self) -> Option<&str> {
         self.version.as_deref()
     }
+
+    /// Set the max retries of the option, which overrides the `max_times` of
+    /// `RetryLayer`.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Get max retries from option
+    pub fn max_retries(&self) -> Option<usize> {
+        self.max_retries
+    }
+
+    /// Set whether to read the stored bytes exactly without transcoding.
+    pub fn with_identity_encoding(mut self, identity_encoding: bool) -> Self {
+        self.identity_encoding = identity_encoding;
+        self
+    }
+
+    /// Get whether to read the stored bytes exactly without transcoding.
+    pub fn identity_encoding(&self) -> bool {
+        self.identity_encoding
+    }
+
+    /// Set whether to send the read request at once, so that the metadata
+    /// advertised by services could be returned via [`RpRead`].
+    ///
+    /// Readers are lazy by default, the request will not be sent until the
+    /// first read.
+    pub fn with_return_metadata(mut self, return_metadata: bool) -> Self {
+        self.return_metadata = return_metadata;
+        self
+    }
+
+    /// Get whether to send the read request at once to return metadata.
+    pub fn return_metadata(&self) -> bool {
+        self.return_metadata
+    }
 }
 
 /// Args for `stat` operation.
@@ -391,6 +506,7 @@ pub struct OpStat {
     if_match: Option<String>,
     if_none_match: Option<String>,
     version: Option<String>,
+    max_retries: Option<usize>,
 }
 
 impl OpStat {
@@ -431,6 +547,18 @@ impl OpStat {
     pub fn version(&self) -> Option<&str> {
         self.version.as_deref()
     }
+
+    /// Set the max retries of the option, which overrides the `max_times` of
+    /// `RetryLayer`.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Get max retries from option
+    pub fn max_retries(&self) -> Option<usize> {
+        self.max_retries
+    }
 }
 
 /// Args for `write` operation.
@@ -439,9 +567,12 @@ pub struct OpWrite {
     append: bool,
     buffer: Option<usize>,
 
+    content_length: Option<u64>,
     content_type: Option<String>,
     content_disposition: Option<String>,
     cache_control: Option<String>,
+    if_match: Option<String>,
+    max_retries: Option<usize>,
 }
 
 impl OpWrite {
@@ -491,6 +622,20 @@ impl OpWrite {
         self
     }
 
+    /// Get the content length from option.
+    ///
+    /// The content length is the total size of the data to be written, it's
+    /// `None` if unknown.
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Set the content length of option.
+    pub fn with_content_length(mut self, content_length: u64) -> Self {
+        self.content_length = Some(content_length);
+        self
+    }
+
     /// Get the content type from option
     pub fn content_type(&self) -> Option<&str> {
         self.content_type.as_deref()
@@ -523,26 +668,131 @@ impl OpWrite {
         self.cache_control = Some(cache_control.to_string());
         self
     }
+
+    /// Set the If-Match of the option
+    pub fn with_if_match(mut self, if_match: &str) -> Self {
+        self.if_match = Some(if_match.to_string());
+        self
+    }
+
+    /// Get If-Match from option
+    pub fn if_match(&self) -> Option<&str> {
+        self.if_match.as_deref()
+    }
+
+    /// Set the max retries of the option, which overrides the `max_times` of
+    /// `RetryLayer`.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Get max retries from option
+    pub fn max_retries(&self) -> Option<usize> {
+        self.max_retries
+    }
 }
 
 /// Args for `copy` operation.
 #[derive(Debug, Clone, Default)]
-pub struct OpCopy {}
+pub struct OpCopy {
+    content_type: Option<String>,
+    cache_control: Option<String>,
+    source_version: Option<String>,
+    reset_metadata: bool,
+}
 
 impl OpCopy {
     /// Create a new `OpCopy`.
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Get the content type that overrides the destination's.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Set the content type that overrides the destination's.
+    pub fn with_content_type(mut self, content_type: &str) -> Self {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+
+    /// Get the cache control that overrides the destination's.
+    pub fn cache_control(&self) -> Option<&str> {
+        self.cache_control.as_deref()
+    }
+
+    /// Set the cache control that overrides the destination's.
+    pub fn with_cache_control(mut self, cache_control: &str) -> Self {
+        self.cache_control = Some(cache_control.to_string());
+        self
+    }
+
+    /// Get the version of source to copy from.
+    pub fn source_version(&self) -> Option<&str> {
+        self.source_version.as_deref()
+    }
+
+    /// Pin the version of source to copy from.
+    ///
+    /// Services that support it will copy this exact version instead of the
+    /// latest one, which may be overwritten in the meantime.
+    pub fn with_source_version(mut self, version: &str) -> Self {
+        self.source_version = Some(version.to_string());
+        self
+    }
+
+    /// Get whether the destination's metadata will be reset.
+    pub fn reset_metadata(&self) -> bool {
+        self.reset_metadata
+    }
+
+    /// Reset the destination's metadata to defaults instead of preserving
+    /// the source's.
+    ///
+    /// By default, the destination preserves the metadata of the source. If
+    /// reset, only the metadata set in this op will be kept, like
+    /// `content_type` and `cache_control`.
+    pub fn with_reset_metadata(mut self, reset_metadata: bool) -> Self {
+        self.reset_metadata = reset_metadata;
+        self
+    }
 }
 
 /// Args for `rename` operation.
-#[derive(Debug, Clone, Default)]
-pub struct OpRename {}
+#[derive(Debug, Clone)]
+pub struct OpRename {
+    /// Whether to overwrite the destination if it exists.
+    ///
+    /// Default to `true`.
+    overwrite: bool,
+}
+
+impl Default for OpRename {
+    fn default() -> Self {
+        OpRename { overwrite: true }
+    }
+}
 
 impl OpRename {
     /// Create a new `OpMove`.
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Change the overwrite of this rename operation.
+    ///
+    /// If `false`, services that support it will return an `AlreadyExists`
+    /// error if the destination exists.
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Get the overwrite of this rename operation.
+    pub fn overwrite(&self) -> bool {
+        self.overwrite
+    }
 }
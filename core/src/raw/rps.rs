@@ -15,6 +15,9 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::sync::Arc;
+use std::sync::Mutex;
+
 use http::Request;
 
 use crate::*;
@@ -107,6 +110,18 @@ pub struct RpRead {
     /// It's ok to leave size as empty, but it's recommended to set size if possible. We will use
     /// this size as hint to do some optimization like avoid an extra stat or read.
     size: Option<u64>,
+    /// Base64 encoded MD5 of the content advertised by services.
+    content_md5: Option<String>,
+    /// Base64 encoded CRC32C of the content advertised by services.
+    content_crc32c: Option<String>,
+    /// Content type of the content advertised by services.
+    content_type: Option<String>,
+    /// ETag of the content advertised by services.
+    etag: Option<String>,
+    /// Content disposition of the content advertised by services.
+    content_disposition: Option<String>,
+    /// Version of the content advertised by services.
+    version: Option<String>,
 }
 
 impl RpRead {
@@ -128,6 +143,115 @@ impl RpRead {
         self.size = size;
         self
     }
+
+    /// Got the base64 encoded MD5 of the content advertised by services.
+    ///
+    /// It's the checksum of the whole object, not the range been read.
+    pub fn content_md5(&self) -> Option<&str> {
+        self.content_md5.as_deref()
+    }
+
+    /// Set the base64 encoded MD5 of the content.
+    pub fn with_content_md5(mut self, content_md5: Option<String>) -> Self {
+        self.content_md5 = content_md5;
+        self
+    }
+
+    /// Got the base64 encoded CRC32C of the content advertised by services.
+    ///
+    /// It's the checksum of the whole object, not the range been read.
+    pub fn content_crc32c(&self) -> Option<&str> {
+        self.content_crc32c.as_deref()
+    }
+
+    /// Set the base64 encoded CRC32C of the content.
+    pub fn with_content_crc32c(mut self, content_crc32c: Option<String>) -> Self {
+        self.content_crc32c = content_crc32c;
+        self
+    }
+
+    /// Got the content type of the content advertised by services.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Set the content type of the content.
+    pub fn with_content_type(mut self, content_type: Option<String>) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// Got the etag of the content advertised by services.
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /// Set the etag of the content.
+    pub fn with_etag(mut self, etag: Option<String>) -> Self {
+        self.etag = etag;
+        self
+    }
+
+    /// Got the content disposition of the content advertised by services.
+    pub fn content_disposition(&self) -> Option<&str> {
+        self.content_disposition.as_deref()
+    }
+
+    /// Set the content disposition of the content.
+    pub fn with_content_disposition(mut self, content_disposition: Option<String>) -> Self {
+        self.content_disposition = content_disposition;
+        self
+    }
+
+    /// Got the version of the content advertised by services.
+    ///
+    /// For example, it's the generation of the object in GCS, which could be
+    /// used to make sure following reads are served from the same content.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Set the version of the content.
+    pub fn with_version(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Consume RpRead to get the metadata advertised by services.
+    ///
+    /// All keys carried by `RpRead` are marked as set, so that keys not
+    /// advertised by services will be `None` instead of panic.
+    pub fn into_metadata(self) -> Metadata {
+        let mut meta = Metadata::new(EntryMode::FILE);
+        if let Some(v) = self.content_md5 {
+            meta.set_content_md5(&v);
+        }
+        if let Some(v) = self.content_crc32c {
+            meta.set_content_crc32c(&v);
+        }
+        if let Some(v) = self.content_type {
+            meta.set_content_type(&v);
+        }
+        if let Some(v) = self.etag {
+            meta.set_etag(&v);
+        }
+        if let Some(v) = self.content_disposition {
+            meta.set_content_disposition(&v);
+        }
+        if let Some(v) = self.version {
+            meta.set_version(&v);
+        }
+
+        meta.with_metakey(
+            Metakey::Mode
+                | Metakey::ContentMd5
+                | Metakey::ContentCrc32c
+                | Metakey::ContentType
+                | Metakey::Etag
+                | Metakey::ContentDisposition
+                | Metakey::Version,
+        )
+    }
 }
 
 /// Reply for `batch` operation.
@@ -150,6 +274,42 @@ impl RpBatch {
     pub fn into_results(self) -> Vec<(String, Result<BatchedReply>)> {
         self.results
     }
+
+    /// Summarize the batched results into succeeded count and failed paths.
+    pub fn summary(&self) -> BatchSummary {
+        let mut summary = BatchSummary::default();
+        for (path, res) in &self.results {
+            match res {
+                Ok(_) => summary.succeeded += 1,
+                Err(err) => summary.failed.push((path.clone(), err.kind())),
+            }
+        }
+        summary
+    }
+}
+
+/// Summary of `batch` operation's results.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    succeeded: usize,
+    failed: Vec<(String, ErrorKind)>,
+}
+
+impl BatchSummary {
+    /// Get the count of succeeded operations.
+    pub fn succeeded(&self) -> usize {
+        self.succeeded
+    }
+
+    /// Get the count of failed operations.
+    pub fn failed_count(&self) -> usize {
+        self.failed.len()
+    }
+
+    /// Get the failed paths along with their error kinds.
+    pub fn failed(&self) -> &[(String, ErrorKind)] {
+        &self.failed
+    }
 }
 
 /// Batch results of `batch` operations.
@@ -190,12 +350,37 @@ impl RpStat {
 
 /// Reply for `write` operation.
 #[derive(Debug, Clone, Default)]
-pub struct RpWrite {}
+pub struct RpWrite {
+    /// Metadata of the written content, which is filled by services after
+    /// the writer is closed.
+    ///
+    /// Writers are type-erased once created, so the metadata can't be
+    /// returned while closing them.
+    metadata: Option<Arc<Mutex<Option<Metadata>>>>,
+}
 
 impl RpWrite {
     /// Create a new reply for `write`.
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Set the slot which will be filled with the metadata of the written
+    /// content after the writer is closed.
+    pub fn with_metadata(mut self, metadata: Arc<Mutex<Option<Metadata>>>) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Get the metadata of the written content returned by services.
+    ///
+    /// It's only available after the writer is closed.
+    pub fn metadata(&self) -> Option<Metadata> {
+        self.metadata
+            .as_ref()?
+            .lock()
+            .expect("lock must succeed")
+            .clone()
     }
 }
 
@@ -258,4 +443,31 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_batch_summary() {
+        let rp = RpBatch::new(vec![
+            ("a".to_string(), Ok(RpDelete::default().into())),
+            (
+                "b".to_string(),
+                Err(Error::new(ErrorKind::PermissionDenied, "denied")),
+            ),
+            ("c".to_string(), Ok(RpDelete::default().into())),
+            (
+                "d".to_string(),
+                Err(Error::new(ErrorKind::RateLimited, "slow down")),
+            ),
+        ]);
+
+        let summary = rp.summary();
+        assert_eq!(summary.succeeded(), 2);
+        assert_eq!(summary.failed_count(), 2);
+        assert_eq!(
+            summary.failed(),
+            &[
+                ("b".to_string(), ErrorKind::PermissionDenied),
+                ("d".to_string(), ErrorKind::RateLimited),
+            ]
+        );
+    }
 }
@@ -19,8 +19,16 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use futures::stream;
+use futures::StreamExt;
+use http::HeaderValue;
+use http::Request;
 use http::StatusCode;
 use log::debug;
 use reqsign::GoogleCredentialLoader;
@@ -29,10 +37,23 @@ use reqsign::GoogleTokenLoad;
 use reqsign::GoogleTokenLoader;
 use serde::Deserialize;
 use serde_json;
+use sha2::Digest;
+use sha2::Sha256;
+use tokio::sync::OnceCell;
 
+use super::core::is_transcoded;
+use super::core::normalize_etag;
+use super::core::parse_content_md5;
+use super::core::parse_generation;
+use super::core::parse_goog_hash;
 use super::core::GcsCore;
 use super::error::parse_error;
+use super::metrics::GcsMetricsHook;
 use super::pager::GcsPager;
+use super::pager::ListResponse;
+use super::post_policy::PostPolicy;
+use super::reader::GcsReader;
+use super::signed_url::GcsSignedUrlVersion;
 use super::writer::GcsWriter;
 use crate::raw::*;
 use crate::services::gcs::writer::GcsWriters;
@@ -40,6 +61,13 @@ use crate::*;
 
 const DEFAULT_GCS_ENDPOINT: &str = "https://storage.googleapis.com";
 const DEFAULT_GCS_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_write";
+const DEFAULT_GCS_API_VERSION: &str = "v1";
+/// Content type of directory markers written by `create_dir`, which is used
+/// to tell markers apart from objects whose names end with `/`.
+const DIR_MARKER_CONTENT_TYPE: &str = "application/x-directory";
+/// Chunks of resumable uploads must be a multiple of 256 KiB except the last
+/// one.
+const GCS_RESUMABLE_CHUNK_ALIGN: usize = 256 * 1024;
 
 /// [Google Cloud Storage](https://cloud.google.com/storage) services support.
 #[doc = include_str!("docs.md")]
@@ -47,6 +75,8 @@ const DEFAULT_GCS_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read
 pub struct GcsBuilder {
     /// root URI, all operations happens under `root`
     root: Option<String>,
+    /// prefix of object names under `root`
+    prefix: Option<String>,
     /// bucket name
     bucket: String,
     /// endpoint URI of GCS service,
@@ -66,6 +96,28 @@ pub struct GcsBuilder {
     customed_token_loader: Option<Box<dyn GoogleTokenLoad>>,
     predefined_acl: Option<String>,
     default_storage_class: Option<String>,
+    kms_key_name: Option<String>,
+    default_content_disposition: Option<String>,
+    disable_implicit_dir: bool,
+    disable_dir_marker: bool,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    host: Option<String>,
+    detect_dir_by_list: bool,
+    api_version: Option<String>,
+    quota_project: Option<String>,
+    error_on_missing_delete: bool,
+    batch_boundary: Option<String>,
+    check_bucket: bool,
+    enable_content_md5: bool,
+    verify_read_md5: bool,
+    enable_crc32c_check: bool,
+    multipart_upload_threshold: Option<usize>,
+    max_object_size: Option<u64>,
+    signed_url_version: Option<String>,
+    encryption_key: Option<String>,
+    encryption_key_sha256: Option<String>,
+    metrics_hook: Option<Arc<dyn GcsMetricsHook>>,
 }
 
 impl GcsBuilder {
@@ -78,6 +130,24 @@ impl GcsBuilder {
         self
     }
 
+    /// Set the prefix of object names, which will be prepended after `root`.
+    ///
+    /// It's useful to isolate tenants sharing one bucket, for example
+    /// `tenant-a` with root `/data` makes all objects stored under
+    /// `data/tenant-a/`. The prefix is included in debug logs of the builder.
+    ///
+    /// # Notes
+    ///
+    /// `build` will return `ConfigInvalid` if the prefix contains `.` or
+    /// `..` segments, so that it can't escape from `root`.
+    pub fn prefix(&mut self, prefix: &str) -> &mut Self {
+        if !prefix.is_empty() {
+            self.prefix = Some(prefix.to_string())
+        }
+
+        self
+    }
+
     /// set the container's name
     pub fn bucket(&mut self, bucket: &str) -> &mut Self {
         self.bucket = bucket.to_string();
@@ -183,6 +253,306 @@ impl GcsBuilder {
         };
         self
     }
+
+    /// Set the Cloud KMS key used to encrypt written objects.
+    ///
+    /// `name` is the fully-qualified resource name of the key like
+    /// `projects/p/locations/l/keyRings/r/cryptoKeys/k`, which will be sent as
+    /// `kmsKeyName` along with `predefined_acl` while creating objects.
+    pub fn kms_key_name(&mut self, name: &str) -> &mut Self {
+        if !name.is_empty() {
+            self.kms_key_name = Some(name.to_string())
+        };
+        self
+    }
+
+    /// Set the default content disposition for GCS.
+    ///
+    /// It will be applied on inserts if `OpWrite` doesn't specify one, for
+    /// example `attachment` to make browsers download objects.
+    pub fn default_content_disposition(&mut self, content_disposition: &str) -> &mut Self {
+        if !content_disposition.is_empty() {
+            self.default_content_disposition = Some(content_disposition.to_string())
+        };
+        self
+    }
+
+    /// Disable the implicit directory assumption in stat.
+    ///
+    /// By default, stat on a path that ends with `/` will return a DIR even
+    /// if GCS returns `404`, since directories are not real objects in GCS.
+    ///
+    /// If enabled, stat will return `NotFound` for a missing path ends with `/`
+    /// instead. This is useful for layouts that never use directory placeholder
+    /// objects.
+    pub fn disable_implicit_dir(&mut self) -> &mut Self {
+        self.disable_implicit_dir = true;
+        self
+    }
+
+    /// Disable writing directory markers in create_dir.
+    ///
+    /// By default, create_dir writes a zero-byte marker object whose name
+    /// ends with `/`, which is expected by tools like the Hadoop GCS
+    /// connector. Markers are hidden from list results of their parents.
+    ///
+    /// If enabled, create_dir will be a no-op since directories are implicit
+    /// in GCS. Don't enable it along with `disable_implicit_dir`, otherwise
+    /// created directories can't be found by stat.
+    pub fn disable_dir_marker(&mut self) -> &mut Self {
+        self.disable_dir_marker = true;
+        self
+    }
+
+    /// Set the timeout of establishing connections to GCS.
+    ///
+    /// A short connect timeout makes requests to unreachable endpoints fail
+    /// fast, it doesn't limit how long the response takes.
+    ///
+    /// Default to `60s`. It's ignored if `http_client` is set, please
+    /// configure the client instead.
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        if !timeout.is_zero() {
+            self.connect_timeout = Some(timeout);
+        }
+        self
+    }
+
+    /// Set the timeout of waiting for the next chunk of data while reading.
+    ///
+    /// The timer is reset every time data arrives, so large reads are never
+    /// interrupted as long as the data keeps flowing. A temporary error will
+    /// be returned if no data is received within the timeout.
+    ///
+    /// Disabled by default.
+    pub fn read_timeout(&mut self, timeout: Duration) -> &mut Self {
+        if !timeout.is_zero() {
+            self.read_timeout = Some(timeout);
+        }
+        self
+    }
+
+    /// Detect directories by listing in stat.
+    ///
+    /// By default, stat on a path that ends with `/` will get the metadata of
+    /// the placeholder object first, and return a DIR if it's not found.
+    ///
+    /// If enabled, stat will list the prefix with `maxResults=1` instead, and
+    /// return a DIR only if there are any objects under it, `NotFound`
+    /// otherwise.
+    pub fn detect_dir_by_list(&mut self) -> &mut Self {
+        self.detect_dir_by_list = true;
+        self
+    }
+
+    /// Override the `Host` header of all requests sent to GCS.
+    ///
+    /// Requests are always built in path-style like `{endpoint}/{bucket}/{path}`,
+    /// so this is useful for storage emulators that route by a fixed `Host`
+    /// instead of the endpoint.
+    ///
+    /// If not set, the `Host` header will be decided by the http client.
+    pub fn host(&mut self, host: &str) -> &mut Self {
+        if !host.is_empty() {
+            self.host = Some(host.to_string())
+        };
+        self
+    }
+
+    /// Set the version of JSON API used in request paths like
+    /// `{endpoint}/storage/{api_version}/b/{bucket}`.
+    ///
+    /// Default to `v1`.
+    pub fn api_version(&mut self, api_version: &str) -> &mut Self {
+        if !api_version.is_empty() {
+            self.api_version = Some(api_version.to_string())
+        };
+        self
+    }
+
+    /// Set the quota project for GCS.
+    ///
+    /// The quota project will be sent via `x-goog-user-project` header, and
+    /// charged for quota and billing instead of the project of credentials.
+    pub fn quota_project(&mut self, quota_project: &str) -> &mut Self {
+        if !quota_project.is_empty() {
+            self.quota_project = Some(quota_project.to_string())
+        };
+        self
+    }
+
+    /// Return `NotFound` while deleting a missing object.
+    ///
+    /// By default, delete treats `404` as success to keep it idempotent. If
+    /// enabled, delete and batch delete will return an error with
+    /// `ErrorKind::NotFound` for missing objects instead.
+    pub fn error_on_missing_delete(&mut self) -> &mut Self {
+        self.error_on_missing_delete = true;
+        self
+    }
+
+    /// Set the multipart boundary of batch requests.
+    ///
+    /// # Notes
+    ///
+    /// A random boundary is generated for every batch request by default.
+    /// Some proxies only accept specific boundaries, the boundary must follow
+    /// [RFC 2046](https://www.rfc-editor.org/rfc/rfc2046#section-5.1.1),
+    /// otherwise `build` will fail with `ErrorKind::ConfigInvalid`.
+    pub fn batch_boundary(&mut self, boundary: &str) -> &mut Self {
+        if !boundary.is_empty() {
+            self.batch_boundary = Some(boundary.to_string())
+        }
+        self
+    }
+
+    /// Check the bucket's configuration against this builder.
+    ///
+    /// If enabled, the bucket will be fetched before the first operation
+    /// that creates objects, like `write`, `copy`, `rename` and
+    /// `create_dir`. When uniform bucket-level access is enabled on the
+    /// bucket, ACLs are disabled and these operations with `predefined_acl`
+    /// will fail with `ErrorKind::ConfigInvalid` instead of sending requests
+    /// which GCS will reject with confusing `400`s.
+    ///
+    /// # Notes
+    ///
+    /// `build` is not async and can't send requests, so the check happens
+    /// lazily and is skipped after the first success.
+    pub fn check_bucket(&mut self) -> &mut Self {
+        self.check_bucket = true;
+        self
+    }
+
+    /// Send `Content-MD5` while uploading objects in a single request.
+    ///
+    /// If enabled, the MD5 of the content will be calculated and sent along
+    /// with the upload, GCS will reject the upload if the received content
+    /// doesn't match, which complements the crc32c validation.
+    ///
+    /// # Notes
+    ///
+    /// Resumable uploads are not covered since the whole content is unknown
+    /// while uploading chunks.
+    pub fn enable_content_md5(&mut self) -> &mut Self {
+        self.enable_content_md5 = true;
+        self
+    }
+
+    /// Verify the MD5 of downloaded content.
+    ///
+    /// If enabled, the MD5 of the content will be calculated while reading,
+    /// and compared with the one advertised by `X-Goog-Hash` or `Content-MD5`
+    /// at the end of content. An error will be returned if they don't match,
+    /// which is independent of the crc32c returned to users.
+    ///
+    /// # Notes
+    ///
+    /// Only reads of whole objects are verified. Range reads, transcoded
+    /// objects and composite objects which don't have MD5 are skipped.
+    pub fn verify_read_md5(&mut self) -> &mut Self {
+        self.verify_read_md5 = true;
+        self
+    }
+
+    /// Verify the CRC32C of downloaded content.
+    ///
+    /// If enabled, the CRC32C of the content will be calculated incrementally
+    /// while reading, and compared with the one advertised by `X-Goog-Hash`
+    /// at the end of content. An error with `ErrorKind::Unexpected` will be
+    /// returned if they don't match.
+    ///
+    /// # Notes
+    ///
+    /// Unlike MD5, composite objects have CRC32C too. Range reads and
+    /// transcoded objects are still skipped.
+    pub fn enable_crc32c_check(&mut self) -> &mut Self {
+        self.enable_crc32c_check = true;
+        self
+    }
+
+    /// Upload objects up to `threshold` bytes in a single request.
+    ///
+    /// # Notes
+    ///
+    /// Writes will be buffered up to `threshold` bytes. Objects that fit in
+    /// are uploaded with `uploadType=multipart`, which sends the metadata and
+    /// the content in one request and saves the extra round trips of
+    /// resumable uploads. Larger objects fall back to resumable uploads with
+    /// chunks of `threshold` bytes.
+    ///
+    /// `threshold` must be a multiple of 256 KiB as required by resumable
+    /// uploads, otherwise `build` will return `ConfigInvalid`.
+    pub fn multipart_upload_threshold(&mut self, threshold: usize) -> &mut Self {
+        self.multipart_upload_threshold = Some(threshold);
+        self
+    }
+
+    /// Refuse to write objects larger than `size` bytes.
+    ///
+    /// # Notes
+    ///
+    /// The size is checked before sending every chunk. Once it's exceeded,
+    /// the write fails with `ErrorKind::Unexpected` and the resumable upload
+    /// session is aborted, so the object will not be created. It's useful to
+    /// catch runaway writes.
+    pub fn max_object_size(&mut self, size: u64) -> &mut Self {
+        self.max_object_size = Some(size);
+        self
+    }
+
+    /// Set the signing process used by `presign`, could be `v2` or `v4`.
+    ///
+    /// # Notes
+    ///
+    /// - Default to `v4`.
+    /// - `v2` is only supported by service account credentials. It's provided
+    ///   for legacy consumers that can't accept V4 signed urls, and carries
+    ///   an absolute `Expires` timestamp instead of `X-Goog-Expires`.
+    /// - V4 signed urls can't be valid for more than 7 days.
+    pub fn signed_url_version(&mut self, version: &str) -> &mut Self {
+        if !version.is_empty() {
+            self.signed_url_version = Some(version.to_string())
+        }
+        self
+    }
+
+    /// Set the customer-supplied encryption key (CSEK) of objects.
+    ///
+    /// # Args
+    ///
+    /// `key`: Base64 encoded AES-256 key, which must be 32 bytes after decoded.
+    ///
+    /// # Notes
+    ///
+    /// The key will be sent along with reads, writes, stats and copies. Objects
+    /// written with it can't be read without it, GCS returns `400` instead.
+    pub fn encryption_key(&mut self, key: &str) -> &mut Self {
+        if !key.is_empty() {
+            self.encryption_key = Some(key.to_string());
+        }
+        self
+    }
+
+    /// Set the base64 encoded SHA256 of the customer-supplied encryption key.
+    ///
+    /// It will be calculated from `encryption_key` if not set.
+    pub fn encryption_key_sha256(&mut self, hash: &str) -> &mut Self {
+        if !hash.is_empty() {
+            self.encryption_key_sha256 = Some(hash.to_string());
+        }
+        self
+    }
+
+    /// Register a hook to observe requests sent to GCS.
+    ///
+    /// The hook will be notified about responses, retries and the bytes read
+    /// and written, which could be used to export metrics. There is no extra
+    /// cost if no hook is registered.
+    pub fn metrics_hook(&mut self, hook: Arc<dyn GcsMetricsHook>) -> &mut Self {
+        self.metrics_hook = Some(hook);
+        self
+    }
 }
 
 impl Debug for GcsBuilder {
@@ -190,15 +560,36 @@ impl Debug for GcsBuilder {
         let mut ds = f.debug_struct("Builder");
 
         ds.field("root", &self.root)
+            .field("prefix", &self.prefix)
             .field("bucket", &self.bucket)
             .field("endpoint", &self.endpoint);
         if self.credential.is_some() {
             ds.field("credentials", &"<redacted>");
         }
+        if self.encryption_key.is_some() {
+            ds.field("encryption_key", &"<redacted>");
+        }
         if self.predefined_acl.is_some() {
             ds.field("predefined_acl", &self.predefined_acl);
         }
         ds.field("default_storage_class", &self.default_storage_class);
+        ds.field("kms_key_name", &self.kms_key_name);
+        ds.field(
+            "default_content_disposition",
+            &self.default_content_disposition,
+        );
+        ds.field("disable_implicit_dir", &self.disable_implicit_dir);
+        ds.field("disable_dir_marker", &self.disable_dir_marker);
+        ds.field("connect_timeout", &self.connect_timeout);
+        ds.field("read_timeout", &self.read_timeout);
+        ds.field("host", &self.host);
+        ds.field("detect_dir_by_list", &self.detect_dir_by_list);
+        ds.field(
+            "multipart_upload_threshold",
+            &self.multipart_upload_threshold,
+        );
+        ds.field("max_object_size", &self.max_object_size);
+        ds.field("signed_url_version", &self.signed_url_version);
         ds.finish()
     }
 }
@@ -211,6 +602,7 @@ impl Builder for GcsBuilder {
         let mut builder = GcsBuilder::default();
 
         map.get("root").map(|v| builder.root(v));
+        map.get("prefix").map(|v| builder.prefix(v));
         map.get("bucket").map(|v| builder.bucket(v));
         map.get("endpoint").map(|v| builder.endpoint(v));
         map.get("credential").map(|v| builder.credential(v));
@@ -218,6 +610,54 @@ impl Builder for GcsBuilder {
         map.get("predefined_acl").map(|v| builder.predefined_acl(v));
         map.get("default_storage_class")
             .map(|v| builder.default_storage_class(v));
+        map.get("kms_key_name").map(|v| builder.kms_key_name(v));
+        map.get("default_content_disposition")
+            .map(|v| builder.default_content_disposition(v));
+        map.get("disable_implicit_dir")
+            .filter(|v| v == &"true")
+            .map(|_| builder.disable_implicit_dir());
+        map.get("disable_dir_marker")
+            .filter(|v| v == &"true")
+            .map(|_| builder.disable_dir_marker());
+        map.get("connect_timeout")
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|v| builder.connect_timeout(Duration::from_secs(v)));
+        map.get("read_timeout")
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|v| builder.read_timeout(Duration::from_secs(v)));
+        map.get("host").map(|v| builder.host(v));
+        map.get("detect_dir_by_list")
+            .filter(|v| v == &"true")
+            .map(|_| builder.detect_dir_by_list());
+        map.get("api_version").map(|v| builder.api_version(v));
+        map.get("quota_project").map(|v| builder.quota_project(v));
+        map.get("error_on_missing_delete")
+            .filter(|v| v == &"true")
+            .map(|_| builder.error_on_missing_delete());
+        map.get("batch_boundary").map(|v| builder.batch_boundary(v));
+        map.get("check_bucket")
+            .filter(|v| v == &"true")
+            .map(|_| builder.check_bucket());
+        map.get("enable_content_md5")
+            .filter(|v| v == &"true")
+            .map(|_| builder.enable_content_md5());
+        map.get("verify_read_md5")
+            .filter(|v| v == &"true")
+            .map(|_| builder.verify_read_md5());
+        map.get("enable_crc32c_check")
+            .filter(|v| v == &"true")
+            .map(|_| builder.enable_crc32c_check());
+        map.get("multipart_upload_threshold")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.multipart_upload_threshold(v));
+        map.get("max_object_size")
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|v| builder.max_object_size(v));
+        map.get("signed_url_version")
+            .map(|v| builder.signed_url_version(v));
+        map.get("encryption_key").map(|v| builder.encryption_key(v));
+        map.get("encryption_key_sha256")
+            .map(|v| builder.encryption_key_sha256(v));
 
         builder
     }
@@ -225,7 +665,19 @@ impl Builder for GcsBuilder {
     fn build(&mut self) -> Result<Self::Accessor> {
         debug!("backend build started: {:?}", self);
 
-        let root = normalize_root(&self.root.take().unwrap_or_default());
+        let mut root = normalize_root(&self.root.take().unwrap_or_default());
+        if let Some(prefix) = &self.prefix {
+            if prefix.split('/').any(|v| v == "." || v == "..") {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "prefix must not contain `.` or `..` segments",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Gcs)
+                .with_context("prefix", prefix));
+            }
+            root = normalize_root(&format!("{root}{prefix}"));
+        }
         debug!("backend use root {}", root);
 
         // Handle endpoint and bucket name
@@ -238,12 +690,69 @@ impl Builder for GcsBuilder {
             ),
         }?;
 
-        // TODO: server side encryption
+        if let Some(threshold) = self.multipart_upload_threshold {
+            if threshold == 0 || threshold % GCS_RESUMABLE_CHUNK_ALIGN != 0 {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "multipart upload threshold must be a multiple of 256 KiB",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Gcs)
+                .with_context("multipart_upload_threshold", threshold.to_string()));
+            }
+        }
+
+        let (encryption_key, encryption_key_sha256) = match &self.encryption_key {
+            Some(key) => {
+                let decoded = BASE64_STANDARD.decode(key).map_err(|err| {
+                    Error::new(
+                        ErrorKind::ConfigInvalid,
+                        "encryption key must be base64 encoded",
+                    )
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::Gcs)
+                    .set_source(err)
+                })?;
+                if decoded.len() != 32 {
+                    return Err(Error::new(
+                        ErrorKind::ConfigInvalid,
+                        "encryption key must be a 32 bytes AES-256 key",
+                    )
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::Gcs)
+                    .with_context("length", decoded.len().to_string()));
+                }
+
+                let key_sha256 = match &self.encryption_key_sha256 {
+                    Some(v) => v.clone(),
+                    None => BASE64_STANDARD.encode(Sha256::digest(&decoded)),
+                };
+                (
+                    Some(build_header_value(key)?),
+                    Some(build_header_value(&key_sha256)?),
+                )
+            }
+            None if self.encryption_key_sha256.is_some() => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "encryption key sha256 is set without encryption key",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Gcs))
+            }
+            None => (None, None),
+        };
 
         let client = if let Some(client) = self.http_client.take() {
             client
         } else {
-            HttpClient::new().map_err(|err| {
+            let client = match self.connect_timeout {
+                Some(timeout) => {
+                    HttpClient::build_with_connect_timeout(reqwest::ClientBuilder::new(), timeout)
+                }
+                None => HttpClient::new(),
+            };
+            client.map_err(|err| {
                 err.with_operation("Builder::build")
                     .with_context("service", Scheme::Gcs)
             })?
@@ -280,6 +789,47 @@ impl Builder for GcsBuilder {
             token_loader = token_loader.with_customed_token_loader(loader)
         }
 
+        let host = match &self.host {
+            Some(host) => Some(HeaderValue::from_str(host).map_err(|err| {
+                Error::new(ErrorKind::ConfigInvalid, "host is invalid")
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::Gcs)
+                    .with_context("host", host)
+                    .set_source(err)
+            })?),
+            None => None,
+        };
+
+        let quota_project = match &self.quota_project {
+            Some(v) => Some(HeaderValue::from_str(v).map_err(|err| {
+                Error::new(ErrorKind::ConfigInvalid, "quota project is invalid")
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::Gcs)
+                    .with_context("quota_project", v)
+                    .set_source(err)
+            })?),
+            None => None,
+        };
+
+        if let Some(boundary) = &self.batch_boundary {
+            if !is_valid_boundary(boundary) {
+                return Err(
+                    Error::new(ErrorKind::ConfigInvalid, "batch boundary is invalid")
+                        .with_operation("Builder::build")
+                        .with_context("service", Scheme::Gcs)
+                        .with_context("batch_boundary", boundary),
+                );
+            }
+        }
+
+        let signed_url_version = match &self.signed_url_version {
+            Some(v) => v.parse::<GcsSignedUrlVersion>().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Gcs)
+            })?,
+            None => GcsSignedUrlVersion::default(),
+        };
+
         let signer = GoogleSigner::new("storage");
 
         let backend = GcsBackend {
@@ -291,8 +841,35 @@ impl Builder for GcsBuilder {
                 signer,
                 token_loader,
                 credential_loader: cred_loader,
+                credential: self.credential.clone(),
+                credential_path: self.credential_path.clone(),
                 predefined_acl: self.predefined_acl.clone(),
                 default_storage_class: self.default_storage_class.clone(),
+                kms_key_name: self.kms_key_name.clone(),
+                default_content_disposition: self.default_content_disposition.clone(),
+                disable_implicit_dir: self.disable_implicit_dir,
+                disable_dir_marker: self.disable_dir_marker,
+                read_timeout: self.read_timeout,
+                host,
+                detect_dir_by_list: self.detect_dir_by_list,
+                api_version: self
+                    .api_version
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_GCS_API_VERSION.to_string()),
+                quota_project,
+                error_on_missing_delete: self.error_on_missing_delete,
+                batch_boundary: self.batch_boundary.clone(),
+                check_bucket: self.check_bucket,
+                bucket_checker: OnceCell::new(),
+                enable_content_md5: self.enable_content_md5,
+                verify_read_md5: self.verify_read_md5,
+                enable_crc32c_check: self.enable_crc32c_check,
+                multipart_upload_threshold: self.multipart_upload_threshold,
+                max_object_size: self.max_object_size,
+                signed_url_version,
+                encryption_key,
+                encryption_key_sha256,
+                metrics_hook: self.metrics_hook.clone(),
             }),
         };
 
@@ -301,83 +878,494 @@ impl Builder for GcsBuilder {
 }
 
 /// GCS storage backend
+///
+/// Most users should go through [`Operator`](crate::Operator) which is built
+/// from [`Gcs`](crate::services::Gcs). The backend is exported for the GCS
+/// specific operations that have no [`Operator`](crate::Operator) counterpart,
+/// like [`GcsBackend::stat_many`] or [`GcsBackend::conditional_stat`]. Obtain
+/// it via `Builder::build()`.
+///
+/// # Notes
+///
+/// The inherent methods on this type are part of OpenDAL's Raw API. They talk
+/// to GCS directly and bypass every layer, including retry, logging, metrics
+/// and the capability checks. They could be changed during minor updates.
 #[derive(Clone, Debug)]
 pub struct GcsBackend {
     core: Arc<GcsCore>,
 }
 
-#[async_trait]
-impl Accessor for GcsBackend {
-    type Reader = IncomingAsyncBody;
-    type BlockingReader = ();
-    type Writer = GcsWriters;
-    type BlockingWriter = ();
-    type Pager = GcsPager;
-    type BlockingPager = ();
+/// Kind of a path returned by [`GcsBackend::probe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcsPathKind {
+    /// An object exists at the path.
+    File,
+    /// The path is a prefix with objects or a directory marker under it.
+    Dir,
+    /// Neither an object nor a prefix exists at the path.
+    NotFound,
+}
 
-    fn info(&self) -> AccessorInfo {
-        let mut am = AccessorInfo::default();
-        am.set_scheme(Scheme::Gcs)
-            .set_root(&self.core.root)
-            .set_name(&self.core.bucket)
-            .set_native_capability(Capability {
-                create_dir: true,
+/// Metadata of a path returned by [`GcsBackend::conditional_stat`].
+///
+/// It carries gcs specific fields of the object which are not part of
+/// [`Metadata`].
+#[derive(Debug, Clone)]
+pub struct GcsMetadata {
+    metadata: Metadata,
+    component_count: Option<u32>,
+    kms_key_name: Option<String>,
+    metageneration: Option<u64>,
+}
 
-                stat: true,
-                stat_with_if_match: true,
-                stat_with_if_none_match: true,
+impl GcsMetadata {
+    fn new(metadata: Metadata) -> Self {
+        GcsMetadata {
+            metadata,
+            component_count: None,
+            kms_key_name: None,
+            metageneration: None,
+        }
+    }
 
-                read: true,
-                read_can_next: true,
-                read_with_range: true,
-                read_with_if_match: true,
-                read_with_if_none_match: true,
+    /// Metadata of the path.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
 
-                write: true,
-                write_can_empty: true,
-                write_can_multi: true,
-                write_with_content_type: true,
-                // The buffer size should be a multiple of 256 KiB (256 x 1024 bytes), unless it's the last chunk that completes the upload.
-                // Larger chunk sizes typically make uploads faster, but note that there's a tradeoff between speed and memory usage.
-                // It's recommended that you use at least 8 MiB for the chunk size.
-                //
-                // Reference: [Perform resumable uploads](https://cloud.google.com/storage/docs/performing-resumable-uploads)
-                write_multi_align_size: Some(256 * 1024 * 1024),
+    /// Consume self to get the metadata of the path.
+    pub fn into_metadata(self) -> Metadata {
+        self.metadata
+    }
 
-                delete: true,
-                copy: true,
+    /// Number of source objects that compose the object.
+    ///
+    /// A value larger than 1 means the object is a composite object, whose
+    /// `content_md5` is not available.
+    pub fn component_count(&self) -> Option<u32> {
+        self.component_count
+    }
 
-                list: true,
-                list_with_limit: true,
-                list_with_start_after: true,
-                list_without_recursive: true,
-                list_with_recursive: true,
+    /// Name of the Cloud KMS key that encrypts the object, like
+    /// `projects/p/locations/l/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1`.
+    pub fn kms_key_name(&self) -> Option<&str> {
+        self.kms_key_name.as_deref()
+    }
 
-                batch: true,
-                batch_max_operations: Some(100),
-                presign: true,
-                presign_stat: true,
-                presign_read: true,
-                presign_write: true,
+    /// Metageneration of the object.
+    ///
+    /// It's changed while updating the metadata of the object, and stays the
+    /// same while the generation, a.k.a. [`Metadata::version`], is unchanged.
+    pub fn metageneration(&self) -> Option<u64> {
+        self.metageneration
+    }
+}
 
-                ..Default::default()
-            });
-        am
+/// Result of [`GcsBackend::conditional_stat`].
+#[derive(Debug, Clone)]
+pub enum GcsStatResult {
+    /// Metadata of the path.
+    Metadata(Box<GcsMetadata>),
+    /// The object's etag matches `If-None-Match`, so it's not modified.
+    NotModified,
+}
+
+impl GcsBackend {
+    /// Stat multiple paths concurrently.
+    ///
+    /// At most `concurrent` stat requests will be in flight at the same time.
+    /// The results are returned in the same order as `paths`, one for each
+    /// path even if it's duplicated. A missing path will be returned as an
+    /// error with `ErrorKind::NotFound`.
+    pub async fn stat_many(&self, paths: Vec<String>, concurrent: usize) -> Vec<Result<Metadata>> {
+        stream::iter(paths)
+            .map(|path| async move {
+                self.stat(&normalize_path(&path), OpStat::new())
+                    .await
+                    .map(|rp| rp.into_metadata())
+            })
+            .buffered(concurrent.max(1))
+            .collect()
+            .await
     }
 
-    async fn create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
-        let mut req = self.core.gcs_insert_object_request(
-            path,
-            Some(0),
-            &OpWrite::default(),
-            AsyncBody::Empty,
-        )?;
+    /// Stat the path, honoring `If-None-Match` of `args`.
+    ///
+    /// Unlike `stat` which fails with `ErrorKind::ConditionNotMatch`,
+    /// [`GcsStatResult::NotModified`] will be returned if the object's etag
+    /// matches, so that caches could revalidate their entries cheaply.
+    pub async fn conditional_stat(&self, path: &str, args: OpStat) -> Result<GcsStatResult> {
+        let path = normalize_path(path);
+        let path = path.as_str();
 
-        self.core.sign(&mut req).await?;
+        // Stat root always returns a DIR.
+        if path == "/" {
+            return Ok(GcsStatResult::Metadata(Box::new(GcsMetadata::new(
+                Metadata::new(EntryMode::DIR),
+            ))));
+        }
 
-        let resp = self.core.send(req).await?;
+        if path.ends_with('/') && self.core.detect_dir_by_list {
+            return self
+                .stat_dir_by_list(path)
+                .await
+                .map(|rp| GcsStatResult::Metadata(Box::new(GcsMetadata::new(rp.into_metadata()))));
+        }
 
-        if resp.status().is_success() {
+        let resp = self.core.gcs_get_object_metadata(path, &args).await?;
+
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            resp.into_body().consume().await?;
+            Ok(GcsStatResult::NotModified)
+        } else if resp.status().is_success() {
+            // read http response body
+            let slc = resp.into_body().bytes().await?;
+
+            let meta: GetObjectJsonResponse =
+                serde_json::from_slice(&slc).map_err(new_json_deserialize_error)?;
+
+            let size = meta
+                .size
+                .parse::<u64>()
+                .map_err(|e| Error::new(ErrorKind::Unexpected, "parse u64").set_source(e))?;
+
+            // The object exists, so it's a file even if its name ends with
+            // `/`, unless it's a directory marker created by us.
+            let mode = if path.ends_with('/') && meta.content_type == DIR_MARKER_CONTENT_TYPE {
+                EntryMode::DIR
+            } else {
+                EntryMode::FILE
+            };
+            let mut m = Metadata::new(mode);
+
+            m.set_etag(normalize_etag(&meta.etag));
+            m.set_content_md5(&meta.md5_hash);
+            m.set_content_length(size);
+            if !meta.content_type.is_empty() {
+                m.set_content_type(&meta.content_type);
+            }
+
+            m.set_last_modified(parse_datetime_from_rfc3339(&meta.updated)?);
+
+            // The version of gcs objects is the generation.
+            if !meta.generation.is_empty() {
+                let generation = meta.generation.parse::<u64>().map_err(|e| {
+                    Error::new(ErrorKind::Unexpected, "parse generation").set_source(e)
+                })?;
+                m.set_version(&generation.to_string());
+            }
+
+            let mut m = GcsMetadata::new(m);
+            if !meta.metageneration.is_empty() {
+                let metageneration = meta.metageneration.parse::<u64>().map_err(|e| {
+                    Error::new(ErrorKind::Unexpected, "parse metageneration").set_source(e)
+                })?;
+                m.metageneration = Some(metageneration);
+            }
+            m.component_count = meta.component_count;
+            m.kms_key_name = meta.kms_key_name;
+
+            Ok(GcsStatResult::Metadata(Box::new(m)))
+        } else if resp.status() == StatusCode::NOT_FOUND
+            && path.ends_with('/')
+            && !self.core.disable_implicit_dir
+            && args.version().is_none()
+        {
+            Ok(GcsStatResult::Metadata(Box::new(GcsMetadata::new(
+                Metadata::new(EntryMode::DIR),
+            ))))
+        } else {
+            Err(parse_error(resp).await?)
+        }
+    }
+
+    /// Create the object only if it doesn't exist.
+    ///
+    /// The object is uploaded in a single request with `ifGenerationMatch=0`,
+    /// so an error with `ErrorKind::AlreadyExists` will be returned if the
+    /// object exists, even if it's created concurrently by others.
+    pub async fn create_if_absent(&self, path: &str, body: Bytes) -> Result<()> {
+        let path = normalize_path(path);
+        let mut req = self.core.gcs_insert_object_request_with_generation(
+            &path,
+            Some(body.len() as u64),
+            &OpWrite::default(),
+            Some(0),
+            AsyncBody::Bytes(body),
+        )?;
+
+        self.core.sign(&mut req).await?;
+
+        let resp = self.core.send(req).await?;
+
+        match resp.status() {
+            StatusCode::OK | StatusCode::CREATED => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            StatusCode::PRECONDITION_FAILED => {
+                let err = parse_error(resp).await?;
+                Err(
+                    Error::new(ErrorKind::AlreadyExists, "object already exists")
+                        .with_operation("GcsBackend::create_if_absent")
+                        .with_context("path", &path)
+                        .set_source(err),
+                )
+            }
+            _ => Err(parse_error(resp)
+                .await?
+                .with_operation("GcsBackend::create_if_absent")
+                .with_context("path", &path)),
+        }
+    }
+
+    /// Read the whole object via a presigned url.
+    ///
+    /// The url is generated like `presign` with `expire`, and then fetched
+    /// by the http client without any credentials, which checks that the
+    /// presigned urls of this backend work end to end.
+    pub async fn read_via_presigned(&self, path: &str, expire: Duration) -> Result<Bytes> {
+        let path = normalize_path(path);
+        let req: Request<AsyncBody> = self
+            .presign(&path, OpPresign::new(OpRead::new(), expire))
+            .await?
+            .into_presigned_request()
+            .into();
+
+        let resp = self.core.client.send(req).await?;
+
+        match resp.status() {
+            StatusCode::OK => resp.into_body().bytes().await,
+            _ => Err(parse_error(resp)
+                .await?
+                .with_operation("GcsBackend::read_via_presigned")
+                .with_context("path", &path)),
+        }
+    }
+
+    /// Tell whether the path is a file, a dir or missing.
+    ///
+    /// Unlike `stat` which decides by the trailing `/` of the path, an object
+    /// named `path` without the trailing `/` is fetched first, and then
+    /// objects under `path/` are listed with delimiter `/`. So a prefix with
+    /// children will be reported as `Dir`, and `NotFound` is only returned
+    /// if there is nothing at all.
+    pub async fn probe(&self, path: &str) -> Result<GcsPathKind> {
+        let path = normalize_path(path);
+        let name = path.trim_end_matches('/');
+        // Root always exists.
+        if name.is_empty() {
+            return Ok(GcsPathKind::Dir);
+        }
+
+        let resp = self
+            .core
+            .gcs_get_object_metadata(name, &OpStat::new())
+            .await?;
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                return Ok(GcsPathKind::File);
+            }
+            StatusCode::NOT_FOUND => resp.into_body().consume().await?,
+            _ => {
+                return Err(parse_error(resp)
+                    .await?
+                    .with_operation("GcsBackend::probe")
+                    .with_context("path", &path))
+            }
+        }
+
+        match self.stat_dir_by_list(&format!("{name}/")).await {
+            Ok(_) => Ok(GcsPathKind::Dir),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(GcsPathKind::NotFound),
+            Err(err) => Err(err
+                .with_operation("GcsBackend::probe")
+                .with_context("path", &path)),
+        }
+    }
+
+    /// Check the bucket once before creating objects if `check_bucket` is
+    /// enabled.
+    async fn ensure_bucket_checked(&self) -> Result<()> {
+        if self.core.check_bucket {
+            self.core
+                .bucket_checker
+                .get_or_try_init(|| self.check_bucket())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Make sure the bucket's configuration is compatible with ours.
+    async fn check_bucket(&self) -> Result<()> {
+        let resp = self.core.gcs_get_bucket().await?;
+        if resp.status() != StatusCode::OK {
+            return Err(parse_error(resp).await?);
+        }
+
+        let bs = resp.into_body().bytes().await?;
+        let bucket: GetBucketJsonResponse =
+            serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+        if bucket.iam_configuration.uniform_bucket_level_access.enabled {
+            if let Some(acl) = &self.core.predefined_acl {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "predefined_acl is not allowed since ACLs are disabled by uniform bucket-level access on the bucket",
+                )
+                .with_operation("GcsBackend::check_bucket")
+                .with_context("bucket", &self.core.bucket)
+                .with_context("predefined_acl", acl));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stat a dir by listing at most one object under it.
+    async fn stat_dir_by_list(&self, path: &str) -> Result<RpStat> {
+        let resp = self
+            .core
+            .gcs_list_objects(path, "", "/", Some(1), None, false)
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(parse_error(resp).await?);
+        }
+
+        let bs = resp.into_body().bytes().await?;
+        let output: ListResponse =
+            serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+        if output.items.is_empty() && output.prefixes.is_empty() {
+            Err(Error::new(ErrorKind::NotFound, "dir not found").with_context("path", path))
+        } else {
+            Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+        }
+    }
+
+    /// Generate a signed [POST policy](https://cloud.google.com/storage/docs/xml-api/post-object-forms)
+    /// for direct uploads from browsers via HTML forms.
+    ///
+    /// Uploaded object's key must start with `key_prefix`, its content type
+    /// must start with `content_type_prefix` and its size must not exceed
+    /// `max_size` if they are set.
+    ///
+    /// The returned form fields must be sent along with the file. Please note
+    /// that only service account credentials can sign the policy.
+    pub fn presign_post(
+        &self,
+        key_prefix: &str,
+        content_type_prefix: Option<&str>,
+        max_size: Option<u64>,
+        expire: Duration,
+    ) -> Result<HashMap<String, String>> {
+        let key_prefix = build_abs_path(&self.core.root, key_prefix);
+        let policy = PostPolicy {
+            bucket: &self.core.bucket,
+            key_prefix: &key_prefix,
+            content_type_prefix,
+            max_size,
+        };
+
+        self.core.sign_post_policy(&policy, expire)
+    }
+}
+
+#[async_trait]
+impl Accessor for GcsBackend {
+    type Reader = GcsReader;
+    type BlockingReader = ();
+    type Writer = GcsWriters;
+    type BlockingWriter = ();
+    type Pager = GcsPager;
+    type BlockingPager = ();
+
+    fn info(&self) -> AccessorInfo {
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Gcs)
+            .set_root(&self.core.root)
+            .set_name(&self.core.bucket)
+            .set_native_capability(Capability {
+                create_dir: true,
+
+                stat: true,
+                stat_with_if_match: true,
+                stat_with_if_none_match: true,
+                stat_with_version: true,
+
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_if_match: true,
+                read_with_if_none_match: true,
+                read_with_identity_encoding: true,
+                read_with_version: true,
+
+                write: true,
+                write_can_empty: true,
+                write_can_multi: true,
+                write_with_content_type: true,
+                write_with_content_disposition: true,
+                // `If-Match` is translated into `ifGenerationMatch`, see
+                // `parse_generation_from_etag` for details.
+                write_with_if_match: true,
+                // The buffer size should be a multiple of 256 KiB (256 x 1024 bytes), unless it's the last chunk that completes the upload.
+                // Larger chunk sizes typically make uploads faster, but note that there's a tradeoff between speed and memory usage.
+                // It's recommended that you use at least 8 MiB for the chunk size.
+                //
+                // Reference: [Perform resumable uploads](https://cloud.google.com/storage/docs/performing-resumable-uploads)
+                write_multi_align_size: Some(256 * 1024 * 1024),
+
+                delete: true,
+                copy: true,
+                copy_with_content_type: true,
+                copy_with_cache_control: true,
+                copy_with_source_version: true,
+                copy_with_reset_metadata: true,
+                rename: true,
+
+                list: true,
+                list_with_limit: true,
+                list_with_start_after: true,
+                list_without_recursive: true,
+                list_with_recursive: true,
+                list_with_soft_deleted: true,
+                list_with_entry_mode: true,
+
+                batch: true,
+                batch_max_operations: Some(100),
+                presign: true,
+                presign_stat: true,
+                presign_read: true,
+                presign_write: true,
+
+                ..Default::default()
+            });
+        am
+    }
+
+    async fn create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
+        if self.core.disable_dir_marker {
+            return Ok(RpCreateDir::default());
+        }
+
+        self.ensure_bucket_checked().await?;
+
+        // Write a zero-byte marker object for the directory.
+        let mut req = self.core.gcs_insert_object_request(
+            path,
+            Some(0),
+            &OpWrite::default().with_content_type(DIR_MARKER_CONTENT_TYPE),
+            AsyncBody::Empty,
+        )?;
+
+        self.core.sign(&mut req).await?;
+
+        let resp = self.core.send(req).await?;
+
+        if resp.status().is_success() {
             resp.into_body().consume().await?;
             Ok(RpCreateDir::default())
         } else {
@@ -389,82 +1377,152 @@ impl Accessor for GcsBackend {
         let resp = self.core.gcs_get_object(path, &args).await?;
 
         if resp.status().is_success() {
-            let size = parse_content_length(resp.headers())?;
-            Ok((RpRead::new().with_size(size), resp.into_body()))
+            // GCS could still transcode even if we only accept identity
+            // encoding, return an error instead of the changed content.
+            if args.identity_encoding() && is_transcoded(resp.headers())? {
+                resp.into_body().consume().await?;
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "object is transcoded by gcs, stored bytes can't be read exactly",
+                )
+                .with_operation(Operation::Read)
+                .with_context("path", path));
+            }
+
+            let headers = resp.headers();
+            let size = parse_content_length(headers)?;
+            let hash = parse_goog_hash(headers)?;
+            let generation = parse_generation(headers)?;
+
+            // Only the whole and untouched content could be verified.
+            let verifiable = resp.status() == StatusCode::OK && !is_transcoded(headers)?;
+            let expected_md5 = if self.core.verify_read_md5 && verifiable {
+                match &hash.md5 {
+                    Some(v) => Some(v.clone()),
+                    None => parse_content_md5(headers)?.map(String::from),
+                }
+            } else {
+                None
+            };
+            let expected_crc32c = if self.core.enable_crc32c_check && verifiable {
+                hash.crc32c.clone()
+            } else {
+                None
+            };
+
+            let rp = RpRead::new()
+                .with_size(size)
+                .with_content_md5(hash.md5)
+                .with_content_crc32c(hash.crc32c)
+                .with_content_type(parse_content_type(headers)?.map(String::from))
+                .with_etag(parse_etag(headers)?.map(|v| normalize_etag(v).to_string()))
+                .with_content_disposition(parse_content_disposition(headers)?.map(String::from))
+                .with_version(generation.map(String::from));
+            let r = GcsReader::new(resp.into_body(), self.core.metrics_hook.clone())
+                .with_read_timeout(self.core.read_timeout)
+                .with_content_length(size)
+                .with_expected_md5(expected_md5)
+                .with_expected_crc32c(expected_crc32c);
+            Ok((rp, r))
         } else if resp.status() == StatusCode::RANGE_NOT_SATISFIABLE {
-            Ok((RpRead::new(), IncomingAsyncBody::empty()))
+            Ok((
+                RpRead::new(),
+                GcsReader::new(IncomingAsyncBody::empty(), None),
+            ))
         } else {
-            Err(parse_error(resp).await?)
+            // GCS returns `404` if the requested generation doesn't exist or
+            // has been purged, which is mapped to `NotFound`.
+            let err = parse_error(resp).await?;
+            match args.version() {
+                Some(v) => Err(err.with_context("version", v)),
+                None => Err(err),
+            }
         }
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.ensure_bucket_checked().await?;
+
         let w = GcsWriter::new(self.core.clone(), path, args);
+        let rp = RpWrite::new().with_metadata(w.committed());
+
         let w = oio::RangeWriter::new(w);
+        let w = match self.core.multipart_upload_threshold {
+            Some(threshold) => oio::TwoWaysWriter::Two(oio::ExactBufWriter::new(w, threshold)),
+            None => oio::TwoWaysWriter::One(w),
+        };
 
-        Ok((RpWrite::default(), w))
+        Ok((rp, w))
     }
 
-    async fn copy(&self, from: &str, to: &str, _: OpCopy) -> Result<RpCopy> {
-        let resp = self.core.gcs_copy_object(from, to).await?;
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.ensure_bucket_checked().await?;
+
+        let resp = self.core.gcs_copy_object(from, to, &args).await?;
 
         if resp.status().is_success() {
             resp.into_body().consume().await?;
             Ok(RpCopy::default())
         } else {
-            Err(parse_error(resp).await?)
+            // GCS returns `404` if the pinned source generation doesn't exist
+            // anymore (mapped to `NotFound`), and `412` for failed
+            // preconditions (mapped to `ConditionNotMatch`).
+            let err = parse_error(resp).await?;
+            match args.source_version() {
+                Some(v) => Err(err.with_context("source_version", v)),
+                None => Err(err),
+            }
         }
     }
 
-    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
-        // Stat root always returns a DIR.
-        if path == "/" {
-            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+    /// GCS doesn't support rename natively, so we emulate it via copy then delete.
+    ///
+    /// The source will only be deleted after copy succeeded, so a failed copy
+    /// will leave the source intact.
+    async fn rename(&self, from: &str, to: &str, _: OpRename) -> Result<RpRename> {
+        if from == to {
+            return Ok(RpRename::default());
         }
 
-        let resp = self.core.gcs_get_object_metadata(path, &args).await?;
-
-        if resp.status().is_success() {
-            // read http response body
-            let slc = resp.into_body().bytes().await?;
-
-            let meta: GetObjectJsonResponse =
-                serde_json::from_slice(&slc).map_err(new_json_deserialize_error)?;
+        self.ensure_bucket_checked().await?;
 
-            let mode = if path.ends_with('/') {
-                EntryMode::DIR
-            } else {
-                EntryMode::FILE
-            };
-            let mut m = Metadata::new(mode);
-
-            m.set_etag(&meta.etag);
-            m.set_content_md5(&meta.md5_hash);
+        let resp = self.core.gcs_copy_object(from, to, &OpCopy::new()).await?;
 
-            let size = meta
-                .size
-                .parse::<u64>()
-                .map_err(|e| Error::new(ErrorKind::Unexpected, "parse u64").set_source(e))?;
-            m.set_content_length(size);
-            if !meta.content_type.is_empty() {
-                m.set_content_type(&meta.content_type);
-            }
+        if resp.status().is_success() {
+            resp.into_body().consume().await?;
+        } else {
+            return Err(parse_error(resp).await?);
+        }
 
-            m.set_last_modified(parse_datetime_from_rfc3339(&meta.updated)?);
+        let resp = self.core.gcs_delete_object(from).await?;
 
-            Ok(RpStat::new(m))
-        } else if resp.status() == StatusCode::NOT_FOUND && path.ends_with('/') {
-            Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+        // deleting not existing objects is ok
+        if resp.status().is_success() || resp.status() == StatusCode::NOT_FOUND {
+            resp.into_body().consume().await?;
+            Ok(RpRename::default())
         } else {
             Err(parse_error(resp).await?)
         }
     }
 
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        match self.conditional_stat(path, args).await? {
+            GcsStatResult::Metadata(m) => Ok(RpStat::new(m.into_metadata())),
+            GcsStatResult::NotModified => Err(Error::new(
+                ErrorKind::ConditionNotMatch,
+                "object is not modified",
+            )
+            .with_context("path", path)),
+        }
+    }
+
     async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
         let resp = self.core.gcs_delete_object(path).await?;
 
-        // deleting not existing objects is ok
-        if resp.status().is_success() || resp.status() == StatusCode::NOT_FOUND {
+        // deleting not existing objects is ok unless users want to know it.
+        if resp.status().is_success()
+            || (resp.status() == StatusCode::NOT_FOUND && !self.core.error_on_missing_delete)
+        {
             Ok(RpDelete::default())
         } else {
             Err(parse_error(resp).await?)
@@ -480,7 +1538,9 @@ impl Accessor for GcsBackend {
                 args.recursive(),
                 args.limit(),
                 args.start_after(),
-            ),
+                args.soft_deleted(),
+            )
+            .with_entry_mode(args.entry_mode()),
         ))
     }
 
@@ -506,15 +1566,25 @@ impl Accessor for GcsBackend {
                     "gcs batch delete response content type is empty",
                 )
             })?;
-            let boundary = content_type
-                .strip_prefix("multipart/mixed; boundary=")
+            // Proxies could rewrite the content type, so we should tolerate
+            // case and whitespace differences here.
+            let mut params = content_type.split(';');
+            let boundary = params
+                .next()
+                .filter(|v| v.trim().eq_ignore_ascii_case("multipart/mixed"))
+                .and_then(|_| {
+                    params
+                        .filter_map(|v| v.split_once('='))
+                        .find(|(k, _)| k.trim().eq_ignore_ascii_case("boundary"))
+                })
+                .map(|(_, v)| v.trim().trim_matches('"'))
                 .ok_or_else(|| {
                     Error::new(
                         ErrorKind::Unexpected,
                         "gcs batch delete response content type is not multipart/mixed",
                     )
-                })?
-                .trim_matches('"');
+                    .with_context("content_type", content_type)
+                })?;
             let multipart: Multipart<MixedPart> = Multipart::new()
                 .with_boundary(boundary)
                 .parse(resp.into_body().bytes().await?)?;
@@ -527,11 +1597,16 @@ impl Accessor for GcsBackend {
                 // TODO: maybe we can take it directly?
                 let path = paths[i].clone();
 
-                // deleting not existing objects is ok
-                if resp.status().is_success() || resp.status() == StatusCode::NOT_FOUND {
+                // deleting not existing objects is ok unless users want to know it.
+                if resp.status().is_success()
+                    || (resp.status() == StatusCode::NOT_FOUND
+                        && !self.core.error_on_missing_delete)
+                {
                     batched_result.push((path, Ok(RpDelete::default().into())));
                 } else {
-                    batched_result.push((path, Err(parse_error(resp).await?)));
+                    // The body of sub-response carries the gcs error detail.
+                    let err = parse_error(resp).await?.with_context("path", &path);
+                    batched_result.push((path, Err(err)));
                 }
             }
 
@@ -567,6 +1642,16 @@ impl Accessor for GcsBackend {
     }
 }
 
+/// Check the boundary against `bchars` of RFC 2046: 1 to 70 characters of
+/// digits, letters and `'()+_,-./:=? `, and not ending with space.
+fn is_valid_boundary(boundary: &str) -> bool {
+    (1..=70).contains(&boundary.len())
+        && !boundary.ends_with(' ')
+        && boundary
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&b))
+}
+
 /// The raw json response returned by [`get`](https://cloud.google.com/storage/docs/json_api/v1/objects/get)
 #[derive(Debug, Default, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
@@ -591,16 +1676,2514 @@ struct GetObjectJsonResponse {
     ///
     /// For example: `"contentType": "image/png",`
     content_type: String,
+    /// Number of underlying components that make up this object.
+    ///
+    /// Only returned for composite objects, for example: `"componentCount": 2`
+    component_count: Option<u32>,
+    /// Generation of the content, returned in string.
+    ///
+    /// For example: `"generation": "1660563214863653"`
+    generation: String,
+    /// Generation of the metadata, returned in string.
+    ///
+    /// For example: `"metageneration": "2"`
+    metageneration: String,
+    /// Cloud KMS key used to encrypt this object.
+    ///
+    /// Only returned for objects encrypted with a customer-managed key, for
+    /// example: `"kmsKeyName": "projects/p/locations/l/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1"`
+    kms_key_name: Option<String>,
+}
+
+/// The raw json response returned by [`get`](https://cloud.google.com/storage/docs/json_api/v1/buckets/get)
+///
+/// Only fields we care about are kept.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GetBucketJsonResponse {
+    iam_configuration: BucketIamConfiguration,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct BucketIamConfiguration {
+    uniform_bucket_level_access: BucketUniformBucketLevelAccess,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BucketUniformBucketLevelAccess {
+    enabled: bool,
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io::Read;
+    use std::io::Write;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering;
+
+    use anyhow::Result;
+    use base64::prelude::BASE64_STANDARD;
+    use base64::Engine;
+    use chrono::Utc;
+    use futures::TryStreamExt;
+    use http::Method;
+    use reqsign::GoogleToken;
+    use rsa::pkcs1v15::Signature;
+    use rsa::pkcs1v15::VerifyingKey;
+    use rsa::pkcs8::DecodePrivateKey;
+    use rsa::sha2::Digest;
+    use rsa::sha2::Sha256;
+    use rsa::signature::Verifier;
+    use rsa::RsaPrivateKey;
+    use serde_json::json;
+    use wiremock::matchers::any;
+    use wiremock::matchers::body_json;
+    use wiremock::matchers::body_string;
+    use wiremock::matchers::body_string_contains;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
     use super::*;
+    use crate::layers::RetryLayer;
+    use crate::raw::oio::RangeWrite;
+    use crate::services::gcs::post_policy::tests::TEST_PRIVATE_KEY;
 
-    #[test]
-    fn test_deserialize_get_object_json_response() {
-        let content = r#"{
-  "kind": "storage#object",
+    /// Token loader that always returns a static token so that tests don't
+    /// need real credentials.
+    #[derive(Debug)]
+    struct StaticTokenLoader;
+
+    #[async_trait]
+    impl GoogleTokenLoad for StaticTokenLoader {
+        async fn load(&self, _: reqwest::Client) -> Result<Option<GoogleToken>> {
+            Ok(Some(GoogleToken::new(
+                "test-token",
+                3600,
+                DEFAULT_GCS_SCOPE,
+            )))
+        }
+    }
+
+    fn new_test_builder(endpoint: &str) -> GcsBuilder {
+        let mut builder = GcsBuilder::default();
+        builder
+            .bucket("test")
+            .endpoint(endpoint)
+            .customed_token_loader(Box::new(StaticTokenLoader));
+        builder
+    }
+
+    fn new_test_operator(endpoint: &str) -> Result<Operator> {
+        Ok(Operator::new(new_test_builder(endpoint))?.finish())
+    }
+
+    #[tokio::test]
+    async fn test_stat_implicit_dir() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/dir%2F"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        let meta = op.stat("dir/").await?;
+        assert!(meta.is_dir());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_object_ends_with_slash() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file%2F"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "file/",
+                "size": "13",
+                "contentType": "application/octet-stream",
+                "updated": "2022-08-15T11:33:34.866Z"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/empty%2F"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "empty/",
+                "size": "0",
+                "contentType": "application/octet-stream",
+                "updated": "2022-08-15T11:33:34.866Z"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/dir%2F"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "dir/",
+                "size": "0",
+                "contentType": DIR_MARKER_CONTENT_TYPE,
+                "updated": "2022-08-15T11:33:34.866Z"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+
+        let meta = op.stat("file/").await?;
+        assert!(meta.is_file());
+        assert_eq!(meta.content_length(), 13);
+
+        let meta = op.stat("empty/").await?;
+        assert!(meta.is_file());
+        assert_eq!(meta.content_length(), 0);
+
+        let meta = op.stat("dir/").await?;
+        assert!(meta.is_dir());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_prefix() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "data/tenant-a/dir/file"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/data%2Ftenant-a%2Fdir%2Ffile"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "data/tenant-a/dir/file",
+                "size": "13",
+                "updated": "2022-08-15T11:33:34.866Z"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.root("/data").prefix("tenant-a/");
+        let op = Operator::new(builder)?.finish();
+
+        op.write("dir/file", "Hello, World!").await?;
+        let meta = op.stat("dir/file").await?;
+        assert_eq!(meta.content_length(), 13);
+
+        for prefix in ["..", "../tenant-b", "tenant-a/../tenant-b", "./tenant-a"] {
+            let mut builder = new_test_builder(&mock_server.uri());
+            builder.root("/data").prefix(prefix);
+            let err = builder.build().expect_err("prefix must be rejected");
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "prefix: {prefix}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_implicit_dir_disabled() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/dir%2F"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.disable_implicit_dir();
+        let op = Operator::new(builder)?.finish();
+
+        let err = op.stat("dir/").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_if_absent() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "new"))
+            .and(query_param("ifGenerationMatch", "0"))
+            .and(body_string("Hello, World!"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "existing"))
+            .and(query_param("ifGenerationMatch", "0"))
+            .respond_with(ResponseTemplate::new(412).set_body_string(
+                r#"{"error": {"code": 412, "message": "At least one of the pre-conditions you specified did not hold.", "errors": [{"reason": "conditionNotMet"}]}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        backend
+            .create_if_absent("new", Bytes::from("Hello, World!"))
+            .await?;
+        let err = backend
+            .create_if_absent("existing", Bytes::from("Hello, World!"))
+            .await
+            .expect_err("create existing object must fail");
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_probe() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "file", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        for name in ["dir", "missing"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/storage/v1/b/test/o/{name}")))
+                .respond_with(ResponseTemplate::new(404))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o"))
+            .and(query_param("prefix", "dir/"))
+            .and(query_param("delimiter", "/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"kind": "storage#objects", "prefixes": ["dir/sub/"]}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o"))
+            .and(query_param("prefix", "missing/"))
+            .and(query_param("delimiter", "/"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"kind": "storage#objects"}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        assert_eq!(backend.probe("file").await?, GcsPathKind::File);
+        // The trailing `/` doesn't matter.
+        assert_eq!(backend.probe("dir/").await?, GcsPathKind::Dir);
+        assert_eq!(backend.probe("missing").await?, GcsPathKind::NotFound);
+        assert_eq!(backend.probe("/").await?, GcsPathKind::Dir);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conditional_stat_with_component_count() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/composite"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "composite",
+                "size": "42",
+                "componentCount": 3,
+                "updated": "2022-08-15T11:33:34.866Z"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        match backend.conditional_stat("composite", OpStat::new()).await? {
+            GcsStatResult::Metadata(m) => {
+                assert_eq!(m.component_count(), Some(3));
+                assert_eq!(m.metadata().content_length(), 42);
+            }
+            GcsStatResult::NotModified => panic!("stat without condition must return metadata"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_if_none_match() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(header("If-None-Match", "CKWasoTgyPkCEAE="))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "file", "size": "42", "etag": "CKWasoTgyPkCEAI=", "updated": "2022-08-15T11:33:34.866Z"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        let res = backend
+            .conditional_stat("file", OpStat::new().with_if_none_match("CKWasoTgyPkCEAE="))
+            .await?;
+        assert!(matches!(res, GcsStatResult::NotModified));
+        let res = backend
+            .conditional_stat("file", OpStat::new().with_if_none_match("CKWasoTgyPkCEAI="))
+            .await?;
+        match res {
+            GcsStatResult::Metadata(m) => {
+                assert_eq!(m.metadata().etag(), Some("CKWasoTgyPkCEAI="))
+            }
+            GcsStatResult::NotModified => panic!("etag doesn't match"),
+        }
+
+        // `stat` reports not modified as an error.
+        let err = backend
+            .stat("file", OpStat::new().with_if_none_match("CKWasoTgyPkCEAE="))
+            .await
+            .expect_err("stat of not modified object must fail");
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_detect_dir_by_list() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o"))
+            .and(query_param("prefix", "dir/"))
+            .and(query_param("delimiter", "/"))
+            .and(query_param("maxResults", "1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"kind": "storage#objects", "prefixes": ["dir/sub/"]}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o"))
+            .and(query_param("prefix", "empty/"))
+            .and(query_param("delimiter", "/"))
+            .and(query_param("maxResults", "1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"kind": "storage#objects"}"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.detect_dir_by_list();
+        let op = Operator::new(builder)?.finish();
+
+        let meta = op.stat("dir/").await?;
+        assert!(meta.is_dir());
+
+        let err = op.stat("empty/").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_soft_deleted() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o"))
+            .and(query_param("prefix", "dir/"))
+            .and(query_param("softDeleted", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+  "kind": "storage#objects",
+  "items": [
+    {
+      "name": "dir/a",
+      "size": "42",
+      "etag": "CKWasoTgyPkCEAE=",
+      "md5Hash": "fHcEH1vPwA6eTPqxuasXcg==",
+      "updated": "2022-08-15T11:33:34.866Z",
+      "softDeleteTime": "2023-11-01T10:00:00.000Z",
+      "hardDeleteTime": "2023-11-08T10:00:00.000Z"
+    }
+  ]
+}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+        let entries = op
+            .list_with("dir/")
+            .soft_deleted(true)
+            .metakey(Metakey::SoftDeleteTime | Metakey::HardDeleteTime)
+            .await?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), "dir/a");
+
+        let meta = entries[0].metadata();
+        assert_eq!(
+            meta.soft_delete_time(),
+            Some(parse_datetime_from_rfc3339("2023-11-01T10:00:00.000Z")?)
+        );
+        assert_eq!(
+            meta.hard_delete_time(),
+            Some(parse_datetime_from_rfc3339("2023-11-08T10:00:00.000Z")?)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_marker() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "dir/"))
+            .and(header("content-length", "0"))
+            .and(header("content-type", DIR_MARKER_CONTENT_TYPE))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        op.create_dir("dir/").await?;
+
+        // No marker will be written if disabled.
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.disable_dir_marker();
+        let op = Operator::new(builder)?.finish();
+        op.create_dir("dir/").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_hides_dir_marker() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o"))
+            .and(query_param("prefix", "dir/"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{
+  "kind": "storage#objects",
+  "prefixes": ["dir/sub/"],
+  "items": [
+    {
+      "name": "dir/",
+      "size": "0",
+      "updated": "2022-08-15T11:33:34.866Z"
+    },
+    {
+      "name": "dir/a",
+      "size": "42",
+      "updated": "2022-08-15T11:33:34.866Z"
+    }
+  ]
+}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        let mut paths: Vec<String> = op
+            .list("dir/")
+            .await?
+            .into_iter()
+            .map(|e| e.path().to_string())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["dir/a", "dir/sub/"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_api_version() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v2/b/test/o/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "file", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.api_version("v2");
+        let op = Operator::new(builder)?.finish();
+
+        let meta = op.stat("file").await?;
+        assert_eq!(meta.content_length(), 42);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_many() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/exist"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "exist", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        let res = backend
+            .stat_many(vec!["exist".to_string(), "missing".to_string()], 2)
+            .await;
+
+        assert_eq!(res.len(), 2);
+        let meta = res[0].as_ref().expect("stat must succeed");
+        assert_eq!(meta.content_length(), 42);
+        let err = res[1].as_ref().expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_many_keeps_order_and_duplicates() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/slow"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(
+                        r#"{"name": "slow", "size": "1", "updated": "2022-08-15T11:33:34.866Z"}"#,
+                    )
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/fast"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "fast", "size": "2", "updated": "2022-08-15T11:33:34.866Z"}"#,
+            ))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        let res = backend
+            .stat_many(
+                vec!["slow".to_string(), "fast".to_string(), "fast".to_string()],
+                3,
+            )
+            .await;
+
+        let sizes = res
+            .into_iter()
+            .map(|r| r.map(|meta| meta.content_length()))
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(sizes, vec![1, 2, 2]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_max_retries() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/flaky"))
+            .respond_with(ResponseTemplate::new(503))
+            // 2 requests by the layer's default, 1 request by the override.
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?.layer(
+            RetryLayer::new()
+                .with_min_delay(Duration::from_millis(1))
+                .with_max_times(1),
+        );
+
+        let err = op.stat("flaky").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        let err = op
+            .stat_with("flaky")
+            .max_retries(0)
+            .await
+            .expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_with_metadata() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/b/test/o/src/copyTo/b/test/o/dst"))
+            .and(body_json(json!({
+                "contentType": "text/plain",
+                "cacheControl": "no-store",
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        op.copy_with("src", "dst")
+            .content_type("text/plain")
+            .cache_control("no-store")
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_with_reset_metadata() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        // Metadata is taken from the body if it's not empty.
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/b/test/o/src/copyTo/b/test/o/reset"))
+            .and(body_json(json!({
+                "contentType": "application/octet-stream",
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(
+                "/storage/v1/b/test/o/src/copyTo/b/test/o/reset-with-cache-control",
+            ))
+            .and(body_json(json!({
+                "contentType": "application/octet-stream",
+                "cacheControl": "no-store",
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // Metadata is copied from the source if the body is empty.
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/b/test/o/src/copyTo/b/test/o/preserve"))
+            .and(body_string(""))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        op.copy_with("src", "reset").reset_metadata(true).await?;
+        op.copy_with("src", "reset-with-cache-control")
+            .reset_metadata(true)
+            .cache_control("no-store")
+            .await?;
+        op.copy("src", "preserve").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_with_source_version() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/b/test/o/src/copyTo/b/test/o/dst"))
+            .and(query_param("sourceGeneration", "1660563214863653"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/b/test/o/src/copyTo/b/test/o/dst"))
+            .and(query_param("sourceGeneration", "1"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        op.copy_with("src", "dst")
+            .source_version("1660563214863653")
+            .await?;
+
+        let err = op
+            .copy_with("src", "dst")
+            .source_version("1")
+            .await
+            .expect_err("copy with missing source generation must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_version() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("alt", "media"))
+            .and(query_param("generation", "1660563214863653"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-goog-generation", "1660563214863653")
+                    .set_body_string("hello"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("alt", "media"))
+            .and(query_param("generation", "1"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(
+                r#"{"error": {"code": 404, "message": "No such object: test/file"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        let (rp, _) = backend
+            .read("file", OpRead::new().with_version("1660563214863653"))
+            .await?;
+        assert_eq!(rp.version(), Some("1660563214863653"));
+
+        let err = backend
+            .read("file", OpRead::new().with_version("1"))
+            .await
+            .err()
+            .expect("read with purged generation must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_host() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(header("host", "storage.emulator.test"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "file", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.host("storage.emulator.test");
+        let op = Operator::new(builder)?.finish();
+
+        let meta = op.stat("file").await?;
+        assert_eq!(meta.content_length(), 42);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_generations() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "file", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z", "generation": "1660563214863653", "metageneration": "2"}"#,
+            ))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/invalid"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "invalid", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z", "generation": "abc"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+
+        let meta = op.stat("file").await?;
+        assert_eq!(meta.version(), Some("1660563214863653"));
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        match backend.conditional_stat("file", OpStat::new()).await? {
+            GcsStatResult::Metadata(m) => {
+                assert_eq!(m.metadata().version(), Some("1660563214863653"));
+                assert_eq!(m.metageneration(), Some(2));
+            }
+            GcsStatResult::NotModified => panic!("stat without condition must return metadata"),
+        }
+
+        let err = op
+            .stat("invalid")
+            .await
+            .expect_err("invalid generation must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_version() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("generation", "1660563214863653"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "file", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z", "generation": "1660563214863653", "metageneration": "1"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("generation", "1"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(
+                r#"{"error": {"code": 404, "message": "No such object: test/file"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/dir%2F"))
+            .and(query_param("generation", "1"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(
+                r#"{"error": {"code": 404, "message": "No such object: test/dir/"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+
+        let meta = op.stat_with("file").version("1660563214863653").await?;
+        assert_eq!(meta.version(), Some("1660563214863653"));
+        assert_eq!(meta.content_length(), 42);
+
+        let err = op
+            .stat_with("file")
+            .version("1")
+            .await
+            .expect_err("stat with missing generation must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // A missing generation is never treated as an implicit dir.
+        let err = op
+            .stat_with("dir/")
+            .version("1")
+            .await
+            .expect_err("stat with missing generation must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_presign_with_version() -> Result<()> {
+        let credential = json!({
+            "type": "service_account",
+            "project_id": "test",
+            "private_key": TEST_PRIVATE_KEY,
+            "client_email": "test@example.iam.gserviceaccount.com",
+        });
+        let mut builder = new_test_builder("https://storage.googleapis.com");
+        builder.credential(&BASE64_STANDARD.encode(credential.to_string()));
+        let backend = builder.build()?;
+
+        let req = backend
+            .presign(
+                "file",
+                OpPresign::new(
+                    OpRead::new().with_version("1660563214863653"),
+                    Duration::from_secs(3600),
+                ),
+            )
+            .await?
+            .into_presigned_request();
+        let query = req.uri().query().expect("presigned uri must have query");
+        assert!(
+            query.contains("generation=1660563214863653"),
+            "query: {query}"
+        );
+
+        let req = backend
+            .presign(
+                "file",
+                OpPresign::new(
+                    OpStat::new().with_version("1660563214863653"),
+                    Duration::from_secs(3600),
+                ),
+            )
+            .await?
+            .into_presigned_request();
+        let query = req.uri().query().expect("presigned uri must have query");
+        assert!(
+            query.contains("generation=1660563214863653"),
+            "query: {query}"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_quota_project() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(header("x-goog-user-project", "billing-project"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "file", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.quota_project("billing-project");
+        let op = Operator::new(builder)?.finish();
+
+        let meta = op.stat("file").await?;
+        assert_eq!(meta.content_length(), 42);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_timeout() -> Result<()> {
+        // Non-routable address, connections to it will hang until timeout.
+        let mut builder = new_test_builder("http://10.255.255.1");
+        builder.connect_timeout(Duration::from_millis(100));
+        let backend = builder.build()?;
+
+        let start = std::time::Instant::now();
+        let err = backend
+            .read("file", OpRead::new())
+            .await
+            .err()
+            .expect("read from unreachable endpoint must fail");
+        assert!(err.is_temporary());
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_timeout_with_slow_response() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("alt", "media"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("Hello, World!")
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder
+            .connect_timeout(Duration::from_millis(100))
+            .read_timeout(Duration::from_secs(10));
+        let op = Operator::new(builder)?.finish();
+
+        let bs = op.read("file").await?;
+        assert_eq!(bs, b"Hello, World!");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_metadata() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("alt", "media"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/plain")
+                    .insert_header("etag", "CKWasoTgyPkCEAE=")
+                    .insert_header("content-disposition", "attachment")
+                    .insert_header("x-goog-generation", "1660563214863653")
+                    .insert_header("x-goog-metageneration", "2")
+                    .insert_header(
+                        "x-goog-hash",
+                        "crc32c=TVUQaA==,md5=ZajifYh5KDgxtmS9i38K1A==",
+                    )
+                    .set_body_string("Hello, World!"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+        let r = op.reader_with("file").return_metadata(true).await?;
+
+        let meta = r.metadata().expect("metadata must be returned");
+        assert_eq!(meta.content_type(), Some("text/plain"));
+        assert_eq!(meta.etag(), Some("CKWasoTgyPkCEAE="));
+        assert_eq!(meta.content_disposition(), Some("attachment"));
+        assert_eq!(meta.version(), Some("1660563214863653"));
+        assert_eq!(meta.content_md5(), Some("ZajifYh5KDgxtmS9i38K1A=="));
+        assert_eq!(meta.content_crc32c(), Some("TVUQaA=="));
+
+        // The content is read from the same response.
+        let bs = r
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("read must succeed")
+            .concat();
+        assert_eq!(bs, b"Hello, World!");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_and_read_with_same_etag() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("alt", "media"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"CKWasoTgyPkCEAE=\"")
+                    .set_body_string("Hello, World!"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "file", "size": "13", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+        let r = op.reader_with("file").return_metadata(true).await?;
+        let read_meta = r.metadata().expect("metadata must be returned");
+        let meta = op.stat("file").await?;
+        assert_eq!(read_meta.etag(), Some("CKWasoTgyPkCEAE="));
+        assert_eq!(meta.etag(), read_meta.etag());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_verify_md5() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("alt", "media"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-goog-hash", "md5=ZajifYh5KDgxtmS9i38K1A==")
+                    .set_body_string("Hello, World!"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/corrupted"))
+            .and(query_param("alt", "media"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-MD5", "ZajifYh5KDgxtmS9i38K1A==")
+                    .set_body_string("Hello, World?"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.verify_read_md5();
+        let op = Operator::new(builder)?.finish();
+
+        let bs = op.read("file").await?;
+        assert_eq!(bs, b"Hello, World!");
+
+        let err = op
+            .read("corrupted")
+            .await
+            .expect_err("read corrupted content must fail");
+        assert!(err.to_string().contains("md5 mismatch"), "{err}");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_crc32c_check() -> Result<()> {
+        let crc32c = BASE64_STANDARD.encode(crc32c::crc32c(b"Hello, World!").to_be_bytes());
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("alt", "media"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-goog-hash", format!("crc32c={crc32c}").as_str())
+                    .set_body_string("Hello, World!"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/corrupted"))
+            .and(query_param("alt", "media"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-goog-hash", format!("crc32c={crc32c}").as_str())
+                    .set_body_string("Hello, World?"),
+            )
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.enable_crc32c_check();
+        let op = Operator::new(builder)?.finish();
+
+        let bs = op.read("file").await?;
+        assert_eq!(bs, b"Hello, World!");
+
+        let err = op
+            .read("corrupted")
+            .await
+            .expect_err("read corrupted content must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.to_string().contains("crc32c mismatch"), "{err}");
+
+        // Streaming reads are verified as well.
+        let err = op
+            .reader("corrupted")
+            .await?
+            .try_collect::<Vec<_>>()
+            .await
+            .expect_err("stream corrupted content must fail");
+        assert!(err.to_string().contains("crc32c mismatch"), "{err}");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_identity_encoding() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/transcoded"))
+            .and(header("accept-encoding", "identity"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-goog-stored-content-encoding", "gzip")
+                    .set_body_string("Hello, World!"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/raw"))
+            .and(header("accept-encoding", "identity"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-goog-stored-content-encoding", "gzip")
+                    .insert_header("content-encoding", "gzip")
+                    .set_body_bytes(vec![0x1f, 0x8b, 0x08]),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+
+        let err = op
+            .read_with("transcoded")
+            .identity_encoding(true)
+            .await
+            .expect_err("read must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        let bs = op.read_with("raw").identity_encoding(true).await?;
+        assert_eq!(bs, vec![0x1f, 0x8b, 0x08]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_missing() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/storage/v1/b/test/o/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        op.delete("missing").await?;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.error_on_missing_delete();
+        let op = Operator::new(builder)?.finish();
+        let err = op.delete("missing").await.expect_err("delete must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_metrics_hook() -> Result<()> {
+        #[derive(Default)]
+        struct CountingHook {
+            responses: AtomicU64,
+            read_bytes: AtomicU64,
+        }
+
+        impl GcsMetricsHook for CountingHook {
+            fn on_response(&self, _: &Method, _: StatusCode) {
+                self.responses.fetch_add(1, Ordering::Relaxed);
+            }
+
+            fn on_read_bytes(&self, n: u64) {
+                self.read_bytes.fetch_add(n, Ordering::Relaxed);
+            }
+        }
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(query_param("alt", "media"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let hook = Arc::new(CountingHook::default());
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.metrics_hook(hook.clone());
+        let op = Operator::new(builder)?.finish();
+
+        let bs = op.read("file").await?;
+        assert_eq!(bs, b"Hello, World!");
+        assert!(hook.responses.load(Ordering::Relaxed) > 0);
+        assert_eq!(hook.read_bytes.load(Ordering::Relaxed), 13);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_default_content_disposition() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "default"))
+            .and(query_param("uploadType", "multipart"))
+            .and(body_string_contains(r#""contentDisposition":"attachment""#))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "override"))
+            .and(query_param("uploadType", "multipart"))
+            .and(body_string_contains(r#""contentDisposition":"inline""#))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.default_content_disposition("attachment");
+        let op = Operator::new(builder)?.finish();
+
+        op.write("default", "Hello, World!").await?;
+        op.write_with("override", "Hello, World!")
+            .content_disposition("inline")
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_content_md5() -> Result<()> {
+        // MD5 of `Hello, World!`.
+        let content_md5 = "ZajifYh5KDgxtmS9i38K1A==";
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "media"))
+            .and(query_param("uploadType", "media"))
+            .and(header("content-md5", content_md5))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "multipart"))
+            .and(query_param("uploadType", "multipart"))
+            .and(body_string_contains(format!(
+                r#""md5Hash":"{content_md5}""#
+            )))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "corrupted"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": {
+                    "code": 400,
+                    "message": "Provided MD5 hash \"ZajifYh5KDgxtmS9i38K1A==\" doesn't match calculated MD5 hash \"1B2M2Y8AsgTpgAmY7PhCfg==\"."
+                }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.enable_content_md5();
+        let op = Operator::new(builder)?.finish();
+
+        op.write("media", "Hello, World!").await?;
+        op.write_with("multipart", "Hello, World!")
+            .content_disposition("inline")
+            .await?;
+
+        let err = op
+            .write("corrupted", "Hello, World!")
+            .await
+            .expect_err("write must fail");
+        assert!(err
+            .to_string()
+            .contains("uploaded content doesn't match the given Content-MD5"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_if_match() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "lock"))
+            .and(query_param("ifGenerationMatch", "1660563214863653"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "lock"))
+            .and(query_param("ifGenerationMatch", "1"))
+            .respond_with(ResponseTemplate::new(412))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+
+        // etag of generation `1660563214863653`.
+        op.write_with("lock", "locked")
+            .if_match("CKWasoTgyPkCEAE=")
+            .await?;
+
+        // etag of generation `1`.
+        let err = op
+            .write_with("lock", "locked")
+            .if_match("CAEQAQ==")
+            .await
+            .expect_err("write with stale etag must fail");
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+
+        let err = op
+            .write_with("lock", "locked")
+            .if_match("d41d8cd98f00b204e9800998ecf8427e")
+            .await
+            .expect_err("write with invalid etag must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_returns_committed_object() -> Result<()> {
+        let object = r#"{
+  "kind": "storage#object",
+  "name": "file",
+  "bucket": "test",
+  "generation": "1660563214863653",
+  "size": "10",
+  "md5Hash": "fHcEH1vPwA6eTPqxuasXcg==",
+  "crc32c": "j/un9g==",
+  "etag": "CKWasoTgyPkCEAE="
+}"#;
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("uploadType", "media"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(object))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/session"))
+            .and(header("content-range", "bytes 5-9/10"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(object))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+        let mut w = op.writer("file").await?;
+        w.write("HelloWorld").await?;
+        assert_eq!(w.metadata(), None);
+        w.close().await?;
+
+        let meta = w.metadata().expect("committed metadata must be returned");
+        assert_eq!(meta.content_length(), 10);
+        assert_eq!(meta.content_md5(), Some("fHcEH1vPwA6eTPqxuasXcg=="));
+        assert_eq!(meta.content_crc32c(), Some("j/un9g=="));
+        assert_eq!(meta.version(), Some("1660563214863653"));
+        assert_eq!(meta.etag(), Some("CKWasoTgyPkCEAE="));
+
+        // The final request of resumable uploads returns the object too.
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        let w = GcsWriter::new(backend.core.clone(), "file", OpWrite::default());
+        let location = format!("{}/upload/session", mock_server.uri());
+        w.complete_range(&location, 5, 5, AsyncBody::Bytes("World".into()))
+            .await?;
+        let committed = w.committed();
+        let meta = committed.lock().expect("lock must succeed");
+        assert_eq!(meta.as_ref().map(|v| v.content_length()), Some(10));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_multipart_upload_threshold() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("uploadType", "media"))
+            .and(query_param("name", "small"))
+            .and(body_string_contains("Hello, World!"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("uploadType", "resumable"))
+            .and(query_param("name", "large"))
+            .respond_with(ResponseTemplate::new(200).insert_header(
+                "location",
+                format!("{}/upload/session", mock_server.uri()).as_str(),
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/upload/session"))
+            .and(header("Content-Range", "bytes 0-262143/*"))
+            .respond_with(ResponseTemplate::new(308))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/session"))
+            .and(header("Content-Range", "bytes 262144-262153/262154"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.multipart_upload_threshold(256 * 1024);
+        let op = Operator::new(builder)?.finish();
+
+        op.write("small", "Hello, World!").await?;
+        op.write("large", vec![0; 256 * 1024 + 10]).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_resumable_upload_with_metadata() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("uploadType", "resumable"))
+            .and(query_param("name", "large"))
+            .and(body_json(json!({
+                "contentDisposition": "attachment",
+                "contentType": "text/plain",
+            })))
+            .respond_with(ResponseTemplate::new(200).insert_header(
+                "location",
+                format!("{}/upload/session", mock_server.uri()).as_str(),
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/upload/session"))
+            .respond_with(ResponseTemplate::new(308))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/session"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder
+            .multipart_upload_threshold(256 * 1024)
+            .default_content_disposition("attachment");
+        let op = Operator::new(builder)?.finish();
+
+        op.write_with("large", vec![0; 256 * 1024 + 10])
+            .content_type("text/plain")
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_with_entry_mode() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o"))
+            .and(query_param("prefix", "dir/"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"prefixes": ["dir/sub/"], "items": [{"name": "dir/file", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z"}]}"#,
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+
+        let list = |mode: Option<EntryMode>| {
+            let op = op.clone();
+            async move {
+                let mut fut = op.list_with("dir/");
+                if let Some(mode) = mode {
+                    fut = fut.entry_mode(mode);
+                }
+                let mut paths = fut
+                    .await?
+                    .into_iter()
+                    .map(|v| v.path().to_string())
+                    .collect::<Vec<_>>();
+                paths.sort();
+                Ok::<_, Error>(paths)
+            }
+        };
+
+        assert_eq!(list(None).await?, vec!["dir/file", "dir/sub/"]);
+        assert_eq!(list(Some(EntryMode::FILE)).await?, vec!["dir/file"]);
+        assert_eq!(list(Some(EntryMode::DIR)).await?, vec!["dir/sub/"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_kms_key_name() -> Result<()> {
+        let key = "projects/p/locations/global/keyRings/r/cryptoKeys/k";
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("uploadType", "multipart"))
+            .and(query_param("predefinedAcl", "private"))
+            .and(query_param("kmsKeyName", key))
+            .and(body_string_contains(r#""storageClass":"COLDLINE""#))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("uploadType", "resumable"))
+            .and(query_param("predefinedAcl", "private"))
+            .and(query_param("kmsKeyName", key))
+            .respond_with(ResponseTemplate::new(200).insert_header(
+                "location",
+                format!("{}/upload/session", mock_server.uri()).as_str(),
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                r#"{{"name": "file", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z", "kmsKeyName": "{key}/cryptoKeyVersions/1"}}"#
+            )))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder
+            .kms_key_name(key)
+            .predefined_acl("private")
+            .default_storage_class("COLDLINE");
+        let backend = builder.build()?;
+
+        let mut req = backend.core.gcs_insert_object_request(
+            "file",
+            Some(13),
+            &OpWrite::default(),
+            AsyncBody::Bytes(Bytes::from("Hello, World!")),
+        )?;
+        backend.core.sign(&mut req).await?;
+        let resp = backend.core.send(req).await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = backend
+            .core
+            .gcs_initiate_resumable_upload("file", &OpWrite::default())
+            .await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Both uploads must carry the key encoded in the same way.
+        let requests = mock_server
+            .received_requests()
+            .await
+            .expect("request recording must be enabled");
+        let kms_key_query = |idx: usize| {
+            requests[idx]
+                .url
+                .query()
+                .unwrap_or_default()
+                .split('&')
+                .find(|v| v.starts_with("kmsKeyName="))
+                .map(|v| v.to_string())
+        };
+        assert!(kms_key_query(0).is_some());
+        assert_eq!(kms_key_query(0), kms_key_query(1));
+
+        match backend.conditional_stat("file", OpStat::new()).await? {
+            GcsStatResult::Metadata(m) => assert_eq!(
+                m.kms_key_name(),
+                Some(format!("{key}/cryptoKeyVersions/1").as_str())
+            ),
+            GcsStatResult::NotModified => panic!("stat without condition must return metadata"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_customer_supplied_encryption_key() -> Result<()> {
+        let key = BASE64_STANDARD.encode([7u8; 32]);
+        let key_sha256 = BASE64_STANDARD.encode(Sha256::digest([7u8; 32]));
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/b/test/o/from/copyTo/b/test/o/to"))
+            .and(header("x-goog-copy-source-encryption-algorithm", "AES256"))
+            .and(header("x-goog-copy-source-encryption-key", key.as_str()))
+            .and(header(
+                "x-goog-copy-source-encryption-key-sha256",
+                key_sha256.as_str(),
+            ))
+            .and(header("x-goog-encryption-key", key.as_str()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(header("x-goog-encryption-algorithm", "AES256"))
+            .and(header("x-goog-encryption-key", key.as_str()))
+            .and(header("x-goog-encryption-key-sha256", key_sha256.as_str()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // Both stat and read must carry the key.
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test/o/file"))
+            .and(header("x-goog-encryption-algorithm", "AES256"))
+            .and(header("x-goog-encryption-key", key.as_str()))
+            .and(header("x-goog-encryption-key-sha256", key_sha256.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name": "file", "size": "42", "etag": "CKWasoTgyPkCEAE=", "updated": "2022-08-15T11:33:34.866Z"}"#,
+            ))
+            .expect(2..)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.encryption_key(&key);
+        let op = Operator::new(builder)?.finish();
+
+        op.write("file", "Hello, World!").await?;
+        op.stat("file").await?;
+        op.read("file").await?;
+        op.copy("from", "to").await?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encryption_key_must_be_32_bytes() {
+        let mut builder = new_test_builder("http://127.0.0.1:1");
+        builder.encryption_key(&BASE64_STANDARD.encode([7u8; 16]));
+        let err = builder
+            .build()
+            .expect_err("16 bytes encryption key must be refused");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut builder = new_test_builder("http://127.0.0.1:1");
+        builder.encryption_key("not base64!");
+        let err = builder
+            .build()
+            .expect_err("invalid base64 encryption key must be refused");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[tokio::test]
+    async fn test_precondition_failed_is_condition_not_match() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(412).set_body_string(
+                r#"{"error": {"code": 412, "message": "At least one of the pre-conditions you specified did not hold.", "errors": [{"domain": "global", "reason": "conditionNotMet", "message": "At least one of the pre-conditions you specified did not hold.", "locationType": "header", "location": "If-Match"}]}}"#,
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+
+        let errs = [
+            ("read", op.read("file").await.map(|_| ())),
+            ("write", op.write("file", "Hello, World!").await),
+            ("delete", op.delete("file").await),
+            ("copy", op.copy("file", "copied").await),
+        ];
+        for (name, res) in errs {
+            let err = res.expect_err("412 must be returned as error");
+            assert_eq!(err.kind(), ErrorKind::ConditionNotMatch, "{name}");
+            assert!(!err.is_temporary(), "{name}");
+            assert!(
+                err.to_string().contains("precondition: If-Match"),
+                "{name}: {err}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_exceeds_max_object_size() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("uploadType", "resumable"))
+            .respond_with(ResponseTemplate::new(200).insert_header(
+                "location",
+                format!("{}/upload/session", mock_server.uri()).as_str(),
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/upload/session"))
+            .and(header("Content-Range", "bytes 0-262143/*"))
+            .respond_with(ResponseTemplate::new(308))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // The final chunk must not be sent.
+        Mock::given(method("PUT"))
+            .and(path("/upload/session"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/upload/session"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.multipart_upload_threshold(256 * 1024);
+        builder.max_object_size(256 * 1024);
+        let op = Operator::new(builder)?.finish();
+
+        let err = op
+            .write("large", vec![0; 256 * 1024 + 10])
+            .await
+            .expect_err("write exceeding max object size must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.to_string().contains("max object size"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multipart_upload_threshold_must_be_aligned() {
+        let mut builder = new_test_builder("http://127.0.0.1:1");
+        builder.multipart_upload_threshold(1000);
+        let err = builder
+            .build()
+            .expect_err("unaligned threshold must be refused");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[tokio::test]
+    async fn test_initiate_resumable_upload_with_content_headers() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("uploadType", "resumable"))
+            .and(query_param("name", "file"))
+            .and(header("X-Upload-Content-Type", "text/plain"))
+            .and(header("X-Upload-Content-Length", "10"))
+            .respond_with(ResponseTemplate::new(200).insert_header(
+                "location",
+                format!("{}/upload/session", mock_server.uri()).as_str(),
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        let op = OpWrite::default()
+            .with_content_type("text/plain")
+            .with_content_length(10);
+        let w = GcsWriter::new(backend.core.clone(), "file", op);
+
+        let location = w.initiate_range().await?;
+        assert_eq!(location, format!("{}/upload/session", mock_server.uri()));
+
+        Ok(())
+    }
+
+    /// Serve a single request with the given raw response, since wiremock
+    /// can't respond with status like 499.
+    fn serve_once(response: &'static str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind must succeed");
+        let addr = listener.local_addr().expect("local addr must be valid");
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept must succeed");
+            // Read until the end of headers, the request carries no body.
+            let mut req = Vec::new();
+            let mut buf = [0; 1024];
+            while !req.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).expect("read must succeed");
+                if n == 0 {
+                    break;
+                }
+                req.extend_from_slice(&buf[..n]);
+            }
+            stream
+                .write_all(response.as_bytes())
+                .expect("write must succeed");
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_abort_resumable_upload_session() -> Result<()> {
+        let endpoint =
+            serve_once("HTTP/1.1 499 Client Closed Request\r\ncontent-length: 0\r\n\r\n");
+
+        let backend = new_test_builder(&endpoint).build()?;
+        let w = GcsWriter::new(backend.core.clone(), "aborted", OpWrite::default());
+        w.abort_range(&format!("{endpoint}/upload/aborted")).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reserved_chars_in_path() -> Result<()> {
+        // `dir/a+b c你好.txt` encoded for JSON API.
+        let encoded = "dir%2Fa%2Bb%20c%E4%BD%A0%E5%A5%BD.txt";
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload/storage/v1/b/test/o"))
+            .and(query_param("name", "dir/a+b c你好.txt"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/storage/v1/b/test/o/{encoded}")))
+            .and(query_param("alt", "media"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/storage/v1/b/test/o/{encoded}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "size": "13",
+                "updated": "2022-08-15T11:33:34.866Z",
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/storage/v1/b/test/o/{encoded}/copyTo/b/test/o/dir%2Fb%2B%20copy.txt"
+            )))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+
+        op.write("dir/a+b c你好.txt", "Hello, World!").await?;
+
+        let bs = op.read("dir/a+b c你好.txt").await?;
+        assert_eq!(bs, b"Hello, World!");
+
+        let meta = op.stat("dir/a+b c你好.txt").await?;
+        assert_eq!(meta.content_length(), 13);
+
+        op.copy("dir/a+b c你好.txt", "dir/b+ copy.txt").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_via_presigned() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/test/file"))
+            .and(query_param("X-Goog-Algorithm", "GOOG4-RSA-SHA256"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let credential = json!({
+            "type": "service_account",
+            "project_id": "test",
+            "private_key": TEST_PRIVATE_KEY,
+            "client_email": "test@example.iam.gserviceaccount.com",
+        });
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.credential(&BASE64_STANDARD.encode(credential.to_string()));
+        let backend = builder.build()?;
+
+        let bs = backend
+            .read_via_presigned("file", Duration::from_secs(3600))
+            .await?;
+        assert_eq!(bs, "Hello, World!");
+
+        // The presigned url carries the signature instead of the token.
+        let requests = mock_server
+            .received_requests()
+            .await
+            .expect("request recording must be enabled");
+        assert_eq!(requests.len(), 1);
+        let query = requests[0].url.query().unwrap_or_default();
+        assert!(query.contains("X-Goog-Signature="), "query: {query}");
+
+        Ok(())
+    }
+
+    /// Rebuild the string to sign following GCS V4 signing process and
+    /// make sure the signature of presigned request is valid.
+    fn verify_v4_signature(req: &PresignedRequest, host: &str) {
+        let uri = req.uri();
+        let query = uri.query().expect("presigned uri must have query");
+        let mut signature = None;
+        let mut pairs = vec![];
+        for pair in query.split('&') {
+            match pair.strip_prefix("X-Goog-Signature=") {
+                Some(v) => signature = Some(v),
+                None => pairs.push(pair),
+            }
+        }
+        pairs.sort();
+        let find = |key: &str| {
+            pairs
+                .iter()
+                .find_map(|p| p.strip_prefix(&format!("{key}=")))
+                .expect("query must exist")
+        };
+        let date = find("X-Goog-Date");
+        let scope = percent_decode_path(find("X-Goog-Credential"))
+            .split_once('/')
+            .expect("credential must contain scope")
+            .1
+            .to_string();
+
+        let canonical_request = format!(
+            "HEAD\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+            uri.path(),
+            pairs.join("&"),
+            host,
+        );
+        let string_to_sign = format!(
+            "GOOG4-RSA-SHA256\n{date}\n{scope}\n{}",
+            Sha256::digest(canonical_request.as_bytes())
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<String>()
+        );
+
+        let signature = signature.expect("signature must exist");
+        let signature = (0..signature.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&signature[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        let public_key = RsaPrivateKey::from_pkcs8_pem(TEST_PRIVATE_KEY)
+            .unwrap()
+            .to_public_key();
+        VerifyingKey::<Sha256>::new(public_key)
+            .verify(
+                string_to_sign.as_bytes(),
+                &Signature::try_from(signature.as_slice()).unwrap(),
+            )
+            .expect("signature must be valid");
+    }
+
+    #[tokio::test]
+    async fn test_presign_with_reserved_chars() -> Result<()> {
+        let credential = json!({
+            "type": "service_account",
+            "project_id": "test",
+            "private_key": TEST_PRIVATE_KEY,
+            "client_email": "test@example.iam.gserviceaccount.com",
+        });
+        let mut builder = new_test_builder("https://storage.googleapis.com");
+        builder.credential(&BASE64_STANDARD.encode(credential.to_string()));
+        let backend = builder.build()?;
+
+        let req = backend
+            .presign(
+                "dir/a+b c你好.txt",
+                OpPresign::new(OpStat::new(), Duration::from_secs(3600)),
+            )
+            .await?
+            .into_presigned_request();
+
+        let uri = req.uri();
+        assert_eq!(uri.path(), "/test/dir/a%2Bb%20c%E4%BD%A0%E5%A5%BD.txt");
+
+        verify_v4_signature(&req, "storage.googleapis.com");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_presign_with_host() -> Result<()> {
+        let credential = json!({
+            "type": "service_account",
+            "project_id": "test",
+            "private_key": TEST_PRIVATE_KEY,
+            "client_email": "test@example.iam.gserviceaccount.com",
+        });
+        let mut builder = new_test_builder("http://127.0.0.1:4443");
+        builder
+            .credential(&BASE64_STANDARD.encode(credential.to_string()))
+            .host("storage.emulator.test");
+        let backend = builder.build()?;
+
+        let req = backend
+            .presign(
+                "file",
+                OpPresign::new(OpStat::new(), Duration::from_secs(3600)),
+            )
+            .await?
+            .into_presigned_request();
+
+        // The request is still sent to the endpoint, but signed over the
+        // overridden host.
+        assert_eq!(req.uri().authority().unwrap().as_str(), "127.0.0.1:4443");
+        assert_eq!(
+            req.header().get(http::header::HOST).unwrap(),
+            "storage.emulator.test"
+        );
+        verify_v4_signature(&req, "storage.emulator.test");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_presign_with_signed_url_version() -> Result<()> {
+        let credential = json!({
+            "type": "service_account",
+            "project_id": "test",
+            "private_key": TEST_PRIVATE_KEY,
+            "client_email": "test@example.iam.gserviceaccount.com",
+        });
+        let mut builder = new_test_builder("https://storage.googleapis.com");
+        builder
+            .credential(&BASE64_STANDARD.encode(credential.to_string()))
+            .signed_url_version("v2");
+        let backend = builder.build()?;
+
+        let now = Utc::now().timestamp();
+        let req = backend
+            .presign(
+                "dir/a b.txt",
+                OpPresign::new(OpRead::new(), Duration::from_secs(3600)),
+            )
+            .await?
+            .into_presigned_request();
+
+        let uri = req.uri();
+        assert_eq!(uri.path(), "/test/dir/a%20b.txt");
+        let query = uri.query().expect("presigned uri must have query");
+        assert!(!query.contains("X-Goog-"), "query: {query}");
+        let find = |key: &str| {
+            query
+                .split('&')
+                .find_map(|p| p.strip_prefix(&format!("{key}=")))
+                .expect("query must exist")
+        };
+        assert_eq!(
+            percent_decode_path(find("GoogleAccessId")),
+            "test@example.iam.gserviceaccount.com"
+        );
+
+        // V2 carries the absolute expiration time instead of a duration.
+        let expires = find("Expires").parse::<i64>().expect("must be timestamp");
+        assert!(
+            (now + 3600..now + 3660).contains(&expires),
+            "expires: {expires}"
+        );
+
+        let string_to_sign = format!("GET\n\n\n{expires}\n{}", uri.path());
+        let signature = BASE64_STANDARD
+            .decode(percent_decode_path(find("Signature")))
+            .expect("signature must be base64 encoded");
+        let public_key = RsaPrivateKey::from_pkcs8_pem(TEST_PRIVATE_KEY)
+            .unwrap()
+            .to_public_key();
+        VerifyingKey::<Sha256>::new(public_key)
+            .verify(
+                string_to_sign.as_bytes(),
+                &Signature::try_from(signature.as_slice()).unwrap(),
+            )
+            .expect("signature must be valid");
+
+        // V4 is still the default and rejects expiration longer than 7 days.
+        let mut builder = new_test_builder("https://storage.googleapis.com");
+        builder
+            .credential(&BASE64_STANDARD.encode(credential.to_string()))
+            .signed_url_version("v4");
+        let backend = builder.build()?;
+        let req = backend
+            .presign(
+                "file",
+                OpPresign::new(OpRead::new(), Duration::from_secs(3600)),
+            )
+            .await?
+            .into_presigned_request();
+        let query = req.uri().query().expect("presigned uri must have query");
+        assert!(query.contains("X-Goog-Algorithm=GOOG4-RSA-SHA256"));
+        assert!(query.contains("X-Goog-Expires=3600"));
+        assert!(!query.contains("GoogleAccessId="));
+
+        let err = backend
+            .presign(
+                "file",
+                OpPresign::new(OpRead::new(), Duration::from_secs(8 * 24 * 3600)),
+            )
+            .await
+            .expect_err("must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_url_version_must_be_valid() {
+        let mut builder = new_test_builder("https://storage.googleapis.com");
+        builder.signed_url_version("v3");
+        let err = builder.build().expect_err("must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[tokio::test]
+    async fn test_predefined_acl_on_ubla_bucket() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "iamConfiguration": {
+                    "uniformBucketLevelAccess": {
+                        "enabled": true,
+                    },
+                },
+            })))
+            // The failed check isn't cached, so it's sent for every op.
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.predefined_acl("publicRead").check_bucket();
+        let op = Operator::new(builder)?.finish();
+
+        let err = op
+            .write("file", "Hello, World!")
+            .await
+            .expect_err("write must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+        assert!(err.to_string().contains("uniform bucket-level access"));
+
+        let err = op.copy("file", "copied").await.expect_err("copy must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let err = op
+            .create_dir("dir/")
+            .await
+            .expect_err("create_dir must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_with_predefined_acl() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/b/test/o/src/copyTo/b/test/o/dst"))
+            .and(query_param("sourceGeneration", "1"))
+            .and(query_param("destinationPredefinedAcl", "publicRead"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.predefined_acl("publicRead");
+        let op = Operator::new(builder)?.finish();
+
+        op.copy_with("src", "dst").source_version("1").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rename() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/b/test/o/src/copyTo/b/test/o/dst"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/storage/v1/b/test/o/src"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        op.rename("src", "dst").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rename_copy_failed_keeps_source() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/b/test/o/src/copyTo/b/test/o/dst"))
+            .respond_with(ResponseTemplate::new(403))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/storage/v1/b/test/o/src"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        let err = op.rename("src", "dst").await.expect_err("rename must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch_delete_with_lf_response() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        // Generated requests must always use CRLF.
+        Mock::given(method("POST"))
+            .and(path("/batch/storage/v1"))
+            .and(body_string_contains(
+                "\r\nDELETE /storage/v1/b/test/o/a HTTP/1.1\r\n",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "--batch_lf\n\
+                 Content-Type: application/http\n\
+                 Content-ID: <response-0>\n\
+                 \n\
+                 HTTP/1.1 204 No Content\n\
+                 \n\
+                 \n\
+                 --batch_lf\n\
+                 Content-Type: application/http\n\
+                 Content-ID: <response-1>\n\
+                 \n\
+                 HTTP/1.1 204 No Content\n\
+                 \n\
+                 \n\
+                 --batch_lf--\n",
+                "Multipart/Mixed;Boundary=\"batch_lf\"",
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_test_operator(&mock_server.uri())?;
+        op.remove(vec!["a".to_string(), "b".to_string()]).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch_delete_with_custom_boundary() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/batch/storage/v1"))
+            .and(header(
+                "content-type",
+                "multipart/mixed; boundary=custom_boundary",
+            ))
+            .and(body_string_contains("--custom_boundary\r\n"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "--batch_resp\r\n\
+                 Content-Type: application/http\r\n\
+                 Content-ID: <response-0>\r\n\
+                 \r\n\
+                 HTTP/1.1 204 No Content\r\n\
+                 \r\n\
+                 \r\n\
+                 --batch_resp--\r\n",
+                "multipart/mixed; boundary=batch_resp",
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.batch_boundary("custom_boundary");
+        let op = Operator::new(builder)?.finish();
+        op.remove(vec!["a".to_string()]).await?;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.batch_boundary("invalid\nboundary");
+        let err = builder.build().expect_err("must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch_delete_with_error_detail() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/batch/storage/v1"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "--batch_err\r\n\
+                 Content-Type: application/http\r\n\
+                 Content-ID: <response-0>\r\n\
+                 \r\n\
+                 HTTP/1.1 204 No Content\r\n\
+                 \r\n\
+                 \r\n\
+                 --batch_err\r\n\
+                 Content-Type: application/http\r\n\
+                 Content-ID: <response-1>\r\n\
+                 \r\n\
+                 HTTP/1.1 403 Forbidden\r\n\
+                 Content-Type: application/json; charset=UTF-8\r\n\
+                 \r\n\
+                 {\"error\": {\"code\": 403, \"message\": \"Object 'test/b' is under retention\", \
+                 \"errors\": [{\"domain\": \"global\", \"reason\": \"retentionPolicyNotMet\", \
+                 \"message\": \"Object 'test/b' is under retention\"}]}}\r\n\
+                 --batch_err--\r\n",
+                "multipart/mixed; boundary=batch_err",
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_builder(&mock_server.uri()).build()?;
+        let rp = backend
+            .batch(OpBatch::new(vec![
+                ("a".to_string(), OpDelete::new().into()),
+                ("b".to_string(), OpDelete::new().into()),
+            ]))
+            .await?;
+        let results = rp.into_results();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].1.is_ok());
+
+        let err = results[1].1.as_ref().err().expect("delete b must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        let msg = err.to_string();
+        assert!(msg.contains("retentionPolicyNotMet"), "{msg}");
+        assert!(msg.contains("under retention"), "{msg}");
+        assert!(msg.contains("path: b"), "{msg}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_get_object_json_response() {
+        let content = r#"{
+  "kind": "storage#object",
   "id": "example/1.png/1660563214863653",
   "selfLink": "https://www.googleapis.com/storage/v1/b/example/o/1.png",
   "mediaLink": "https://content-storage.googleapis.com/download/storage/v1/b/example/o/1.png?generation=1660563214863653&alt=media",
@@ -627,5 +4210,32 @@ mod tests {
         assert_eq!(meta.md5_hash, "fHcEH1vPwA6eTPqxuasXcg==");
         assert_eq!(meta.etag, "CKWasoTgyPkCEAE=");
         assert_eq!(meta.content_type, "image/png");
+        assert_eq!(meta.component_count, None);
+    }
+
+    #[test]
+    fn test_deserialize_get_object_json_response_with_component_count() {
+        let content = r#"{
+  "kind": "storage#object",
+  "name": "composed.bin",
+  "bucket": "example",
+  "generation": "1660563214863653",
+  "metageneration": "1",
+  "contentType": "application/octet-stream",
+  "storageClass": "STANDARD",
+  "size": "1048576",
+  "crc32c": "j/un9g==",
+  "componentCount": 3,
+  "etag": "CKWasoTgyPkCEAE=",
+  "timeCreated": "2022-08-15T11:33:34.866Z",
+  "updated": "2022-08-15T11:33:34.866Z"
+}"#;
+
+        let meta: GetObjectJsonResponse =
+            serde_json::from_str(content).expect("json Deserialize must succeed");
+
+        assert_eq!(meta.size, "1048576");
+        assert_eq!(meta.md5_hash, "");
+        assert_eq!(meta.component_count, Some(3));
     }
 }
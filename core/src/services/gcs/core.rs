@@ -19,30 +19,67 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Write;
+use std::sync::Arc;
 use std::time::Duration;
 
 use backon::ExponentialBuilder;
 use backon::Retryable;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use chrono::Utc;
+use http::header::ACCEPT_ENCODING;
+use http::header::CONTENT_DISPOSITION;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_RANGE;
 use http::header::CONTENT_TYPE;
 use http::header::HOST;
 use http::header::IF_MATCH;
 use http::header::IF_NONE_MATCH;
+use http::HeaderMap;
+use http::HeaderValue;
 use http::Request;
 use http::Response;
+use md5::Digest;
+use md5::Md5;
 use once_cell::sync::Lazy;
 use reqsign::GoogleCredential;
 use reqsign::GoogleCredentialLoader;
 use reqsign::GoogleSigner;
 use reqsign::GoogleToken;
 use reqsign::GoogleTokenLoader;
+use serde::Deserialize;
 use serde_json::json;
+use tokio::sync::OnceCell;
 
+use super::metrics::GcsMetricsHook;
+use super::post_policy::PostPolicy;
+use super::signed_url::sign_query_v2;
+use super::signed_url::GcsSignedUrlVersion;
 use super::uri::percent_encode_path;
+use super::uri::percent_encode_xml_path;
+use crate::raw::oio::WriteBuf;
 use crate::raw::*;
 use crate::*;
 
+const GOOGLE_APPLICATION_CREDENTIALS: &str = "GOOGLE_APPLICATION_CREDENTIALS";
+const X_GOOG_HASH: &str = "x-goog-hash";
+const X_GOOG_USER_PROJECT: &str = "x-goog-user-project";
+const X_GOOG_STORED_CONTENT_ENCODING: &str = "x-goog-stored-content-encoding";
+const X_GOOG_GENERATION: &str = "x-goog-generation";
+const X_UPLOAD_CONTENT_TYPE: &str = "X-Upload-Content-Type";
+const X_UPLOAD_CONTENT_LENGTH: &str = "X-Upload-Content-Length";
+const CONTENT_MD5: &str = "Content-MD5";
+const X_GOOG_ENCRYPTION_ALGORITHM: &str = "x-goog-encryption-algorithm";
+const X_GOOG_ENCRYPTION_KEY: &str = "x-goog-encryption-key";
+const X_GOOG_ENCRYPTION_KEY_SHA256: &str = "x-goog-encryption-key-sha256";
+const X_GOOG_COPY_SOURCE_ENCRYPTION_ALGORITHM: &str = "x-goog-copy-source-encryption-algorithm";
+const X_GOOG_COPY_SOURCE_ENCRYPTION_KEY: &str = "x-goog-copy-source-encryption-key";
+const X_GOOG_COPY_SOURCE_ENCRYPTION_KEY_SHA256: &str = "x-goog-copy-source-encryption-key-sha256";
+
+/// V4 signed urls can be valid for at most 7 days.
+const MAX_V4_SIGNED_URL_EXPIRE: Duration = Duration::from_secs(7 * 24 * 3600);
+
 pub struct GcsCore {
     pub endpoint: String,
     pub bucket: String,
@@ -52,9 +89,36 @@ pub struct GcsCore {
     pub signer: GoogleSigner,
     pub token_loader: GoogleTokenLoader,
     pub credential_loader: GoogleCredentialLoader,
+    /// Base64 encoded credential content set by users.
+    pub credential: Option<String>,
+    pub credential_path: Option<String>,
 
     pub predefined_acl: Option<String>,
+    pub kms_key_name: Option<String>,
     pub default_storage_class: Option<String>,
+    pub default_content_disposition: Option<String>,
+    pub disable_implicit_dir: bool,
+    pub disable_dir_marker: bool,
+    pub read_timeout: Option<Duration>,
+    pub host: Option<HeaderValue>,
+    pub detect_dir_by_list: bool,
+    pub api_version: String,
+    pub quota_project: Option<HeaderValue>,
+    pub error_on_missing_delete: bool,
+    pub batch_boundary: Option<String>,
+    pub check_bucket: bool,
+    pub bucket_checker: OnceCell<()>,
+    pub enable_content_md5: bool,
+    pub verify_read_md5: bool,
+    pub enable_crc32c_check: bool,
+    pub multipart_upload_threshold: Option<usize>,
+    pub max_object_size: Option<u64>,
+    pub signed_url_version: GcsSignedUrlVersion,
+    /// Base64 encoded AES-256 customer-supplied encryption key.
+    pub encryption_key: Option<HeaderValue>,
+    /// Base64 encoded SHA256 of `encryption_key`.
+    pub encryption_key_sha256: Option<HeaderValue>,
+    pub metrics_hook: Option<Arc<dyn GcsMetricsHook>>,
 }
 
 impl Debug for GcsCore {
@@ -103,60 +167,226 @@ impl GcsCore {
         }
     }
 
+    /// Load the key of service account from the credential set by users or
+    /// `GOOGLE_APPLICATION_CREDENTIALS`.
+    ///
+    /// reqsign doesn't expose the loaded service account, so we need to parse
+    /// the credential by ourselves to sign urls and policies with its key.
+    fn load_service_account(&self) -> Result<Option<ServiceAccountKey>> {
+        let content = if let Some(v) = &self.credential {
+            BASE64_STANDARD.decode(v).map_err(|err| {
+                Error::new(ErrorKind::ConfigInvalid, "credential is not valid base64")
+                    .set_source(err)
+            })?
+        } else if let Some(path) = self
+            .credential_path
+            .clone()
+            .or_else(|| std::env::var(GOOGLE_APPLICATION_CREDENTIALS).ok())
+        {
+            std::fs::read(&path).map_err(|err| {
+                Error::new(ErrorKind::ConfigInvalid, "read credential failed")
+                    .with_context("path", path)
+                    .set_source(err)
+            })?
+        } else {
+            return Ok(None);
+        };
+
+        // Other kinds of credential like external account don't have keys.
+        Ok(serde_json::from_slice(&content).ok())
+    }
+
+    /// Set the host header overridden by users before signing, so that the
+    /// signature is calculated over the host that will be sent instead of
+    /// the endpoint's authority.
+    fn override_host<T>(&self, req: &mut Request<T>) {
+        if let Some(host) = &self.host {
+            req.headers_mut().insert(HOST, host.clone());
+        }
+    }
+
+    /// Remove host header unless it's overridden by users, let users' client
+    /// to set it based on HTTP version.
+    ///
+    /// As discussed in <https://github.com/seanmonstar/reqwest/issues/1809>,
+    /// google server could send RST_STREAM of PROTOCOL_ERROR if our request
+    /// contains host header.
+    fn remove_host<T>(&self, req: &mut Request<T>) {
+        if self.host.is_none() {
+            req.headers_mut().remove(HOST);
+        }
+    }
+
     pub async fn sign<T>(&self, req: &mut Request<T>) -> Result<()> {
         let cred = self.load_token().await?;
 
+        // `x-goog-user-project` decides the project to be charged for quota
+        // and billing of requests authorized by OAuth token, which could be
+        // different from the project of credentials.
+        //
+        // It's different from the `userProject` query used by requester pays
+        // buckets, and is not applied in `sign_query` since presigned urls
+        // can't carry extra headers.
+        if let Some(v) = &self.quota_project {
+            req.headers_mut().insert(X_GOOG_USER_PROJECT, v.clone());
+        }
+
+        self.override_host(req);
         self.signer
             .sign(req, &cred)
             .map_err(new_request_sign_error)?;
-
-        // Always remove host header, let users' client to set it based on HTTP
-        // version.
-        //
-        // As discussed in <https://github.com/seanmonstar/reqwest/issues/1809>,
-        // google server could send RST_STREAM of PROTOCOL_ERROR if our request
-        // contains host header.
-        req.headers_mut().remove(HOST);
+        self.remove_host(req);
 
         Ok(())
     }
 
     pub async fn sign_query<T>(&self, req: &mut Request<T>, duration: Duration) -> Result<()> {
-        let cred = self.load_credential()?;
-
-        self.signer
-            .sign_query(req, duration, &cred)
-            .map_err(new_request_sign_error)?;
+        self.override_host(req);
+
+        match self.signed_url_version {
+            GcsSignedUrlVersion::V2 => {
+                let service_account = self.load_service_account()?.ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::ConfigInvalid,
+                        "v2 signed url can only be signed by service account",
+                    )
+                })?;
+                sign_query_v2(
+                    req,
+                    &service_account.client_email,
+                    &service_account.private_key,
+                    Utc::now(),
+                    duration,
+                )?;
+            }
+            GcsSignedUrlVersion::V4 => {
+                if duration > MAX_V4_SIGNED_URL_EXPIRE {
+                    return Err(Error::new(
+                        ErrorKind::Unsupported,
+                        "v4 signed url can't be valid for more than 7 days",
+                    )
+                    .with_context("expire", format!("{duration:?}")));
+                }
+                let cred = self.load_credential()?;
+                self.signer
+                    .sign_query(req, duration, &cred)
+                    .map_err(new_request_sign_error)?;
+            }
+        }
 
-        // Always remove host header, let users' client to set it based on HTTP
-        // version.
-        //
-        // As discussed in <https://github.com/seanmonstar/reqwest/issues/1809>,
-        // google server could send RST_STREAM of PROTOCOL_ERROR if our request
-        // contains host header.
-        req.headers_mut().remove(HOST);
+        self.remove_host(req);
 
         Ok(())
     }
 
+    /// Sign a POST policy with the service account's private key.
+    ///
+    /// Only service account credentials can be used to sign policies.
+    pub(super) fn sign_post_policy(
+        &self,
+        policy: &PostPolicy,
+        expire: Duration,
+    ) -> Result<HashMap<String, String>> {
+        let service_account = self.load_service_account()?.ok_or_else(|| {
+            Error::new(
+                ErrorKind::ConfigInvalid,
+                "post policy can only be signed by service account",
+            )
+        })?;
+
+        policy.sign(
+            &service_account.client_email,
+            &service_account.private_key,
+            Utc::now(),
+            expire,
+        )
+    }
+
+    /// Send the request and report the response to metrics hook.
     #[inline]
     pub async fn send(&self, req: Request<AsyncBody>) -> Result<Response<IncomingAsyncBody>> {
-        self.client.send(req).await
+        let hook = match &self.metrics_hook {
+            Some(hook) => hook,
+            None => return self.client.send(req).await,
+        };
+
+        let method = req.method().clone();
+        let resp = self.client.send(req).await?;
+        hook.on_response(&method, resp.status());
+        Ok(resp)
     }
 }
 
 impl GcsCore {
+    /// Insert headers of the customer-supplied encryption key if configured.
+    ///
+    /// The `x-goog-copy-source-encryption-*` variants will be used instead
+    /// if `copy_source` is true, which decrypt the source object of copy.
+    pub fn insert_encryption_headers(
+        &self,
+        mut req: http::request::Builder,
+        copy_source: bool,
+    ) -> http::request::Builder {
+        let (Some(key), Some(key_sha256)) = (&self.encryption_key, &self.encryption_key_sha256)
+        else {
+            return req;
+        };
+
+        let (algorithm_header, key_header, key_sha256_header) = if copy_source {
+            (
+                X_GOOG_COPY_SOURCE_ENCRYPTION_ALGORITHM,
+                X_GOOG_COPY_SOURCE_ENCRYPTION_KEY,
+                X_GOOG_COPY_SOURCE_ENCRYPTION_KEY_SHA256,
+            )
+        } else {
+            (
+                X_GOOG_ENCRYPTION_ALGORITHM,
+                X_GOOG_ENCRYPTION_KEY,
+                X_GOOG_ENCRYPTION_KEY_SHA256,
+            )
+        };
+
+        let mut key = key.clone();
+        key.set_sensitive(true);
+        let mut key_sha256 = key_sha256.clone();
+        key_sha256.set_sensitive(true);
+
+        req = req.header(algorithm_header, "AES256");
+        req = req.header(key_header, key);
+        req = req.header(key_sha256_header, key_sha256);
+        req
+    }
+
+    /// Append the query params shared by all requests creating objects.
+    ///
+    /// `default_storage_class` is not a query param, it's sent along with
+    /// the metadata of objects instead.
+    fn insert_object_query(&self, url: &mut String) {
+        if let Some(acl) = &self.predefined_acl {
+            write!(url, "&predefinedAcl={}", percent_encode_path(acl)).unwrap();
+        }
+        if let Some(kms_key_name) = &self.kms_key_name {
+            write!(url, "&kmsKeyName={}", percent_encode_path(kms_key_name)).unwrap();
+        }
+    }
+
     pub fn gcs_get_object_request(&self, path: &str, args: &OpRead) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
-        let url = format!(
-            "{}/storage/v1/b/{}/o/{}?alt=media",
+        let mut url = format!(
+            "{}/storage/{}/b/{}/o/{}?alt=media",
             self.endpoint,
+            self.api_version,
             self.bucket,
             percent_encode_path(&p)
         );
+        // The version of gcs objects is the generation, pin it in the same
+        // request so that metadata and content always come from one generation.
+        if let Some(generation) = args.version() {
+            write!(&mut url, "&generation={}", percent_encode_path(generation)).unwrap();
+        }
 
-        let mut req = Request::get(&url);
+        let mut req = self.insert_encryption_headers(Request::get(&url), false);
 
         if let Some(if_match) = args.if_match() {
             req = req.header(IF_MATCH, if_match);
@@ -167,6 +397,9 @@ impl GcsCore {
         if !args.range().is_full() {
             req = req.header(http::header::RANGE, args.range().to_header());
         }
+        if args.identity_encoding() {
+            req = req.header(ACCEPT_ENCODING, "identity");
+        }
 
         let req = req
             .body(AsyncBody::Empty)
@@ -183,7 +416,15 @@ impl GcsCore {
     ) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
-        let url = format!("{}/{}/{}", self.endpoint, self.bucket, p);
+        let mut url = format!(
+            "{}/{}/{}",
+            self.endpoint,
+            self.bucket,
+            percent_encode_xml_path(&p)
+        );
+        if let Some(generation) = args.version() {
+            write!(&mut url, "?generation={}", percent_encode_path(generation)).unwrap();
+        }
 
         let mut req = Request::get(&url);
 
@@ -222,8 +463,14 @@ impl GcsCore {
         op: &OpWrite,
         body: AsyncBody,
     ) -> Result<Request<AsyncBody>> {
-        let p = build_abs_path(&self.root, path);
+        let generation = op.if_match().map(parse_generation_from_etag).transpose()?;
+
+        self.gcs_insert_object_request_with_generation(path, size, op, generation, body)
+    }
 
+    /// Build the metadata of objects to insert, which is shared by both
+    /// the multipart and resumable uploads.
+    fn insert_object_metadata<'a>(&'a self, op: &'a OpWrite) -> HashMap<&'static str, &'a str> {
         let mut metadata = HashMap::new();
         if let Some(storage_class) = &self.default_storage_class {
             metadata.insert("storageClass", storage_class.as_str());
@@ -231,35 +478,73 @@ impl GcsCore {
         if let Some(cache_control) = op.cache_control() {
             metadata.insert("cacheControl", cache_control);
         }
+        if let Some(content_disposition) = op
+            .content_disposition()
+            .or(self.default_content_disposition.as_deref())
+        {
+            metadata.insert("contentDisposition", content_disposition);
+        }
+        metadata
+    }
+
+    /// Build the insert request with `ifGenerationMatch` set to the given
+    /// generation, `Some(0)` means the object must not exist.
+    pub fn gcs_insert_object_request_with_generation(
+        &self,
+        path: &str,
+        size: Option<u64>,
+        op: &OpWrite,
+        if_generation_match: Option<u64>,
+        body: AsyncBody,
+    ) -> Result<Request<AsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let content_md5 = if self.enable_content_md5 {
+            format_body_md5(&body)
+        } else {
+            None
+        };
+
+        let mut metadata = self.insert_object_metadata(op);
+
+        // Use the simple media upload if there is no metadata to send.
+        let media = metadata.is_empty();
 
         let mut url = format!(
-            "{}/upload/storage/v1/b/{}/o?uploadType={}&name={}",
+            "{}/upload/storage/{}/b/{}/o?uploadType={}&name={}",
             self.endpoint,
+            self.api_version,
             self.bucket,
-            if metadata.is_empty() {
-                "media"
-            } else {
-                "multipart"
-            },
+            if media { "media" } else { "multipart" },
             percent_encode_path(&p)
         );
 
-        if let Some(acl) = &self.predefined_acl {
-            write!(&mut url, "&predefinedAcl={}", acl).unwrap();
+        self.insert_object_query(&mut url);
+        if let Some(generation) = if_generation_match {
+            write!(&mut url, "&ifGenerationMatch={}", generation).unwrap();
         }
 
-        let mut req = Request::post(&url);
+        let mut req = self.insert_encryption_headers(Request::post(&url), false);
 
         req = req.header(CONTENT_LENGTH, size.unwrap_or_default());
 
-        if metadata.is_empty() {
+        if media {
             if let Some(content_type) = op.content_type() {
                 req = req.header(CONTENT_TYPE, content_type);
             }
+            if let Some(content_md5) = &content_md5 {
+                req = req.header(CONTENT_MD5, content_md5);
+            }
 
             let req = req.body(body).map_err(new_request_build_error)?;
             Ok(req)
         } else {
+            // `Content-MD5` of the request covers the whole multipart body,
+            // so we send the MD5 of the media via metadata instead.
+            if let Some(content_md5) = &content_md5 {
+                metadata.insert("md5Hash", content_md5);
+            }
+
             let mut multipart = Multipart::new();
 
             multipart = multipart.part(
@@ -297,7 +582,7 @@ impl GcsCore {
 
             multipart = multipart.part(media_part);
 
-            let req = multipart.apply(Request::post(url))?;
+            let req = multipart.apply(self.insert_encryption_headers(Request::post(url), false))?;
             Ok(req)
         }
     }
@@ -311,7 +596,12 @@ impl GcsCore {
     ) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
-        let url = format!("{}/{}/{}", self.endpoint, self.bucket, p);
+        let url = format!(
+            "{}/{}/{}",
+            self.endpoint,
+            self.bucket,
+            percent_encode_xml_path(&p)
+        );
 
         let mut req = Request::put(&url);
 
@@ -319,6 +609,13 @@ impl GcsCore {
             req = req.header(CONTENT_TYPE, content_type);
         }
 
+        if let Some(content_disposition) = args
+            .content_disposition()
+            .or(self.default_content_disposition.as_deref())
+        {
+            req = req.header(CONTENT_DISPOSITION, content_disposition);
+        }
+
         if let Some(acl) = &self.predefined_acl {
             req = req.header("x-goog-acl", acl);
         }
@@ -335,14 +632,21 @@ impl GcsCore {
     pub fn gcs_head_object_request(&self, path: &str, args: &OpStat) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
-        let url = format!(
-            "{}/storage/v1/b/{}/o/{}",
+        let mut url = format!(
+            "{}/storage/{}/b/{}/o/{}",
             self.endpoint,
+            self.api_version,
             self.bucket,
             percent_encode_path(&p)
         );
+        // The version of gcs objects is the generation.
+        if let Some(generation) = args.version() {
+            write!(&mut url, "?generation={}", percent_encode_path(generation)).unwrap();
+        }
 
-        let mut req = Request::get(&url);
+        // Metadata like `md5Hash` of CSEK encrypted objects is only returned
+        // with the key.
+        let mut req = self.insert_encryption_headers(Request::get(&url), false);
 
         if let Some(if_none_match) = args.if_none_match() {
             req = req.header(IF_NONE_MATCH, if_none_match);
@@ -367,7 +671,15 @@ impl GcsCore {
     ) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
-        let url = format!("{}/{}/{}", self.endpoint, self.bucket, p);
+        let mut url = format!(
+            "{}/{}/{}",
+            self.endpoint,
+            self.bucket,
+            percent_encode_xml_path(&p)
+        );
+        if let Some(generation) = args.version() {
+            write!(&mut url, "?generation={}", percent_encode_path(generation)).unwrap();
+        }
 
         let mut req = Request::head(&url);
 
@@ -398,6 +710,20 @@ impl GcsCore {
         self.send(req).await
     }
 
+    pub async fn gcs_get_bucket(&self) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!(
+            "{}/storage/{}/b/{}?fields=iamConfiguration",
+            self.endpoint, self.api_version, self.bucket
+        );
+
+        let mut req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.sign(&mut req).await?;
+        self.send(req).await
+    }
+
     pub async fn gcs_delete_object(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
         let mut req = self.gcs_delete_object_request(path)?;
 
@@ -409,8 +735,9 @@ impl GcsCore {
         let p = build_abs_path(&self.root, path);
 
         let url = format!(
-            "{}/storage/v1/b/{}/o/{}",
+            "{}/storage/{}/b/{}/o/{}",
             self.endpoint,
+            self.api_version,
             self.bucket,
             percent_encode_path(&p)
         );
@@ -424,9 +751,12 @@ impl GcsCore {
         &self,
         paths: Vec<String>,
     ) -> Result<Response<IncomingAsyncBody>> {
-        let uri = format!("{}/batch/storage/v1", self.endpoint);
+        let uri = format!("{}/batch/storage/{}", self.endpoint, self.api_version);
 
         let mut multipart = Multipart::new();
+        if let Some(boundary) = &self.batch_boundary {
+            multipart = multipart.with_boundary(boundary);
+        }
 
         for (idx, path) in paths.iter().enumerate() {
             let req = self.gcs_delete_object_request(path)?;
@@ -447,23 +777,69 @@ impl GcsCore {
         &self,
         from: &str,
         to: &str,
+        args: &OpCopy,
     ) -> Result<Response<IncomingAsyncBody>> {
         let source = build_abs_path(&self.root, from);
         let dest = build_abs_path(&self.root, to);
 
-        let req_uri = format!(
-            "{}/storage/v1/b/{}/o/{}/copyTo/b/{}/o/{}",
+        let mut req_uri = format!(
+            "{}/storage/{}/b/{}/o/{}/copyTo/b/{}/o/{}",
             self.endpoint,
+            self.api_version,
             self.bucket,
             percent_encode_path(&source),
             self.bucket,
             percent_encode_path(&dest)
         );
+        let mut query = vec![];
+        // The version of gcs objects is the generation.
+        if let Some(generation) = args.source_version() {
+            query.push(format!(
+                "sourceGeneration={}",
+                percent_encode_path(generation)
+            ));
+        }
+        // Objects created by copy get the same acl as the inserted ones.
+        if let Some(acl) = &self.predefined_acl {
+            query.push(format!(
+                "destinationPredefinedAcl={}",
+                percent_encode_path(acl)
+            ));
+        }
+        if !query.is_empty() {
+            write!(&mut req_uri, "?{}", query.join("&")).unwrap();
+        }
 
-        let mut req = Request::post(req_uri)
-            .header(CONTENT_LENGTH, 0)
-            .body(AsyncBody::Empty)
-            .map_err(new_request_build_error)?;
+        // Metadata in the body will override the destination's metadata.
+        let mut metadata = HashMap::new();
+        if let Some(content_type) = args.content_type() {
+            metadata.insert("contentType", content_type);
+        }
+        if let Some(cache_control) = args.cache_control() {
+            metadata.insert("cacheControl", cache_control);
+        }
+        // GCS takes the destination's metadata from the body instead of the
+        // source if it's not empty, so the default content type is sent to
+        // reset the others.
+        if args.reset_metadata() && !metadata.contains_key("contentType") {
+            metadata.insert("contentType", "application/octet-stream");
+        }
+
+        // The destination is encrypted with the same key as the source.
+        let req = self.insert_encryption_headers(Request::post(req_uri), true);
+        let req = self.insert_encryption_headers(req, false);
+
+        let mut req = if metadata.is_empty() {
+            req.header(CONTENT_LENGTH, 0)
+                .body(AsyncBody::Empty)
+                .map_err(new_request_build_error)?
+        } else {
+            let bs = Bytes::from(json!(metadata).to_string());
+            req.header(CONTENT_TYPE, "application/json")
+                .header(CONTENT_LENGTH, bs.len())
+                .body(AsyncBody::Bytes(bs))
+                .map_err(new_request_build_error)?
+        };
 
         self.sign(&mut req).await?;
         self.send(req).await
@@ -476,12 +852,14 @@ impl GcsCore {
         delimiter: &str,
         limit: Option<usize>,
         start_after: Option<String>,
+        soft_deleted: bool,
     ) -> Result<Response<IncomingAsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
         let mut url = format!(
-            "{}/storage/v1/b/{}/o?prefix={}",
+            "{}/storage/{}/b/{}/o?prefix={}",
             self.endpoint,
+            self.api_version,
             self.bucket,
             percent_encode_path(&p)
         );
@@ -496,6 +874,9 @@ impl GcsCore {
             write!(url, "&startOffset={}", percent_encode_path(&start_after))
                 .expect("write into string must succeed");
         }
+        if soft_deleted {
+            url.push_str("&softDeleted=true");
+        }
 
         if !page_token.is_empty() {
             // NOTE:
@@ -520,17 +901,50 @@ impl GcsCore {
     pub async fn gcs_initiate_resumable_upload(
         &self,
         path: &str,
+        op: &OpWrite,
     ) -> Result<Response<IncomingAsyncBody>> {
         let p = build_abs_path(&self.root, path);
-        let url = format!(
-            "{}/upload/storage/v1/b/{}/o?uploadType=resumable&name={}",
-            self.endpoint, self.bucket, p
+        let mut url = format!(
+            "{}/upload/storage/{}/b/{}/o?uploadType=resumable&name={}",
+            self.endpoint,
+            self.api_version,
+            self.bucket,
+            percent_encode_path(&p)
         );
+        self.insert_object_query(&mut url);
+        if let Some(etag) = op.if_match() {
+            let generation = parse_generation_from_etag(etag)?;
+            write!(&mut url, "&ifGenerationMatch={}", generation).unwrap();
+        }
 
-        let mut req = Request::post(&url)
-            .header(CONTENT_LENGTH, 0)
-            .body(AsyncBody::Empty)
-            .map_err(new_request_build_error)?;
+        let mut req = self.insert_encryption_headers(Request::post(&url), false);
+
+        // Declare the object's content type and length up front so that GCS
+        // could reject mismatched uploads early.
+        if let Some(content_type) = op.content_type() {
+            req = req.header(X_UPLOAD_CONTENT_TYPE, content_type);
+        }
+        if let Some(content_length) = op.content_length() {
+            req = req.header(X_UPLOAD_CONTENT_LENGTH, content_length);
+        }
+
+        // The object's metadata is sent in the body of the initiation.
+        let mut metadata = self.insert_object_metadata(op);
+        if let Some(content_type) = op.content_type() {
+            metadata.insert("contentType", content_type);
+        }
+        let body = if metadata.is_empty() {
+            req = req.header(CONTENT_LENGTH, 0);
+            AsyncBody::Empty
+        } else {
+            let bs = Bytes::from(json!(metadata).to_string());
+            req = req
+                .header(CONTENT_TYPE, "application/json; charset=UTF-8")
+                .header(CONTENT_LENGTH, bs.len());
+            AsyncBody::Bytes(bs)
+        };
+
+        let mut req = req.body(body).map_err(new_request_build_error)?;
 
         self.sign(&mut req).await?;
         self.send(req).await
@@ -543,7 +957,8 @@ impl GcsCore {
         written: u64,
         body: AsyncBody,
     ) -> Result<Request<AsyncBody>> {
-        let mut req = Request::put(location);
+        // GCS requires the encryption key in every request of the session.
+        let mut req = self.insert_encryption_headers(Request::put(location), false);
 
         let range_header = format!("bytes {}-{}/*", written, written + size - 1);
 
@@ -564,7 +979,8 @@ impl GcsCore {
         size: u64,
         body: AsyncBody,
     ) -> Result<Response<IncomingAsyncBody>> {
-        let mut req = Request::post(location)
+        let mut req = self
+            .insert_encryption_headers(Request::post(location), false)
             .header(CONTENT_LENGTH, size)
             .header(
                 CONTENT_RANGE,
@@ -597,3 +1013,249 @@ impl GcsCore {
         self.send(req).await
     }
 }
+
+/// Normalize gcs etag into the canonical unquoted form.
+///
+/// Etags returned by JSON API like `stat` and `list` are unquoted, while the
+/// `ETag` header of downloads could be quoted. Strip the quotes so that the
+/// same object always yields the same etag, like `CKWasoTgyPkCEAE=`.
+pub fn normalize_etag(etag: &str) -> &str {
+    etag.trim_matches('"')
+}
+
+/// The key of service account in credential file.
+#[derive(Deserialize)]
+pub struct ServiceAccountKey {
+    pub client_email: String,
+    pub private_key: String,
+}
+
+/// Parse the object's generation from its etag.
+///
+/// GCS etag returned by JSON API is the base64 encoded protobuf message of
+/// generation and metageneration, for example `CKWasoTgyPkCEAE=` is
+/// generation `1660563214863653` with metageneration `1`.
+///
+/// # Notes
+///
+/// This encoding is not documented by GCS and only works for etags returned
+/// by JSON API. Etags returned by XML API are MD5 of the content, and will be
+/// rejected as invalid.
+pub fn parse_generation_from_etag(etag: &str) -> Result<u64> {
+    let invalid = || {
+        Error::new(ErrorKind::InvalidInput, "etag is not a valid gcs etag")
+            .with_context("etag", etag)
+    };
+
+    let bs = BASE64_STANDARD
+        .decode(normalize_etag(etag))
+        .map_err(|err| invalid().set_source(err))?;
+
+    // The first byte must be the tag of field 1 (generation) with varint type.
+    let (tag, bs) = bs.split_first().ok_or_else(invalid)?;
+    if *tag != 0x08 {
+        return Err(invalid());
+    }
+
+    let mut generation = 0u64;
+    for (i, b) in bs.iter().enumerate().take(10) {
+        generation |= u64::from(b & 0x7f) << (7 * i);
+        if b & 0x80 == 0 {
+            return Ok(generation);
+        }
+    }
+
+    Err(invalid())
+}
+
+/// The object resource returned by GCS once an upload is committed.
+///
+/// Both simple uploads and the final request of a resumable upload respond
+/// with the [object resource](https://cloud.google.com/storage/docs/json_api/v1/objects#resource),
+/// so callers can verify the written object without another `stat`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GcsObject {
+    /// Name of the object.
+    pub name: String,
+    /// GCS will return size in string.
+    ///
+    /// For example: `"size": "56535"`
+    pub size: String,
+    /// Base64 encoded MD5 of the object, composite objects don't have it.
+    pub md5_hash: String,
+    /// Base64 encoded CRC32C of the object.
+    pub crc32c: String,
+    /// Generation of the object, also in string.
+    ///
+    /// For example: `"generation": "1660563214863653"`
+    pub generation: String,
+    /// etag is not quoted.
+    pub etag: String,
+}
+
+/// Checksums advertised by GCS in the `X-Goog-Hash` header.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GoogHash {
+    /// Base64 encoded CRC32C of the object.
+    pub crc32c: Option<String>,
+    /// Base64 encoded MD5 of the object, composite objects don't have it.
+    pub md5: Option<String>,
+}
+
+/// Parse the `X-Goog-Hash` header like `crc32c=n03x6A==,md5=Ojk9c3dhfxgoKVVHYwFbHQ==`.
+///
+/// GCS could send the checksums in one header separated by comma, or in
+/// multiple headers.
+pub fn parse_goog_hash(headers: &HeaderMap) -> Result<GoogHash> {
+    let mut hash = GoogHash::default();
+
+    for v in headers.get_all(X_GOOG_HASH) {
+        let v = v.to_str().map_err(|err| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("gcs::parse_goog_hash")
+            .set_source(err)
+        })?;
+
+        for pair in v.split(',') {
+            match pair.trim().split_once('=') {
+                Some(("crc32c", value)) => hash.crc32c = Some(value.to_string()),
+                Some(("md5", value)) => hash.md5 = Some(value.to_string()),
+                // Ignore unknown hashes for forward compatibility.
+                _ => {}
+            }
+        }
+    }
+
+    Ok(hash)
+}
+
+/// Parse the base64 encoded MD5 from `Content-MD5` header.
+pub fn parse_content_md5(headers: &HeaderMap) -> Result<Option<&str>> {
+    headers
+        .get(CONTENT_MD5)
+        .map(|v| {
+            v.to_str().map_err(|err| {
+                Error::new(
+                    ErrorKind::Unexpected,
+                    "header value is not valid utf-8 string",
+                )
+                .with_operation("gcs::parse_content_md5")
+                .set_source(err)
+            })
+        })
+        .transpose()
+}
+
+/// Calculate the base64 encoded MD5 of the body.
+///
+/// Returns `None` for stream bodies, since they can't be read twice.
+pub fn format_body_md5(body: &AsyncBody) -> Option<String> {
+    match body {
+        AsyncBody::Empty => Some(format_content_md5(&[])),
+        AsyncBody::Bytes(bs) => Some(format_content_md5(bs)),
+        AsyncBody::ChunkedBytes(bs) => {
+            let mut hasher = Md5::new();
+            for chunk in bs.vectored_chunk() {
+                hasher.update(&*chunk);
+            }
+            Some(BASE64_STANDARD.encode(hasher.finalize()))
+        }
+        AsyncBody::Stream(_) => None,
+    }
+}
+
+/// Parse the object's generation from `x-goog-generation` header.
+pub fn parse_generation(headers: &HeaderMap) -> Result<Option<&str>> {
+    headers
+        .get(X_GOOG_GENERATION)
+        .map(|v| {
+            v.to_str().map_err(|err| {
+                Error::new(
+                    ErrorKind::Unexpected,
+                    "header value is not valid utf-8 string",
+                )
+                .with_operation("gcs::parse_generation")
+                .with_context("header", X_GOOG_GENERATION)
+                .set_source(err)
+            })
+        })
+        .transpose()
+}
+
+/// Check whether the response is transcoded from the stored content.
+///
+/// GCS serves objects stored with `Content-Encoding: gzip` decompressed
+/// (a.k.a. decompressive transcoding) unless the request accepts gzip, the
+/// stored encoding is only kept in `x-goog-stored-content-encoding` then.
+pub fn is_transcoded(headers: &HeaderMap) -> Result<bool> {
+    let stored = match headers.get(X_GOOG_STORED_CONTENT_ENCODING) {
+        Some(v) => v.to_str().map_err(|err| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("gcs::is_transcoded")
+            .set_source(err)
+        })?,
+        None => return Ok(false),
+    };
+    if stored.eq_ignore_ascii_case("identity") {
+        return Ok(false);
+    }
+
+    let served = match headers.get(http::header::CONTENT_ENCODING) {
+        Some(v) => v.to_str().unwrap_or_default(),
+        None => "identity",
+    };
+    Ok(!served.eq_ignore_ascii_case(stored))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_goog_hash() {
+        let cases = vec![
+            (
+                "crc32c and md5",
+                vec!["crc32c=n03x6A==,md5=Ojk9c3dhfxgoKVVHYwFbHQ=="],
+                GoogHash {
+                    crc32c: Some("n03x6A==".to_string()),
+                    md5: Some("Ojk9c3dhfxgoKVVHYwFbHQ==".to_string()),
+                },
+            ),
+            (
+                "multiple headers",
+                vec!["crc32c=n03x6A==", "md5=Ojk9c3dhfxgoKVVHYwFbHQ=="],
+                GoogHash {
+                    crc32c: Some("n03x6A==".to_string()),
+                    md5: Some("Ojk9c3dhfxgoKVVHYwFbHQ==".to_string()),
+                },
+            ),
+            (
+                "composite object",
+                vec!["crc32c=n03x6A=="],
+                GoogHash {
+                    crc32c: Some("n03x6A==".to_string()),
+                    md5: None,
+                },
+            ),
+            ("no header", vec![], GoogHash::default()),
+        ];
+
+        for (name, values, expected) in cases {
+            let mut headers = HeaderMap::new();
+            for v in values {
+                headers.append(X_GOOG_HASH, HeaderValue::from_static(v));
+            }
+
+            let actual = parse_goog_hash(&headers).expect("parse must succeed");
+            assert_eq!(actual, expected, "{name}");
+        }
+    }
+}
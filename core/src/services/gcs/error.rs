@@ -49,17 +49,68 @@ struct GcsErrorDetail {
     reason: String,
 }
 
+/// Reasons of errors caused by exhausted quota or rate limit.
+///
+/// GCS returns them with `429` or `403`, which should be retried with backoff
+/// instead of being treated as permission denied.
+const RATE_LIMITED_REASONS: [&str; 3] = [
+    "rateLimitExceeded",
+    "userRateLimitExceeded",
+    "quotaExceeded",
+];
+
+/// Reasons of errors caused by missing permissions.
+///
+/// They are mostly returned with `403`, but GCS could also use them for
+/// other status like `401` returned by some proxies.
+const PERMISSION_DENIED_REASONS: [&str; 3] =
+    ["forbidden", "accessDenied", "insufficientPermissions"];
+
 /// Parse error response into Error.
 pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     let (parts, body) = resp.into_parts();
     let bs = body.bytes().await?;
 
+    let (message, reasons, locations) = match de::from_slice::<GcsErrorResponse>(&bs) {
+        Ok(gcs_err) => {
+            let reasons = gcs_err
+                .error
+                .errors
+                .iter()
+                .map(|v| v.reason.clone())
+                .filter(|v| !v.is_empty())
+                .collect::<Vec<_>>();
+            let locations = gcs_err
+                .error
+                .errors
+                .iter()
+                .map(|v| v.location.clone())
+                .filter(|v| !v.is_empty())
+                .collect::<Vec<_>>();
+            (format!("{gcs_err:?}"), reasons, locations)
+        }
+        Err(_) => (String::from_utf8_lossy(&bs).into_owned(), vec![], vec![]),
+    };
+    let rate_limited = reasons
+        .iter()
+        .any(|v| RATE_LIMITED_REASONS.contains(&v.as_str()));
+    let permission_denied = reasons
+        .iter()
+        .any(|v| PERMISSION_DENIED_REASONS.contains(&v.as_str()));
+    let not_found = reasons.iter().any(|v| v == "notFound");
+
     let (kind, retryable) = match parts.status {
+        // All failed preconditions are mapped to `ConditionNotMatch`
+        // regardless of the reason, so that users could handle them in one
+        // place for reads, writes, deletes and copies.
+        StatusCode::PRECONDITION_FAILED => (ErrorKind::ConditionNotMatch, false),
+        _ if rate_limited => (ErrorKind::RateLimited, true),
+        StatusCode::TOO_MANY_REQUESTS => (ErrorKind::RateLimited, true),
+        _ if permission_denied => (ErrorKind::PermissionDenied, false),
+        _ if not_found => (ErrorKind::NotFound, false),
         StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
         StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
-        StatusCode::PRECONDITION_FAILED | StatusCode::NOT_MODIFIED => {
-            (ErrorKind::ConditionNotMatch, false)
-        }
+        StatusCode::NOT_MODIFIED => (ErrorKind::ConditionNotMatch, false),
         StatusCode::INTERNAL_SERVER_ERROR
         | StatusCode::BAD_GATEWAY
         | StatusCode::SERVICE_UNAVAILABLE
@@ -67,12 +118,26 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
         _ => (ErrorKind::Unexpected, false),
     };
 
-    let message = match de::from_slice::<GcsErrorResponse>(&bs) {
-        Ok(gcs_err) => format!("{gcs_err:?}"),
-        Err(_) => String::from_utf8_lossy(&bs).into_owned(),
+    // GCS returns `400` with messages like `Provided MD5 hash "..." doesn't
+    // match calculated MD5 hash "..."` if the content is corrupted.
+    let mut err = if parts.status == StatusCode::BAD_REQUEST && message.contains("MD5 hash") {
+        Error::new(kind, "uploaded content doesn't match the given Content-MD5")
+            .with_context("response", message)
+    } else {
+        Error::new(kind, &message)
     };
 
-    let mut err = Error::new(kind, &message);
+    // Keep the detailed reason like `retentionPolicyNotMet`, which is
+    // important for errors of batch sub-requests.
+    if !reasons.is_empty() {
+        err = err.with_context("reason", reasons.join(", "));
+    }
+
+    // GCS reports the failed precondition like `If-Match` or
+    // `ifGenerationMatch` as the location of the error.
+    if parts.status == StatusCode::PRECONDITION_FAILED && !locations.is_empty() {
+        err = err.with_context("precondition", locations.join(", "));
+    }
 
     err = with_error_response_context(err, parts);
 
@@ -85,8 +150,94 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
 
 #[cfg(test)]
 mod tests {
+    use futures::stream;
+
     use super::*;
 
+    fn new_error_response(status: StatusCode, reason: &str) -> Response<IncomingAsyncBody> {
+        let bs = bytes::Bytes::from(format!(
+            r#"{{"error": {{"errors": [{{"domain": "usageLimits", "reason": "{reason}", "message": "limit exceeded"}}], "code": {}, "message": "limit exceeded"}}}}"#,
+            status.as_u16()
+        ));
+        let body =
+            IncomingAsyncBody::new(Box::new(oio::into_stream(stream::iter(vec![Ok(bs)]))), None);
+        Response::builder().status(status).body(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_parse_rate_limited_error() -> Result<()> {
+        let cases = [
+            (StatusCode::TOO_MANY_REQUESTS, "rateLimitExceeded"),
+            (StatusCode::FORBIDDEN, "userRateLimitExceeded"),
+            (StatusCode::FORBIDDEN, "quotaExceeded"),
+            (StatusCode::TOO_MANY_REQUESTS, "tooManyRequests"),
+        ];
+
+        for (status, reason) in cases {
+            let err = parse_error(new_error_response(status, reason)).await?;
+            assert_eq!(err.kind(), ErrorKind::RateLimited, "{reason}");
+            assert!(err.is_temporary(), "{reason}");
+            assert!(
+                err.to_string().contains(&format!("reason: {reason}")),
+                "{reason}"
+            );
+        }
+
+        // Other reasons of 403 are still permission denied.
+        let err = parse_error(new_error_response(StatusCode::FORBIDDEN, "forbidden")).await?;
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert!(!err.is_temporary());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_permission_denied_and_not_found_error() -> Result<()> {
+        let cases = [
+            (
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                ErrorKind::PermissionDenied,
+            ),
+            (
+                StatusCode::FORBIDDEN,
+                "accessDenied",
+                ErrorKind::PermissionDenied,
+            ),
+            (
+                StatusCode::UNAUTHORIZED,
+                "insufficientPermissions",
+                ErrorKind::PermissionDenied,
+            ),
+            (StatusCode::NOT_FOUND, "notFound", ErrorKind::NotFound),
+            (StatusCode::BAD_REQUEST, "notFound", ErrorKind::NotFound),
+        ];
+
+        for (status, reason, kind) in cases {
+            let err = parse_error(new_error_response(status, reason)).await?;
+            assert_eq!(err.kind(), kind, "{reason}");
+            assert!(!err.is_temporary(), "{reason}");
+            assert!(
+                err.to_string().contains(&format!("reason: {reason}")),
+                "{reason}"
+            );
+        }
+
+        // Media downloads return plain text instead of json.
+        let bs = bytes::Bytes::from("No such object: bucket/path/to/file");
+        let body =
+            IncomingAsyncBody::new(Box::new(oio::into_stream(stream::iter(vec![Ok(bs)]))), None);
+        let resp = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(body)
+            .unwrap();
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(err.to_string().contains("No such object"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_parse_error() {
         let bs = bytes::Bytes::from(
@@ -0,0 +1,45 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use http::Method;
+use http::StatusCode;
+
+/// Hook to observe requests sent to GCS, which could be used to export metrics
+/// like requests by method and status, bytes read and written.
+///
+/// Retries happen in [`RetryLayer`](crate::layers::RetryLayer), which could
+/// be observed by its `RetryInterceptor`.
+///
+/// All methods do nothing by default, so implementors only need to override
+/// the events they care about. Please make sure they are cheap since they are
+/// called on the IO path.
+pub trait GcsMetricsHook: Send + Sync + 'static {
+    /// Called after a response is received from GCS.
+    fn on_response(&self, method: &Method, status: StatusCode) {
+        let _ = (method, status);
+    }
+
+    /// Called after bytes are read from GCS.
+    fn on_read_bytes(&self, n: u64) {
+        let _ = n;
+    }
+
+    /// Called after bytes are written to GCS.
+    fn on_write_bytes(&self, n: u64) {
+        let _ = n;
+    }
+}
@@ -16,10 +16,19 @@
 // under the License.
 
 mod backend;
+pub use backend::GcsBackend;
 pub use backend::GcsBuilder as Gcs;
+pub use backend::GcsMetadata;
+pub use backend::GcsPathKind;
+pub use backend::GcsStatResult;
 
 mod core;
 mod error;
+mod metrics;
+pub use metrics::GcsMetricsHook;
 mod pager;
+mod post_policy;
+mod reader;
+mod signed_url;
 mod uri;
 mod writer;
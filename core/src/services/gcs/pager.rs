@@ -21,6 +21,7 @@ use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json;
 
+use super::core::normalize_etag;
 use super::core::GcsCore;
 use super::error::parse_error;
 use crate::raw::*;
@@ -38,6 +39,11 @@ pub struct GcsPager {
     /// Filter results to objects whose names are lexicographically
     /// **equal to or after** startOffset
     start_after: Option<String>,
+    /// List soft deleted objects only.
+    soft_deleted: bool,
+    /// Only return entries of this mode, `FILE` for `items` and `DIR` for
+    /// `prefixes`.
+    entry_mode: Option<EntryMode>,
 
     page_token: String,
     done: bool,
@@ -51,6 +57,7 @@ impl GcsPager {
         recursive: bool,
         limit: Option<usize>,
         start_after: Option<&str>,
+        soft_deleted: bool,
     ) -> Self {
         let delimiter = if recursive { "" } else { "/" };
         Self {
@@ -60,11 +67,18 @@ impl GcsPager {
             delimiter,
             limit,
             start_after: start_after.map(String::from),
+            soft_deleted,
+            entry_mode: None,
 
             page_token: "".to_string(),
             done: false,
         }
     }
+
+    pub(super) fn with_entry_mode(mut self, entry_mode: Option<EntryMode>) -> Self {
+        self.entry_mode = entry_mode;
+        self
+    }
 }
 
 #[async_trait]
@@ -82,6 +96,7 @@ impl oio::Page for GcsPager {
                 self.delimiter,
                 self.limit,
                 self.start_after.clone(),
+                self.soft_deleted,
             )
             .await?;
 
@@ -101,7 +116,13 @@ impl oio::Page for GcsPager {
 
         let mut entries = Vec::with_capacity(output.prefixes.len() + output.items.len());
 
-        for prefix in output.prefixes {
+        let (list_dirs, list_files) = match self.entry_mode {
+            Some(EntryMode::DIR) => (true, false),
+            Some(EntryMode::FILE) => (false, true),
+            _ => (true, true),
+        };
+
+        for prefix in output.prefixes.into_iter().filter(|_| list_dirs) {
             let de = oio::Entry::new(
                 &build_rel_path(&self.core.root, &prefix),
                 Metadata::new(EntryMode::DIR),
@@ -110,7 +131,9 @@ impl oio::Page for GcsPager {
             entries.push(de);
         }
 
-        for object in output.items {
+        for object in output.items.into_iter().filter(|_| list_files) {
+            // Skip directory markers written by create_dir, including the
+            // marker of the listing dir itself.
             if object.name.ends_with('/') {
                 continue;
             }
@@ -125,7 +148,7 @@ impl oio::Page for GcsPager {
 
             // set metadata fields
             meta.set_content_md5(object.md5_hash.as_str());
-            meta.set_etag(object.etag.as_str());
+            meta.set_etag(normalize_etag(&object.etag));
 
             let size = object.size.parse().map_err(|e| {
                 Error::new(ErrorKind::Unexpected, "parse u64 from list response").set_source(e)
@@ -136,6 +159,12 @@ impl oio::Page for GcsPager {
             }
 
             meta.set_last_modified(parse_datetime_from_rfc3339(object.updated.as_str())?);
+            if let Some(v) = &object.soft_delete_time {
+                meta.set_soft_delete_time(parse_datetime_from_rfc3339(v)?);
+            }
+            if let Some(v) = &object.hard_delete_time {
+                meta.set_hard_delete_time(parse_datetime_from_rfc3339(v)?);
+            }
 
             let de = oio::Entry::new(path, meta);
 
@@ -151,21 +180,21 @@ impl oio::Page for GcsPager {
 /// refer to https://cloud.google.com/storage/docs/json_api/v1/objects/list for details
 #[derive(Default, Debug, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
-struct ListResponse {
+pub(super) struct ListResponse {
     /// The continuation token.
     ///
     /// If this is the last page of results, then no continuation token is returned.
     next_page_token: Option<String>,
     /// Object name prefixes for objects that matched the listing request
     /// but were excluded from [items] because of a delimiter.
-    prefixes: Vec<String>,
+    pub(super) prefixes: Vec<String>,
     /// The list of objects, ordered lexicographically by name.
-    items: Vec<ListResponseItem>,
+    pub(super) items: Vec<ListResponseItem>,
 }
 
 #[derive(Default, Debug, Eq, PartialEq, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
-struct ListResponseItem {
+pub(super) struct ListResponseItem {
     name: String,
     size: String,
     // metadata
@@ -173,6 +202,10 @@ struct ListResponseItem {
     md5_hash: String,
     updated: String,
     content_type: String,
+    /// Only returned for soft deleted objects.
+    soft_delete_time: Option<String>,
+    /// Only returned for soft deleted objects.
+    hard_delete_time: Option<String>,
 }
 
 #[cfg(test)]
@@ -0,0 +1,249 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use md5::Digest;
+use md5::Md5;
+
+use super::metrics::GcsMetricsHook;
+use crate::raw::*;
+use crate::*;
+
+/// GcsReader reports the bytes read to the metrics hook if registered, fails
+/// the read if no data arrives within the read timeout, and verifies the MD5
+/// and CRC32C of content at the end if expected.
+pub struct GcsReader {
+    inner: IncomingAsyncBody,
+    hook: Option<Arc<dyn GcsMetricsHook>>,
+
+    read_timeout: Option<Duration>,
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+
+    /// The expected base64 encoded MD5 and the hasher of content read so far.
+    md5: Option<(String, Md5)>,
+    /// The expected base64 encoded CRC32C and the checksum of content read
+    /// so far, which is updated incrementally.
+    crc32c: Option<(String, u32)>,
+    /// The length of content and the size read so far, checksums are
+    /// verified once the whole content is read.
+    content_length: Option<u64>,
+    read_size: u64,
+}
+
+impl GcsReader {
+    pub fn new(inner: IncomingAsyncBody, hook: Option<Arc<dyn GcsMetricsHook>>) -> Self {
+        Self {
+            inner,
+            hook,
+            read_timeout: None,
+            sleep: None,
+            md5: None,
+            crc32c: None,
+            content_length: None,
+            read_size: 0,
+        }
+    }
+
+    /// Set the length of content to verify checksums as soon as the whole
+    /// content is read.
+    ///
+    /// Callers like `RangeReader` stop reading once the content length is
+    /// reached, so EOF may never be polled.
+    pub fn with_content_length(mut self, size: Option<u64>) -> Self {
+        self.content_length = size;
+        self
+    }
+
+    /// Set the expected MD5 of the whole content to verify.
+    pub fn with_expected_md5(mut self, md5: Option<String>) -> Self {
+        self.md5 = md5.map(|v| (v, Md5::new()));
+        self
+    }
+
+    /// Set the expected CRC32C of the whole content to verify.
+    pub fn with_expected_crc32c(mut self, crc32c: Option<String>) -> Self {
+        self.crc32c = crc32c.map(|v| (v, 0));
+        self
+    }
+
+    fn update_checksums(&mut self, bs: &[u8]) {
+        if let Some((_, hasher)) = &mut self.md5 {
+            hasher.update(bs);
+        }
+        if let Some((_, checksum)) = &mut self.crc32c {
+            *checksum = crc32c::crc32c_append(*checksum, bs);
+        }
+    }
+
+    fn on_read(&mut self, bs: &[u8]) -> Result<()> {
+        self.update_checksums(bs);
+        self.read_size += bs.len() as u64;
+        if self.content_length == Some(self.read_size) {
+            self.verify_checksums()?;
+        }
+        Ok(())
+    }
+
+    fn verify_checksums(&mut self) -> Result<()> {
+        self.verify_md5()?;
+        self.verify_crc32c()
+    }
+
+    /// Compare the MD5 of content read with the expected one.
+    fn verify_md5(&mut self) -> Result<()> {
+        let Some((expected, hasher)) = self.md5.take() else {
+            return Ok(());
+        };
+
+        let actual = BASE64_STANDARD.encode(hasher.finalize());
+        if actual == expected {
+            Ok(())
+        } else {
+            // The content could be corrupted during transfer, read again
+            // might succeed.
+            Err(
+                Error::new(ErrorKind::Unexpected, "md5 mismatch of read content")
+                    .with_context("expected", expected)
+                    .with_context("actual", actual)
+                    .set_temporary(),
+            )
+        }
+    }
+
+    /// Compare the CRC32C of content read with the expected one.
+    ///
+    /// GCS encodes CRC32C in base64 with big-endian byte order.
+    fn verify_crc32c(&mut self) -> Result<()> {
+        let Some((expected, checksum)) = self.crc32c.take() else {
+            return Ok(());
+        };
+
+        let actual = BASE64_STANDARD.encode(checksum.to_be_bytes());
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(
+                Error::new(ErrorKind::Unexpected, "crc32c mismatch of read content")
+                    .with_context("expected", expected)
+                    .with_context("actual", actual)
+                    .set_temporary(),
+            )
+        }
+    }
+
+    /// Set the max duration to wait for the next chunk of data.
+    pub fn with_read_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Poll the read timer while the inner reader is pending, returns an
+    /// error if it's expired.
+    fn poll_timeout(&mut self, cx: &mut Context<'_>) -> Result<()> {
+        let Some(timeout) = self.read_timeout else {
+            return Ok(());
+        };
+
+        let sleep = self
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+        match sleep.as_mut().poll(cx) {
+            Poll::Pending => Ok(()),
+            Poll::Ready(()) => {
+                self.sleep = None;
+                Err(
+                    Error::new(ErrorKind::Unexpected, "read timeout while waiting for data")
+                        .with_context("timeout", timeout.as_secs_f64().to_string())
+                        .set_temporary(),
+                )
+            }
+        }
+    }
+}
+
+impl oio::Read for GcsReader {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let n = match self.inner.poll_read(cx, buf) {
+            Poll::Ready(res) => {
+                self.sleep = None;
+                res?
+            }
+            Poll::Pending => {
+                self.poll_timeout(cx)?;
+                return Poll::Pending;
+            }
+        };
+        if let Some(hook) = &self.hook {
+            hook.on_read_bytes(n as u64);
+        }
+        if n == 0 && !buf.is_empty() {
+            self.verify_checksums()?;
+        } else {
+            self.on_read(&buf[..n])?;
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        // Content is not read in sequence anymore, can't verify it.
+        self.md5 = None;
+        self.crc32c = None;
+        self.inner.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let res = match self.inner.poll_next(cx) {
+            Poll::Ready(res) => {
+                self.sleep = None;
+                res
+            }
+            Poll::Pending => {
+                if let Err(err) = self.poll_timeout(cx) {
+                    return Poll::Ready(Some(Err(err)));
+                }
+                return Poll::Pending;
+            }
+        };
+        if let (Some(hook), Some(Ok(bs))) = (&self.hook, &res) {
+            hook.on_read_bytes(bs.len() as u64);
+        }
+        match &res {
+            Some(Ok(bs)) => {
+                if let Err(err) = self.on_read(bs) {
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+            None => {
+                if let Err(err) = self.verify_checksums() {
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+            Some(Err(_)) => {}
+        }
+        Poll::Ready(res)
+    }
+}
@@ -0,0 +1,133 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::time::Duration;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use chrono::DateTime;
+use chrono::Utc;
+use http::header::CONTENT_TYPE;
+use http::Request;
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::sha2::Sha256;
+use rsa::signature::SignatureEncoding;
+use rsa::signature::Signer;
+use rsa::RsaPrivateKey;
+
+use super::uri::percent_encode_path;
+use crate::*;
+
+/// The version of signing process used by presigned urls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GcsSignedUrlVersion {
+    /// Legacy [V2 signing process](https://cloud.google.com/storage/docs/access-control/signed-urls-v2),
+    /// which is still required by some old consumers.
+    V2,
+    /// [V4 signing process](https://cloud.google.com/storage/docs/access-control/signing-urls-manually),
+    /// the default one.
+    #[default]
+    V4,
+}
+
+impl std::str::FromStr for GcsSignedUrlVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "v2" => Ok(GcsSignedUrlVersion::V2),
+            "v4" => Ok(GcsSignedUrlVersion::V4),
+            _ => Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "signed url version must be v2 or v4",
+            )
+            .with_context("version", s)),
+        }
+    }
+}
+
+/// Sign the request via query with the V2 signing process.
+///
+/// Unlike V4 whose `X-Goog-Expires` is the duration in seconds, V2 carries
+/// the absolute expiration time in unix seconds as `Expires`.
+pub(super) fn sign_query_v2<T>(
+    req: &mut Request<T>,
+    client_email: &str,
+    private_key: &str,
+    now: DateTime<Utc>,
+    expire: Duration,
+) -> Result<()> {
+    let expires = now.timestamp()
+        + i64::try_from(expire.as_secs()).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "expire is out of range").set_source(err)
+        })?;
+
+    let header = |name| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+    };
+
+    // Extension headers are canonicalized as lowercase `name:value` sorted
+    // by name, each in a line.
+    let mut extension_headers = req
+        .headers()
+        .iter()
+        .filter(|(k, _)| k.as_str().starts_with("x-goog-"))
+        .map(|(k, v)| format!("{}:{}\n", k, v.to_str().unwrap_or_default().trim()))
+        .collect::<Vec<_>>();
+    extension_headers.sort();
+
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}\n{}{}",
+        req.method(),
+        header("content-md5"),
+        header(CONTENT_TYPE.as_str()),
+        expires,
+        extension_headers.concat(),
+        req.uri().path(),
+    );
+
+    let private_key = RsaPrivateKey::from_pkcs8_pem(private_key).map_err(|err| {
+        Error::new(
+            ErrorKind::ConfigInvalid,
+            "private key of service account is invalid",
+        )
+        .set_source(err)
+    })?;
+    let signature = SigningKey::<Sha256>::new(private_key)
+        .sign(string_to_sign.as_bytes())
+        .to_bytes();
+
+    let query = format!(
+        "GoogleAccessId={}&Expires={}&Signature={}",
+        percent_encode_path(client_email),
+        expires,
+        percent_encode_path(&BASE64_STANDARD.encode(signature)),
+    );
+    let uri = match req.uri().query() {
+        Some(q) if !q.is_empty() => format!("{}&{query}", req.uri()),
+        _ => format!("{}?{query}", req.uri().to_string().trim_end_matches('?')),
+    };
+    *req.uri_mut() = uri.parse().map_err(|err| {
+        Error::new(ErrorKind::Unexpected, "signed uri is invalid").set_source(err)
+    })?;
+
+    Ok(())
+}
@@ -30,6 +30,9 @@ static GCS_PATH_ENCODE_SET: AsciiSet = NON_ALPHANUMERIC
     .remove(b'.')
     .remove(b'*');
 
+/// GCS_XML_PATH_ENCODE_SET is [`GCS_PATH_ENCODE_SET`] with `/` allowed.
+static GCS_XML_PATH_ENCODE_SET: AsciiSet = GCS_PATH_ENCODE_SET.remove(b'/');
+
 /// percent_encode_path will do percent encoding for http encode path.
 ///
 /// Follows [encodeURIComponent](https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/encodeURIComponent)
@@ -40,6 +43,15 @@ pub(super) fn percent_encode_path(path: &str) -> String {
     utf8_percent_encode(path, &GCS_PATH_ENCODE_SET).to_string()
 }
 
+/// percent_encode_xml_path will do percent encoding for object path in XML API.
+///
+/// Same as [`percent_encode_path`] except that `/` is kept, since XML API
+/// addresses objects by path like `/bucket/dir/file` and the signed
+/// canonical resource must match the encoded path exactly.
+pub(super) fn percent_encode_xml_path(path: &str) -> String {
+    utf8_percent_encode(path, &GCS_XML_PATH_ENCODE_SET).to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,6 +81,21 @@ mod tests {
         for (name, input, expected) in cases {
             let actual = percent_encode_path(input);
 
+            assert_eq!(actual, expected, "{name}");
+        }
+    }
+    #[test]
+    fn test_percent_encode_xml_path() {
+        let cases = vec![
+            ("Slash", "dir/file", "dir/file"),
+            ("Plus and Space", "dir/a+b c", "dir/a%2Bb%20c"),
+            ("Query Sensitive", "a?b#c&d", "a%3Fb%23c%26d"),
+            ("Unicode", "dir/你好", "dir/%E4%BD%A0%E5%A5%BD"),
+        ];
+
+        for (name, input, expected) in cases {
+            let actual = percent_encode_xml_path(input);
+
             assert_eq!(actual, expected, "{name}");
         }
     }
@@ -16,21 +16,32 @@
 // under the License.
 
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use async_trait::async_trait;
 use http::StatusCode;
+use log::warn;
 
+use super::core::normalize_etag;
 use super::core::GcsCore;
+use super::core::GcsObject;
 use super::error::parse_error;
 use crate::raw::*;
 use crate::*;
 
-pub type GcsWriters = oio::RangeWriter<GcsWriter>;
+/// Writes are buffered up to `multipart_upload_threshold` if it's set.
+pub type GcsWriters = oio::TwoWaysWriter<
+    oio::RangeWriter<GcsWriter>,
+    oio::ExactBufWriter<oio::RangeWriter<GcsWriter>>,
+>;
 
 pub struct GcsWriter {
     core: Arc<GcsCore>,
     path: String,
     op: OpWrite,
+
+    /// Metadata of the committed object, shared with `RpWrite`.
+    committed: Arc<Mutex<Option<Metadata>>>,
 }
 
 impl GcsWriter {
@@ -39,6 +50,81 @@ impl GcsWriter {
             core,
             path: path.to_string(),
             op,
+            committed: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// The slot of the metadata of the committed object, which will be
+    /// filled with the object resource returned by GCS after the upload is
+    /// completed.
+    pub fn committed(&self) -> Arc<Mutex<Option<Metadata>>> {
+        self.committed.clone()
+    }
+
+    async fn set_committed(&self, body: IncomingAsyncBody) -> Result<()> {
+        let bs = body.bytes().await?;
+        // Some gcs-compatible services respond without body, ignore it.
+        if bs.is_empty() {
+            return Ok(());
+        }
+
+        let object: GcsObject = serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+        let mut meta = Metadata::new(EntryMode::FILE);
+        let size = object
+            .size
+            .parse::<u64>()
+            .map_err(|e| Error::new(ErrorKind::Unexpected, "parse u64").set_source(e))?;
+        meta.set_content_length(size);
+        meta.set_etag(normalize_etag(&object.etag));
+        // Composite objects don't have md5.
+        if !object.md5_hash.is_empty() {
+            meta.set_content_md5(&object.md5_hash);
+        }
+        if !object.crc32c.is_empty() {
+            meta.set_content_crc32c(&object.crc32c);
+        }
+        // The version of gcs objects is the generation.
+        if !object.generation.is_empty() {
+            meta.set_version(&object.generation);
+        }
+
+        // Mark all keys returned by GCS as set, even if they are empty.
+        let metakey =
+            meta.metakey() | Metakey::ContentMd5 | Metakey::ContentCrc32c | Metakey::Version;
+        let meta = meta.with_metakey(metakey);
+
+        *self.committed.lock().expect("lock must succeed") = Some(meta);
+        Ok(())
+    }
+
+    /// Check whether the object would exceed `max_object_size` after
+    /// writing up to `total` bytes.
+    fn check_object_size(&self, total: u64) -> Result<()> {
+        match self.core.max_object_size {
+            Some(max) if total > max => Err(Error::new(
+                ErrorKind::Unexpected,
+                "write aborted, object size exceeds the max object size",
+            )
+            .with_operation(Operation::Write)
+            .with_context("path", &self.path)
+            .with_context("max_object_size", max.to_string())
+            .with_context("size", total.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Abort the resumable upload session before returning the size error.
+    async fn abort_oversized(&self, location: &str, err: Error) -> Error {
+        if let Err(e) = oio::RangeWrite::abort_range(self, location).await {
+            warn!("abort oversized gcs resumable upload session {location} failed: {e}");
+        }
+        err
+    }
+
+    fn on_write_bytes(&self, n: u64) {
+        if let Some(hook) = &self.core.metrics_hook {
+            hook.on_write_bytes(n);
         }
     }
 }
@@ -46,6 +132,8 @@ impl GcsWriter {
 #[async_trait]
 impl oio::RangeWrite for GcsWriter {
     async fn write_once(&self, size: u64, body: AsyncBody) -> Result<()> {
+        self.check_object_size(size)?;
+
         let mut req = self.core.gcs_insert_object_request(
             &percent_encode_path(&self.path),
             Some(size),
@@ -61,7 +149,8 @@ impl oio::RangeWrite for GcsWriter {
 
         match status {
             StatusCode::CREATED | StatusCode::OK => {
-                resp.into_body().consume().await?;
+                self.set_committed(resp.into_body()).await?;
+                self.on_write_bytes(size);
                 Ok(())
             }
             _ => Err(parse_error(resp).await?),
@@ -69,7 +158,10 @@ impl oio::RangeWrite for GcsWriter {
     }
 
     async fn initiate_range(&self) -> Result<String> {
-        let resp = self.core.gcs_initiate_resumable_upload(&self.path).await?;
+        let resp = self
+            .core
+            .gcs_initiate_resumable_upload(&self.path, &self.op)
+            .await?;
         let status = resp.status();
 
         match status {
@@ -95,6 +187,10 @@ impl oio::RangeWrite for GcsWriter {
         size: u64,
         body: AsyncBody,
     ) -> Result<()> {
+        if let Err(err) = self.check_object_size(written + size) {
+            return Err(self.abort_oversized(location, err).await);
+        }
+
         let mut req = self
             .core
             .gcs_upload_in_resumable_upload(location, size, written, body)?;
@@ -105,7 +201,10 @@ impl oio::RangeWrite for GcsWriter {
 
         let status = resp.status();
         match status {
-            StatusCode::OK | StatusCode::PERMANENT_REDIRECT => Ok(()),
+            StatusCode::OK | StatusCode::PERMANENT_REDIRECT => {
+                self.on_write_bytes(size);
+                Ok(())
+            }
             _ => Err(parse_error(resp).await?),
         }
     }
@@ -117,6 +216,10 @@ impl oio::RangeWrite for GcsWriter {
         size: u64,
         body: AsyncBody,
     ) -> Result<()> {
+        if let Err(err) = self.check_object_size(written + size) {
+            return Err(self.abort_oversized(location, err).await);
+        }
+
         let resp = self
             .core
             .gcs_complete_resumable_upload(location, written, size, body)
@@ -125,7 +228,8 @@ impl oio::RangeWrite for GcsWriter {
         let status = resp.status();
         match status {
             StatusCode::OK => {
-                resp.into_body().consume().await?;
+                self.set_committed(resp.into_body()).await?;
+                self.on_write_bytes(size);
                 Ok(())
             }
             _ => Err(parse_error(resp).await?),
@@ -138,7 +242,9 @@ impl oio::RangeWrite for GcsWriter {
         match resp.status().as_u16() {
             // gcs returns 499 if the upload aborted successfully
             // reference: https://cloud.google.com/storage/docs/performing-resumable-uploads#cancel-upload-json
-            499 => {
+            //
+            // Some gcs-compatible services respond with 204 instead.
+            499 | 204 => {
                 resp.into_body().consume().await?;
                 Ok(())
             }
@@ -67,6 +67,16 @@ pub use ftp::FtpConfig;
 mod gcs;
 #[cfg(feature = "services-gcs")]
 pub use gcs::Gcs;
+#[cfg(feature = "services-gcs")]
+pub use gcs::GcsBackend;
+#[cfg(feature = "services-gcs")]
+pub use gcs::GcsMetadata;
+#[cfg(feature = "services-gcs")]
+pub use gcs::GcsMetricsHook;
+#[cfg(feature = "services-gcs")]
+pub use gcs::GcsPathKind;
+#[cfg(feature = "services-gcs")]
+pub use gcs::GcsStatResult;
 
 #[cfg(feature = "services-ghac")]
 mod ghac;
@@ -206,7 +216,27 @@ mod dropbox;
 #[cfg(feature = "services-dropbox")]
 pub use dropbox::Dropbox;
 #[cfg(feature = "services-webhdfs")]
+pub use webhdfs::BlockStoragePolicy;
+#[cfg(feature = "services-webhdfs")]
+pub use webhdfs::FileStatus;
+#[cfg(feature = "services-webhdfs")]
+pub use webhdfs::FileStatusType;
+#[cfg(feature = "services-webhdfs")]
+pub use webhdfs::ServerDefaults;
+#[cfg(feature = "services-webhdfs")]
+pub use webhdfs::SnapshottableDirectoryStatus;
+#[cfg(feature = "services-webhdfs")]
 pub use webhdfs::Webhdfs;
+#[cfg(feature = "services-webhdfs")]
+pub use webhdfs::WebhdfsBackend;
+#[cfg(feature = "services-webhdfs")]
+pub use webhdfs::WebhdfsWalkEntry;
+#[cfg(feature = "services-webhdfs")]
+pub use webhdfs::WebhdfsWalker;
+#[cfg(feature = "services-webhdfs")]
+pub use webhdfs::XAttrSetFlag;
+#[cfg(feature = "services-webhdfs")]
+pub use webhdfs::XAttrValueEncoding;
 
 #[cfg(feature = "services-vercel-artifacts")]
 mod vercel_artifacts;
@@ -17,29 +17,68 @@
 
 use core::fmt::Debug;
 use std::collections::HashMap;
+use std::iter;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
 
 use async_trait::async_trait;
+use backon::BackoffBuilder;
+use backon::ExponentialBuilder;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use futures::stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use http::header::CONNECTION;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_TYPE;
+use http::Method;
 use http::Request;
 use http::Response;
 use http::StatusCode;
 use log::debug;
 use tokio::sync::OnceCell;
 
+use super::error::is_being_created_error;
+use super::error::is_safe_mode_error;
 use super::error::parse_error;
 use super::error::parse_error_msg;
+use super::message::BlockStoragePoliciesWrapper;
+use super::message::BlockStoragePolicy;
 use super::message::BooleanResp;
 use super::message::DirectoryListingWrapper;
+use super::message::FileStatus;
 use super::message::FileStatusType;
 use super::message::FileStatusWrapper;
 use super::message::FileStatusesWrapper;
+use super::message::LocationResp;
+use super::message::ServerDefaults;
+use super::message::ServerDefaultsWrapper;
+use super::message::SnapshottableDirectoryListWrapper;
+use super::message::SnapshottableDirectoryStatus;
+use super::message::XAttrSetFlag;
+use super::message::XAttrValueEncoding;
+use super::message::XAttrsWrapper;
 use super::pager::WebhdfsPager;
+use super::pager::WebhdfsWalker;
 use super::writer::WebhdfsWriter;
 use crate::raw::*;
 use crate::*;
 
 const WEBHDFS_DEFAULT_ENDPOINT: &str = "http://127.0.0.1:9870";
+const WEBHDFS_DEFAULT_SAFE_MODE_MAX_RETRIES: usize = 0;
+/// Max levels of symlinks to follow before giving up, the same as `MAXSYMLINKS`
+/// of linux.
+const WEBHDFS_MAX_SYMLINK_DEPTH: usize = 40;
+/// Max duration to wait for `Retry-After`, so that a misbehaving server can't
+/// hang the request.
+const WEBHDFS_MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+/// Name of the xattr that keeps the content type of files, HDFS doesn't
+/// store MIME itself.
+pub const WEBHDFS_CONTENT_TYPE_XATTR: &str = "user.contenttype";
 
 /// [WebHDFS](https://hadoop.apache.org/docs/stable/hadoop-project-dist/hadoop-hdfs/WebHDFS.html)'s REST API support.
 #[doc = include_str!("docs.md")]
@@ -49,6 +88,21 @@ pub struct WebhdfsBuilder {
     endpoint: Option<String>,
     delegation: Option<String>,
     disable_list_batch: bool,
+    list_batch_size: Option<usize>,
+    skip_dir_placeholder: bool,
+    allow_delete_root: bool,
+    manual_redirect: bool,
+    single_hop_create: bool,
+    guess_content_type: bool,
+    datanode_connection_close: bool,
+    auto_create_root: bool,
+    safe_mode_max_retries: Option<usize>,
+    append_max_retries: usize,
+    consistent_read: bool,
+    stat_cache_ttl: Option<Duration>,
+    proxy: Option<String>,
+
+    http_client: Option<HttpClient>,
 }
 
 impl Debug for WebhdfsBuilder {
@@ -117,6 +171,225 @@ impl WebhdfsBuilder {
         self.disable_list_batch = true;
         self
     }
+
+    /// Set the preferred number of entries in every batch while listing.
+    ///
+    /// # Note
+    ///
+    /// The size of `LISTSTATUS_BATCH` is decided by `dfs.ls.limit` of
+    /// namenode, which ignores this hint. Gateways that support it will
+    /// return batches of the given size, which reduces round trips on huge
+    /// directories.
+    ///
+    /// Not set by default.
+    pub fn list_batch_size(&mut self, size: usize) -> &mut Self {
+        if size > 0 {
+            self.list_batch_size = Some(size);
+        }
+        self
+    }
+
+    /// Skip zero-length placeholder files while listing.
+    ///
+    /// # Note
+    ///
+    /// HDFS backed by object storage could return zero-length files as
+    /// directory placeholders, like file `dir` or `dir/` besides directory
+    /// `dir`. If enabled, zero-length files whose names duplicate a directory
+    /// in the same or previous batches will be skipped while listing.
+    ///
+    /// Disabled by default, all entries will be returned as is.
+    pub fn skip_dir_placeholder(&mut self) -> &mut Self {
+        self.skip_dir_placeholder = true;
+        self
+    }
+
+    /// Allow deleting the configured root.
+    ///
+    /// # Note
+    ///
+    /// `delete("/")` will send `DELETE` to the root of this backend, which
+    /// could remove the whole mounted tree. It's refused with
+    /// `ErrorKind::ConfigInvalid` unless this option is enabled.
+    pub fn allow_delete_root(&mut self) -> &mut Self {
+        self.allow_delete_root = true;
+        self
+    }
+
+    /// Handle the redirection of write manually.
+    ///
+    /// # Note
+    ///
+    /// By default, the `307 Temporary Redirect` returned by namenode for
+    /// `CREATE` and `APPEND` will be followed by the http client
+    /// automatically, which hides the datanode location.
+    ///
+    /// If enabled, the backend will send `CREATE` and `APPEND` without data
+    /// to namenode with `noredirect=true`, read the datanode location from
+    /// the response and send data to it in a separate request. Both requests
+    /// are sent by the same http client, including the one set by
+    /// `http_client`.
+    pub fn manual_redirect(&mut self) -> &mut Self {
+        self.manual_redirect = true;
+        self
+    }
+
+    /// Create files in a single request with `data=true`.
+    ///
+    /// # Note
+    ///
+    /// Some WebHDFS gateways like HttpFS accept the data of `CREATE` directly
+    /// with `data=true` instead of redirecting to datanode, which saves a
+    /// round trip. If enabled, data will be sent along with the first
+    /// `CREATE`, and the redirection will still be followed in case the
+    /// gateway doesn't support it.
+    ///
+    /// Takes precedence over `manual_redirect` for writes.
+    pub fn single_hop_create(&mut self) -> &mut Self {
+        self.single_hop_create = true;
+        self
+    }
+
+    /// Store the content type of files as xattr while creating them.
+    ///
+    /// # Note
+    ///
+    /// HDFS doesn't store MIME of files. If enabled, the content type of
+    /// write, or the one guessed from the extension of path if not set, will
+    /// be stored as the `user.contenttype` xattr after `CREATE`, which costs
+    /// an extra request. Use [`WebhdfsBackend::content_type`] to read it back.
+    ///
+    /// xattrs must be enabled on the cluster via `dfs.namenode.xattrs.enabled`.
+    /// Failures of storing the xattr are logged and ignored, since the file
+    /// has been written already.
+    pub fn guess_content_type(&mut self) -> &mut Self {
+        self.guess_content_type = true;
+        self
+    }
+
+    /// Create the root directory on the first write if it doesn't exist.
+    ///
+    /// # Note
+    ///
+    /// Root is never created by `stat`, so that read-only users won't send
+    /// unexpected writes. If not enabled, root is left to be created by HDFS
+    /// along with the parent directories of written files.
+    pub fn auto_create_root(&mut self) -> &mut Self {
+        self.auto_create_root = true;
+        self
+    }
+
+    /// Send `Connection: close` on requests that transfer data with datanodes.
+    ///
+    /// # Note
+    ///
+    /// Some datanodes mishandle keep-alive and return truncated bodies on
+    /// reused connections. If enabled, reads and writes will use a fresh
+    /// connection every time as a workaround, which costs a new TCP (and TLS)
+    /// handshake per request and hurts the throughput of small reads.
+    ///
+    /// The header is also sent on the namenode request when redirects are
+    /// followed automatically, so that connection won't be reused either.
+    pub fn datanode_connection_close(&mut self) -> &mut Self {
+        self.datanode_connection_close = true;
+        self
+    }
+
+    /// Enable consistent read in HA setups with observer namenodes.
+    ///
+    /// # Note
+    ///
+    /// Reads served by observer namenodes could be stale until the client
+    /// syncs its state with the active namenode. If enabled, `MSYNC` will be
+    /// sent before every read so that read-after-write is consistent, at the
+    /// cost of an extra request.
+    pub fn consistent_read(&mut self) -> &mut Self {
+        self.consistent_read = true;
+        self
+    }
+
+    /// Enable the stat cache with given TTL to reduce the load of namenode.
+    ///
+    /// # Note
+    ///
+    /// Results of `GETFILESTATUS` will be cached by path, and served without
+    /// sending requests until the TTL expires. The cached path will be
+    /// invalidated while writing, deleting or renaming it via this backend,
+    /// but changes made by other clients could be invisible within the TTL.
+    ///
+    /// Disabled by default.
+    pub fn stat_cache_ttl(&mut self, ttl: Duration) -> &mut Self {
+        if !ttl.is_zero() {
+            self.stat_cache_ttl = Some(ttl);
+        }
+        self
+    }
+
+    /// Set the max retries for requests rejected while namenode is in safe mode.
+    ///
+    /// # Note
+    ///
+    /// Namenode rejects requests with `SafeModeException` during startup or
+    /// maintenance. Idempotent operations like stat, read, write, list and
+    /// create_dir will be retried with backoff, and return the error if
+    /// retries are exhausted. Non-idempotent operations like append, concat
+    /// and delete are never retried, since they could be applied twice.
+    ///
+    /// Requests rejected by overloaded namenode with `503` and `Retry-After`
+    /// will be retried too, after waiting for the given seconds.
+    ///
+    /// Default to `0`, which disables retry.
+    pub fn safe_mode_max_retries(&mut self, max_retries: usize) -> &mut Self {
+        self.safe_mode_max_retries = Some(max_retries);
+        self
+    }
+
+    /// Set the max retries for appends rejected because the file is being
+    /// written by another client.
+    ///
+    /// # Note
+    ///
+    /// HDFS allows only one writer of a file, appends to a file that another
+    /// client has open for write fail with `AlreadyBeingCreatedException`.
+    /// The error is temporary, and will be retried with backoff if enabled,
+    /// which is useful for concurrent log appends.
+    ///
+    /// Default to `0`, which returns the error directly.
+    pub fn append_max_retries(&mut self, max_retries: usize) -> &mut Self {
+        self.append_max_retries = max_retries;
+        self
+    }
+
+    /// Set the proxy used to access WebHDFS, like `http://proxy.example.com:8080`.
+    ///
+    /// Both requests to namenode and the redirected requests to datanodes
+    /// will be sent through this proxy.
+    ///
+    /// # Notes
+    ///
+    /// The proxy will not be applied to the client set by `http_client`,
+    /// users should configure the proxy on their own client instead.
+    pub fn proxy(&mut self, proxy: &str) -> &mut Self {
+        if !proxy.is_empty() {
+            self.proxy = Some(proxy.to_string());
+        }
+        self
+    }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// Users can build their own client with tuned connection pool settings
+    /// like `pool_max_idle_per_host` and `pool_idle_timeout` to reduce
+    /// connection churn in stat/list heavy workloads.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
 }
 
 impl Builder for WebhdfsBuilder {
@@ -132,6 +405,43 @@ impl Builder for WebhdfsBuilder {
         map.get("disable_list_batch")
             .filter(|v| v == &"true")
             .map(|_| builder.disable_list_batch());
+        map.get("list_batch_size")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.list_batch_size(v));
+        map.get("skip_dir_placeholder")
+            .filter(|v| v == &"true")
+            .map(|_| builder.skip_dir_placeholder());
+        map.get("allow_delete_root")
+            .filter(|v| v == &"true")
+            .map(|_| builder.allow_delete_root());
+        map.get("manual_redirect")
+            .filter(|v| v == &"true")
+            .map(|_| builder.manual_redirect());
+        map.get("single_hop_create")
+            .filter(|v| v == &"true")
+            .map(|_| builder.single_hop_create());
+        map.get("guess_content_type")
+            .filter(|v| v == &"true")
+            .map(|_| builder.guess_content_type());
+        map.get("datanode_connection_close")
+            .filter(|v| v == &"true")
+            .map(|_| builder.datanode_connection_close());
+        map.get("auto_create_root")
+            .filter(|v| v == &"true")
+            .map(|_| builder.auto_create_root());
+        map.get("consistent_read")
+            .filter(|v| v == &"true")
+            .map(|_| builder.consistent_read());
+        map.get("stat_cache_ttl")
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|v| builder.stat_cache_ttl(Duration::from_secs(v)));
+        map.get("safe_mode_max_retries")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.safe_mode_max_retries(v));
+        map.get("append_max_retries")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.append_max_retries(v));
+        map.get("proxy").map(|v| builder.proxy(v));
 
         builder
     }
@@ -167,15 +477,52 @@ impl Builder for WebhdfsBuilder {
             .take()
             .map(|dt| format!("delegation_token={dt}"));
 
-        let client = HttpClient::new()?;
+        let proxy = match &self.proxy {
+            Some(v) => Some(reqwest::Proxy::all(v).map_err(|err| {
+                Error::new(ErrorKind::ConfigInvalid, "proxy is invalid")
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::Webhdfs)
+                    .with_context("proxy", v)
+                    .set_source(err)
+            })?),
+            None => None,
+        };
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            let builder = match proxy {
+                Some(proxy) => reqwest::ClientBuilder::new().proxy(proxy),
+                None => reqwest::ClientBuilder::new(),
+            };
+            HttpClient::build(builder).map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Webhdfs)
+            })?
+        };
 
         let backend = WebhdfsBackend {
             root,
             endpoint,
             auth,
             client,
+            manual_redirect: self.manual_redirect,
+            safe_mode_max_retries: self
+                .safe_mode_max_retries
+                .unwrap_or(WEBHDFS_DEFAULT_SAFE_MODE_MAX_RETRIES),
+            append_max_retries: self.append_max_retries,
+            consistent_read: self.consistent_read,
+            single_hop_create: self.single_hop_create,
+            guess_content_type: self.guess_content_type,
+            datanode_connection_close: self.datanode_connection_close,
+            stat_cache_ttl: self.stat_cache_ttl,
+            stat_cache: Arc::default(),
             root_checker: OnceCell::new(),
+            auto_create_root: self.auto_create_root,
+            root_creator: OnceCell::new(),
             disable_list_batch: self.disable_list_batch,
+            list_batch_size: self.list_batch_size,
+            skip_dir_placeholder: self.skip_dir_placeholder,
+            allow_delete_root: self.allow_delete_root,
         };
 
         Ok(backend)
@@ -183,18 +530,162 @@ impl Builder for WebhdfsBuilder {
 }
 
 /// Backend for WebHDFS service
+///
+/// Most users should go through [`Operator`](crate::Operator) which is built
+/// from [`Webhdfs`](crate::services::Webhdfs). The backend is exported for the
+/// WebHDFS specific operations that have no [`Operator`](crate::Operator)
+/// counterpart, like [`WebhdfsBackend::truncate`] or
+/// [`WebhdfsBackend::concat`]. Obtain it via `Builder::build()`.
+///
+/// # Notes
+///
+/// The inherent methods on this type are part of OpenDAL's Raw API. They talk
+/// to the namenode directly and bypass every layer, including retry, logging,
+/// metrics and the capability checks. They could be changed during minor
+/// updates.
 #[derive(Debug, Clone)]
 pub struct WebhdfsBackend {
     root: String,
     endpoint: String,
     auth: Option<String>,
     root_checker: OnceCell<()>,
+    auto_create_root: bool,
+    root_creator: OnceCell<()>,
 
-    pub disable_list_batch: bool,
-    pub client: HttpClient,
+    pub(super) disable_list_batch: bool,
+    list_batch_size: Option<usize>,
+    pub(super) skip_dir_placeholder: bool,
+    allow_delete_root: bool,
+    client: HttpClient,
+    pub(super) manual_redirect: bool,
+    safe_mode_max_retries: usize,
+    append_max_retries: usize,
+    consistent_read: bool,
+    pub(super) single_hop_create: bool,
+    pub(super) guess_content_type: bool,
+    datanode_connection_close: bool,
+    stat_cache_ttl: Option<Duration>,
+    /// Cached stat results with the time they are cached, only used if
+    /// `stat_cache_ttl` is set.
+    stat_cache: Arc<Mutex<HashMap<String, (Instant, Metadata)>>>,
 }
 
 impl WebhdfsBackend {
+    /// Send the request, retrying with backoff while namenode is in safe mode
+    /// or overloaded.
+    ///
+    /// Overloaded namenode returns `503` with `Retry-After`, which will be
+    /// respected instead of the backoff.
+    ///
+    /// Only idempotent requests like `GET` or `PUT` with `overwrite=true`
+    /// could be sent via this, since a request could be applied even if its
+    /// response is lost. Requests with stream body can't be replayed, so they
+    /// will never be retried.
+    pub(super) async fn send_idempotent(
+        &self,
+        req: Request<AsyncBody>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        if self.safe_mode_max_retries == 0 {
+            return self.client.send(req).await;
+        }
+
+        let (parts, body) = req.into_parts();
+        let bs = match body {
+            AsyncBody::Empty => None,
+            AsyncBody::Bytes(bs) => Some(bs),
+            body => return self.client.send(Request::from_parts(parts, body)).await,
+        };
+
+        let mut backoff = ExponentialBuilder::default()
+            .with_jitter()
+            .with_max_times(self.safe_mode_max_retries)
+            .build();
+        loop {
+            let mut req = Request::new(bs.clone().map_or(AsyncBody::Empty, AsyncBody::Bytes));
+            *req.method_mut() = parts.method.clone();
+            *req.uri_mut() = parts.uri.clone();
+            *req.version_mut() = parts.version;
+            *req.headers_mut() = parts.headers.clone();
+
+            let (err, retry_after) = match self.client.send(req).await {
+                // HDFS returns `403` for `SafeModeException` and `503` for
+                // `RetriableException` or overload, other responses are
+                // returned as is.
+                Ok(resp)
+                    if matches!(
+                        resp.status(),
+                        StatusCode::FORBIDDEN | StatusCode::SERVICE_UNAVAILABLE
+                    ) =>
+                {
+                    let retry_after = match resp.status() {
+                        StatusCode::SERVICE_UNAVAILABLE => parse_retry_after(resp.headers()),
+                        _ => None,
+                    };
+
+                    let (parts, body) = resp.into_parts();
+                    let bs = body.bytes().await?;
+                    let s = String::from_utf8_lossy(&bs);
+                    if is_safe_mode_error(&s) || retry_after.is_some() {
+                        (parse_error_msg(parts, &s)?, retry_after)
+                    } else {
+                        let size = bs.len() as u64;
+                        let body = IncomingAsyncBody::new(
+                            Box::new(oio::into_stream(stream::iter(vec![Ok(bs)]))),
+                            Some(size),
+                        );
+                        return Ok(Response::from_parts(parts, body));
+                    }
+                }
+                Ok(resp) => return Ok(resp),
+                Err(err) if err.is_temporary() => (err, None),
+                Err(err) => return Err(err),
+            };
+
+            match backoff.next() {
+                Some(dur) => tokio::time::sleep(retry_after.unwrap_or(dur)).await,
+                None => return Err(err),
+            }
+        }
+    }
+
+    /// Get the cached stat result of path if it's not expired.
+    fn get_cached_stat(&self, path: &str) -> Option<Metadata> {
+        let ttl = self.stat_cache_ttl?;
+        let cache = self.stat_cache.lock().expect("lock must succeed");
+        cache
+            .get(path)
+            .filter(|(cached_at, _)| cached_at.elapsed() < ttl)
+            .map(|(_, meta)| meta.clone())
+    }
+
+    fn set_cached_stat(&self, path: &str, meta: &Metadata) {
+        let Some(ttl) = self.stat_cache_ttl else {
+            return;
+        };
+        let mut cache = self.stat_cache.lock().expect("lock must succeed");
+        // Evict expired entries so that the cache will not grow unbounded.
+        cache.retain(|_, (cached_at, _)| cached_at.elapsed() < ttl);
+        cache.insert(path.to_string(), (Instant::now(), meta.clone()));
+    }
+
+    /// Invalidate the cached stat result of path, and all paths under it if
+    /// it's a directory.
+    pub(super) fn invalidate_cached_stat(&self, path: &str) {
+        if self.stat_cache_ttl.is_none() {
+            return;
+        }
+        let mut cache = self.stat_cache.lock().expect("lock must succeed");
+        if path.ends_with('/') {
+            cache.retain(|k, _| !k.starts_with(path));
+        } else {
+            cache.remove(path);
+        }
+    }
+
+    // Some strict gateways reject reordered query strings, so all request
+    // builders below put `op` first, followed by the params of the op and
+    // the auth params at last.
+
     /// create object or make a directory
     ///
     /// TODO: we should split it into mkdir and create
@@ -217,6 +708,14 @@ impl WebhdfsBackend {
             percent_encode_path(&p),
             op,
         );
+        // Only requests carrying data could skip the redirection.
+        if op == "CREATE" && size.is_some() && self.single_hop_create {
+            url += "&data=true";
+        }
+        // Requests without data only ask namenode for the datanode location.
+        if op == "CREATE" && size.is_none() {
+            url += "&noredirect=true";
+        }
         if let Some(auth) = &self.auth {
             url += format!("&{auth}").as_str();
         }
@@ -230,6 +729,10 @@ impl WebhdfsBackend {
 
         if let Some(size) = size {
             req = req.header(CONTENT_LENGTH, size.to_string());
+            // Requests carrying data will be redirected to datanode.
+            if self.datanode_connection_close {
+                req = req.header(CONNECTION, "close");
+            }
         }
         if let Some(content_type) = args.content_type() {
             req = req.header(CONTENT_TYPE, content_type);
@@ -238,10 +741,98 @@ impl WebhdfsBackend {
         req.body(body).map_err(new_request_build_error)
     }
 
+    /// Get the datanode location to send data to from namenode.
+    ///
+    /// The request should carry `noredirect=true` so that namenode returns
+    /// the location in body. The `Location` header of `307` is also accepted
+    /// in case the http client doesn't follow redirections.
+    async fn webhdfs_get_datanode_location(
+        &self,
+        req: Request<AsyncBody>,
+        path: &str,
+    ) -> Result<String> {
+        let resp = self.client.send(req).await?;
+
+        let location = match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                serde_json::from_slice::<LocationResp>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .location
+            }
+            StatusCode::TEMPORARY_REDIRECT => {
+                let location = parse_location(resp.headers())?.map(String::from);
+                resp.into_body().consume().await?;
+                location.ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::Unexpected,
+                        "webhdfs request is redirected without location",
+                    )
+                    .with_context("path", path)
+                })?
+            }
+            status if status.is_success() => {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "webhdfs request is not redirected to datanode",
+                )
+                .with_context("path", path));
+            }
+            _ => return Err(parse_error(resp).await?),
+        };
+
+        Ok(location)
+    }
+
+    /// Build the request that sends data to the datanode `location`.
+    fn webhdfs_datanode_request(
+        &self,
+        method: Method,
+        location: &str,
+        content_type: Option<&str>,
+        bs: Bytes,
+    ) -> Result<Request<AsyncBody>> {
+        let mut req = Request::builder()
+            .method(method)
+            .uri(location)
+            .header(CONTENT_LENGTH, bs.len());
+        if self.datanode_connection_close {
+            req = req.header(CONNECTION, "close");
+        }
+        if let Some(content_type) = content_type {
+            req = req.header(CONTENT_TYPE, content_type);
+        }
+
+        req.body(AsyncBody::Bytes(bs))
+            .map_err(new_request_build_error)
+    }
+
+    /// Create object by following the redirection of namenode manually.
+    /// Returns the datanode location redirected to along with its response.
+    pub async fn webhdfs_create_object_with_manual_redirect(
+        &self,
+        path: &str,
+        args: &OpWrite,
+        bs: Bytes,
+    ) -> Result<(String, Response<IncomingAsyncBody>)> {
+        let req = self.webhdfs_create_object_request(path, None, args, AsyncBody::Empty)?;
+        let location = self
+            .webhdfs_get_datanode_location(req, path)
+            .await
+            .map_err(|err| err.with_operation("WebhdfsBackend::create"))?;
+
+        let req = self.webhdfs_datanode_request(Method::PUT, &location, args.content_type(), bs)?;
+        let resp = self.client.send(req).await?;
+        Ok((location, resp))
+    }
+
+    /// If `noredirect` is set, namenode will return the datanode location in
+    /// body instead of redirecting to it.
     async fn webhdfs_open_request(
         &self,
         path: &str,
         range: &BytesRange,
+        noredirect: bool,
     ) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
         let mut url = format!(
@@ -249,8 +840,8 @@ impl WebhdfsBackend {
             self.endpoint,
             percent_encode_path(&p),
         );
-        if let Some(auth) = &self.auth {
-            url += &format!("&{auth}");
+        if noredirect {
+            url += "&noredirect=true";
         }
 
         if !range.is_full() {
@@ -269,8 +860,17 @@ impl WebhdfsBackend {
                 url += &format!("&length={size}")
             }
         }
+        if let Some(auth) = &self.auth {
+            url += &format!("&{auth}");
+        }
 
-        let req = Request::get(&url)
+        let mut req = Request::get(&url);
+        // Only the location is returned without redirection.
+        if self.datanode_connection_close && !noredirect {
+            req = req.header(CONNECTION, "close");
+        }
+
+        let req = req
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
@@ -302,9 +902,12 @@ impl WebhdfsBackend {
         let p = build_abs_path(&self.root, path);
 
         // if it's not the first time to call LISTSTATUS_BATCH, we will add &startAfter=<CHILD>
+        //
+        // The child name must be encoded too, otherwise names like `a&b` or
+        // `a#b` will break the query.
         let start_after_param = match args.start_after() {
             Some(sa) if sa.is_empty() => String::new(),
-            Some(sa) => format!("&startAfter={}", sa),
+            Some(sa) => format!("&startAfter={}", percent_encode_path(sa)),
             None => String::new(),
         };
 
@@ -314,6 +917,9 @@ impl WebhdfsBackend {
             percent_encode_path(&p),
             start_after_param
         );
+        if let Some(size) = self.list_batch_size {
+            url += format!("&batchSize={size}").as_str();
+        }
         if let Some(auth) = &self.auth {
             url += format!("&{auth}").as_str();
         }
@@ -329,8 +935,17 @@ impl WebhdfsBackend {
         path: &str,
         range: BytesRange,
     ) -> Result<Response<IncomingAsyncBody>> {
-        let req = self.webhdfs_open_request(path, &range).await?;
-        self.client.send(req).await
+        let req = self.webhdfs_open_request(path, &range, false).await?;
+        self.send_idempotent(req).await
+    }
+
+    async fn webhdfs_get_read_location(
+        &self,
+        path: &str,
+        range: BytesRange,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let req = self.webhdfs_open_request(path, &range, true).await?;
+        self.send_idempotent(req).await
     }
 
     async fn webhdfs_get_file_status(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
@@ -349,7 +964,29 @@ impl WebhdfsBackend {
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
-        self.client.send(req).await
+        self.send_idempotent(req).await
+    }
+
+    async fn webhdfs_get_file_link_status(
+        &self,
+        path: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=GETFILELINKSTATUS",
+            self.endpoint,
+            percent_encode_path(&p),
+        );
+
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send_idempotent(req).await
     }
 
     async fn webhdfs_delete(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
@@ -370,225 +1007,3587 @@ impl WebhdfsBackend {
         self.client.send(req).await
     }
 
-    async fn check_root(&self) -> Result<()> {
-        let resp = self.webhdfs_get_file_status("/").await?;
-        match resp.status() {
-            StatusCode::OK => {
-                let bs = resp.into_body().bytes().await?;
+    async fn webhdfs_rename(
+        &self,
+        from: &str,
+        to: &str,
+        overwrite: bool,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let from = build_abs_path(&self.root, from);
+        let to = build_abs_path(&self.root, to);
+        // With `renameoptions`, HDFS will use the newer rename API which
+        // returns `FileAlreadyExistsException` instead of `false` if the
+        // destination exists.
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=RENAME&destination=/{}&renameoptions={}",
+            self.endpoint,
+            percent_encode_path(&from),
+            percent_encode_path(&to),
+            if overwrite { "OVERWRITE" } else { "NONE" },
+        );
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
 
-                let file_status = serde_json::from_slice::<FileStatusWrapper>(&bs)
-                    .map_err(new_json_deserialize_error)?
-                    .file_status;
+        let req = Request::put(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
 
-                if file_status.ty == FileStatusType::File {
-                    return Err(Error::new(
-                        ErrorKind::ConfigInvalid,
-                        "root path must be dir",
-                    ));
-                }
-            }
-            StatusCode::NOT_FOUND => {
-                self.create_dir("/", OpCreateDir::new()).await?;
-            }
-            _ => return Err(parse_error(resp).await?),
-        }
-        Ok(())
+        self.client.send(req).await
     }
-}
-
-#[async_trait]
-impl Accessor for WebhdfsBackend {
-    type Reader = IncomingAsyncBody;
-    type BlockingReader = ();
-    type Writer = oio::OneShotWriter<WebhdfsWriter>;
-    type BlockingWriter = ();
-    type Pager = WebhdfsPager;
-    type BlockingPager = ();
 
-    fn info(&self) -> AccessorInfo {
-        let mut am = AccessorInfo::default();
-        am.set_scheme(Scheme::Webhdfs)
+    async fn webhdfs_set_replication(
+        &self,
+        path: &str,
+        replication: u16,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=SETREPLICATION&replication={replication}",
+            self.endpoint,
+            percent_encode_path(&p),
+        );
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::put(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send_idempotent(req).await
+    }
+
+    async fn webhdfs_truncate(
+        &self,
+        path: &str,
+        new_length: u64,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=TRUNCATE&newlength={new_length}",
+            self.endpoint,
+            percent_encode_path(&p),
+        );
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::post(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.client.send(req).await
+    }
+
+    async fn webhdfs_concat(
+        &self,
+        path: &str,
+        sources: &[String],
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let sources = sources
+            .iter()
+            .map(|v| format!("/{}", percent_encode_path(&build_abs_path(&self.root, v))))
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=CONCAT&sources={sources}",
+            self.endpoint,
+            percent_encode_path(&p),
+        );
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::post(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.client.send(req).await
+    }
+
+    async fn webhdfs_get_xattrs(
+        &self,
+        path: &str,
+        names: &[&str],
+        encoding: XAttrValueEncoding,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=GETXATTRS&encoding={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            encoding.as_str(),
+        );
+        // HDFS accepts repeated `xattr.name` to fetch multiple xattrs at once.
+        for name in names {
+            url += format!("&xattr.name={}", percent_encode_path(name)).as_str();
+        }
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send_idempotent(req).await
+    }
+
+    async fn webhdfs_set_xattr(
+        &self,
+        path: &str,
+        name: &str,
+        value: &[u8],
+        flag: XAttrSetFlag,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        // Values are always sent in base64 so that binary values are kept.
+        let value = format!("0s{}", BASE64_STANDARD.encode(value));
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=SETXATTR&xattr.name={}&xattr.value={}&flag={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            percent_encode_path(name),
+            percent_encode_path(&value),
+            flag.as_str(),
+        );
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::put(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.client.send(req).await
+    }
+
+    async fn webhdfs_get_snapshottable_directory_list(
+        &self,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let mut url = format!(
+            "{}/webhdfs/v1/?op=GETSNAPSHOTTABLEDIRECTORYLIST",
+            self.endpoint,
+        );
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send_idempotent(req).await
+    }
+
+    async fn webhdfs_rename_snapshot(
+        &self,
+        path: &str,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=RENAMESNAPSHOT&oldsnapshotname={}&snapshotname={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            percent_encode_path(old_name),
+            percent_encode_path(new_name),
+        );
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::put(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.client.send(req).await
+    }
+
+    async fn webhdfs_set_quota(
+        &self,
+        path: &str,
+        namespace_quota: Option<i64>,
+        space_quota: Option<i64>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=SETQUOTA",
+            self.endpoint,
+            percent_encode_path(&p),
+        );
+        if let Some(quota) = namespace_quota {
+            url += format!("&namespacequota={quota}").as_str();
+        }
+        if let Some(quota) = space_quota {
+            url += format!("&storagespacequota={quota}").as_str();
+        }
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::put(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send_idempotent(req).await
+    }
+
+    /// If `bs` is `None`, namenode will only return the datanode location
+    /// in body instead of redirecting to it.
+    fn webhdfs_append_request(&self, path: &str, bs: Option<Bytes>) -> Result<Request<AsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=APPEND",
+            self.endpoint,
+            percent_encode_path(&p),
+        );
+        if bs.is_none() {
+            url += "&noredirect=true";
+        }
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let mut req = Request::post(&url);
+        let Some(bs) = bs else {
+            return req.body(AsyncBody::Empty).map_err(new_request_build_error);
+        };
+
+        req = req.header(CONTENT_LENGTH, bs.len());
+        // Requests carrying data will be redirected to datanode.
+        if self.datanode_connection_close {
+            req = req.header(CONNECTION, "close");
+        }
+
+        req.body(AsyncBody::Bytes(bs))
+            .map_err(new_request_build_error)
+    }
+
+    /// Append by following the redirection of namenode manually.
+    async fn webhdfs_append_with_manual_redirect(
+        &self,
+        path: &str,
+        bs: Bytes,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let req = self.webhdfs_append_request(path, None)?;
+        let location = self.webhdfs_get_datanode_location(req, path).await?;
+
+        let req = self.webhdfs_datanode_request(Method::POST, &location, None, bs)?;
+        self.client.send(req).await
+    }
+
+    async fn webhdfs_set_acl(&self, path: &str, spec: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=SETACL&aclspec={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            percent_encode_path(spec),
+        );
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::put(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send_idempotent(req).await
+    }
+
+    async fn webhdfs_get_all_storage_policies(&self) -> Result<Response<IncomingAsyncBody>> {
+        let mut url = format!("{}/webhdfs/v1/?op=GETALLSTORAGEPOLICIES", self.endpoint);
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send_idempotent(req).await
+    }
+
+    async fn webhdfs_get_server_defaults(&self) -> Result<Response<IncomingAsyncBody>> {
+        let mut url = format!("{}/webhdfs/v1/?op=GETSERVERDEFAULTS", self.endpoint);
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send_idempotent(req).await
+    }
+
+    async fn webhdfs_msync(&self) -> Result<Response<IncomingAsyncBody>> {
+        let mut url = format!("{}/webhdfs/v1/?op=MSYNC", self.endpoint);
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send_idempotent(req).await
+    }
+
+    /// Check that root is a directory if it exists.
+    ///
+    /// Missing root will be created only if `create` is true.
+    async fn check_root(&self, create: bool) -> Result<()> {
+        let resp = self.webhdfs_get_file_status("/").await?;
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let file_status = serde_json::from_slice::<FileStatusWrapper>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .file_status;
+
+                if file_status.ty == FileStatusType::File {
+                    return Err(Error::new(
+                        ErrorKind::ConfigInvalid,
+                        "root path must be dir",
+                    ));
+                }
+            }
+            StatusCode::NOT_FOUND if create => {
+                self.create_dir("/", OpCreateDir::new()).await?;
+            }
+            StatusCode::NOT_FOUND => {
+                resp.into_body().consume().await?;
+            }
+            _ => return Err(parse_error(resp).await?),
+        }
+        Ok(())
+    }
+}
+
+impl WebhdfsBackend {
+    /// Set the replication factor of an existing file.
+    ///
+    /// # Notes
+    ///
+    /// - `replication` must be at least 1.
+    /// - HDFS doesn't allow setting replication on a directory, an `IsADirectory`
+    ///   error will be returned in this case.
+    pub async fn set_replication(&self, path: &str, replication: u16) -> Result<()> {
+        if replication == 0 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "replication factor must be at least 1",
+            )
+            .with_operation("WebhdfsBackend::set_replication")
+            .with_context("replication", replication.to_string()));
+        }
+
+        let path = normalize_path(path);
+        let resp = self.webhdfs_set_replication(&path, replication).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let resp = serde_json::from_slice::<BooleanResp>(&bs)
+                    .map_err(new_json_deserialize_error)?;
+
+                if resp.boolean {
+                    Ok(())
+                } else {
+                    // HDFS returns `false` if the path is a directory.
+                    Err(Error::new(
+                        ErrorKind::IsADirectory,
+                        "webhdfs set replication failed, path is not a file",
+                    )
+                    .with_operation("WebhdfsBackend::set_replication")
+                    .with_context("path", path))
+                }
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Stat the given path without following symlinks.
+    ///
+    /// Unlike `stat`, the returned status describes the symlink itself if
+    /// `path` is a symlink, its target could be found in `symlink`.
+    pub async fn stat_nofollow(&self, path: &str) -> Result<FileStatus> {
+        let path = normalize_path(path);
+        let resp = self.webhdfs_get_file_link_status(&path).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let file_status = serde_json::from_slice::<FileStatusWrapper>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .file_status;
+
+                Ok(file_status)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Follow the symlink at the given path until a non-symlink is found.
+    ///
+    /// Returns the resolved path and its status, or the status of `path`
+    /// itself if it's not a symlink. At most `40` levels of symlinks will be
+    /// followed, an `Unexpected` error will be returned if there are more,
+    /// which could be caused by symlink cycles.
+    pub async fn resolve_symlink(&self, path: &str) -> Result<(String, FileStatus)> {
+        let mut current = normalize_path(path);
+
+        for _ in 0..=WEBHDFS_MAX_SYMLINK_DEPTH {
+            let status = self.stat_nofollow(&current).await?;
+            if status.ty != FileStatusType::Symlink {
+                return Ok((current, status));
+            }
+
+            let target = status.symlink.as_deref().ok_or_else(|| {
+                Error::new(ErrorKind::Unexpected, "symlink doesn't have a target")
+                    .with_operation("WebhdfsBackend::resolve_symlink")
+                    .with_context("path", &current)
+            })?;
+            current = self.symlink_target_path(&current, target)?;
+        }
+
+        Err(Error::new(ErrorKind::Unexpected, "too many symlink levels")
+            .with_operation("WebhdfsBackend::resolve_symlink")
+            .with_context("path", path)
+            .with_context("max_depth", WEBHDFS_MAX_SYMLINK_DEPTH.to_string()))
+    }
+
+    /// Build the path relative to root of the symlink target, relative
+    /// targets are resolved against the parent of the symlink.
+    fn symlink_target_path(&self, link: &str, target: &str) -> Result<String> {
+        let target = if target.starts_with('/') {
+            target.to_string()
+        } else {
+            let link = build_rooted_abs_path(&self.root, link);
+            let parent = &link[..=link.trim_end_matches('/').rfind('/').unwrap_or(0)];
+            format!("{parent}{target}")
+        };
+
+        match target.strip_prefix(&self.root) {
+            Some("") => Ok("/".to_string()),
+            Some(p) => Ok(normalize_path(p)),
+            None if format!("{target}/") == self.root => Ok("/".to_string()),
+            None => Err(
+                Error::new(ErrorKind::Unexpected, "symlink target is outside of root")
+                    .with_operation("WebhdfsBackend::resolve_symlink")
+                    .with_context("path", link)
+                    .with_context("target", target),
+            ),
+        }
+    }
+
+    /// Truncate an existing file to `new_length`.
+    ///
+    /// HDFS could need to recover the last block before truncating, in which
+    /// case the truncation happens in background. We will poll the length of
+    /// file with backoff until it reaches `new_length`, or return an error if
+    /// it doesn't within `timeout`.
+    pub async fn truncate(&self, path: &str, new_length: u64, timeout: Duration) -> Result<()> {
+        let path = normalize_path(path);
+        let resp = self.webhdfs_truncate(&path, new_length).await?;
+        self.invalidate_cached_stat(&path);
+
+        let done = match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                serde_json::from_slice::<BooleanResp>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .boolean
+            }
+            _ => return Err(parse_error(resp).await?),
+        };
+        if done {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+        let mut backoff = ExponentialBuilder::default()
+            .with_min_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(5))
+            .with_max_times(usize::MAX)
+            .build();
+        loop {
+            let resp = self.webhdfs_get_file_status(&path).await?;
+            let length = match resp.status() {
+                StatusCode::OK => {
+                    let bs = resp.into_body().bytes().await?;
+
+                    serde_json::from_slice::<FileStatusWrapper>(&bs)
+                        .map_err(new_json_deserialize_error)?
+                        .file_status
+                        .length
+                }
+                _ => return Err(parse_error(resp).await?),
+            };
+            if length == new_length {
+                return Ok(());
+            }
+
+            let dur = backoff.next().unwrap_or(Duration::from_secs(5));
+            if start.elapsed() + dur > timeout {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "webhdfs truncate is still in progress after timeout",
+                )
+                .with_operation("WebhdfsBackend::truncate")
+                .with_context("path", path)
+                .with_context("length", length.to_string())
+                .with_context("new_length", new_length.to_string())
+                .set_temporary());
+            }
+            tokio::time::sleep(dur).await;
+        }
+    }
+
+    /// Concat sources into the end of the file at `path`, sources will be
+    /// removed after concat.
+    ///
+    /// # Notes
+    ///
+    /// HDFS requires all sources to share the same block size with the target,
+    /// and all sources except the last one must consist of full blocks. These
+    /// are checked before sending `CONCAT`, an error with
+    /// `ErrorKind::ConditionNotMatch` will be returned if violated, instead of
+    /// the opaque `IOException` returned by HDFS.
+    pub async fn concat(&self, path: &str, sources: &[&str]) -> Result<()> {
+        let path = normalize_path(path);
+        let sources = sources
+            .iter()
+            .map(|v| normalize_path(v))
+            .collect::<Vec<_>>();
+
+        let target = self.file_status(&path).await?;
+        for (idx, source) in sources.iter().enumerate() {
+            let status = self.file_status(source).await?;
+
+            if status.block_size != target.block_size {
+                return Err(Error::new(
+                    ErrorKind::ConditionNotMatch,
+                    "source of concat must have the same block size with target",
+                )
+                .with_operation("WebhdfsBackend::concat")
+                .with_context("path", &path)
+                .with_context("source", source)
+                .with_context("block_size", target.block_size.to_string())
+                .with_context("source_block_size", status.block_size.to_string()));
+            }
+
+            let is_last = idx == sources.len() - 1;
+            if !is_last && status.block_size != 0 && status.length % status.block_size != 0 {
+                return Err(Error::new(
+                    ErrorKind::ConditionNotMatch,
+                    "source of concat must consist of full blocks except the last one",
+                )
+                .with_operation("WebhdfsBackend::concat")
+                .with_context("path", &path)
+                .with_context("source", source)
+                .with_context("block_size", status.block_size.to_string())
+                .with_context("source_length", status.length.to_string()));
+            }
+        }
+
+        let resp = self.webhdfs_concat(&path, &sources).await?;
+        self.invalidate_cached_stat(&path);
+        for source in &sources {
+            self.invalidate_cached_stat(source);
+        }
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Compact small files by appending their content into the end of the
+    /// file at `path`, with `separator` between them.
+    ///
+    /// Returns the number of files compacted.
+    ///
+    /// # Notes
+    ///
+    /// Unlike `concat`, there is no constraint on block sizes of sources, at
+    /// the cost of reading all sources into memory and sending them in one
+    /// `APPEND`. So it's only suitable for small files like logs.
+    ///
+    /// - The file at `path` must exist, `ErrorKind::NotFound` will be returned
+    ///   otherwise.
+    /// - Sources are kept after compaction, users should delete them after
+    ///   the compaction is verified.
+    pub async fn compact(&self, path: &str, sources: &[&str], separator: &[u8]) -> Result<usize> {
+        if sources.is_empty() {
+            return Ok(0);
+        }
+
+        let path = normalize_path(path);
+
+        let mut buf = Vec::new();
+        for (idx, source) in sources.iter().enumerate() {
+            if idx > 0 {
+                buf.extend_from_slice(separator);
+            }
+            let bs = self
+                .read_range(&normalize_path(source), BytesRange::default())
+                .await
+                .map_err(|err| {
+                    err.with_operation("WebhdfsBackend::compact")
+                        .with_context("source", *source)
+                })?;
+            buf.extend_from_slice(&bs);
+        }
+
+        self.append(&path, Bytes::from(buf))
+            .await
+            .map_err(|err| err.with_operation("WebhdfsBackend::compact"))?;
+        Ok(sources.len())
+    }
+
+    /// Append `bs` to the end of the existing file at `path`.
+    ///
+    /// # Notes
+    ///
+    /// If the file is being written by another client, a temporary error
+    /// will be returned, or retried with backoff if `append_max_retries` is
+    /// set.
+    pub async fn append(&self, path: &str, bs: Bytes) -> Result<()> {
+        let path = normalize_path(path);
+
+        let mut backoff = ExponentialBuilder::default()
+            .with_jitter()
+            .with_max_times(self.append_max_retries)
+            .build();
+        loop {
+            let resp = if self.manual_redirect {
+                self.webhdfs_append_with_manual_redirect(&path, bs.clone())
+                    .await
+                    .map_err(|err| err.with_operation("WebhdfsBackend::append"))?
+            } else {
+                let req = self.webhdfs_append_request(&path, Some(bs.clone()))?;
+                self.client.send(req).await?
+            };
+            self.invalidate_cached_stat(&path);
+
+            if resp.status() == StatusCode::OK {
+                resp.into_body().consume().await?;
+                return Ok(());
+            }
+
+            let (parts, body) = resp.into_parts();
+            let body = body.bytes().await?;
+            let s = String::from_utf8_lossy(&body);
+            let err = parse_error_msg(parts, &s)?
+                .with_operation("WebhdfsBackend::append")
+                .with_context("path", &path);
+
+            match backoff.next() {
+                Some(dur) if is_being_created_error(&s) => tokio::time::sleep(dur).await,
+                _ => return Err(err),
+            }
+        }
+    }
+
+    /// Read the whole file via concurrent ranged `OPEN` requests.
+    ///
+    /// The file will be split into chunks of `chunk_size` bytes, and at most
+    /// `concurrency` chunks will be read at the same time. Chunks are returned
+    /// in order, so the returned reader yields the same content as a
+    /// sequential read.
+    ///
+    /// # Notes
+    ///
+    /// It's useful for large files over high-latency links, at the cost of
+    /// buffering up to `concurrency` chunks in memory.
+    pub async fn read_parallel(
+        &self,
+        path: &str,
+        chunk_size: u64,
+        concurrency: usize,
+    ) -> Result<IncomingAsyncBody> {
+        if chunk_size == 0 || concurrency == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "chunk_size and concurrency of parallel read must be positive",
+            )
+            .with_operation("WebhdfsBackend::read_parallel")
+            .with_context("chunk_size", chunk_size.to_string())
+            .with_context("concurrency", concurrency.to_string()));
+        }
+
+        let path = normalize_path(path);
+        let length = self.file_status(&path).await?.length;
+
+        // Offsets are computed in `u64` to avoid truncating `chunk_size` on
+        // 32-bit targets.
+        let offsets = iter::successors(Some(0), move |offset: &u64| offset.checked_add(chunk_size))
+            .take_while(move |offset| *offset < length);
+
+        let backend = self.clone();
+        let chunks = stream::iter(offsets)
+            .map(move |offset| {
+                let backend = backend.clone();
+                let path = path.clone();
+                let range = BytesRange::new(Some(offset), Some(chunk_size.min(length - offset)));
+                // Requests are only sent while being polled by `buffered`, so
+                // the concurrency is capped and pending requests are dropped
+                // along with the reader.
+                async move { backend.read_range(&path, range).await }
+            })
+            .buffered(concurrency);
+
+        Ok(IncomingAsyncBody::new(
+            Box::new(oio::into_stream(chunks)),
+            Some(length),
+        ))
+    }
+
+    async fn read_range(&self, path: &str, range: BytesRange) -> Result<Bytes> {
+        let resp = self.webhdfs_read_file(path, range).await?;
+        match resp.status() {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
+                check_read_range(&range, resp.headers())?;
+                resp.into_body().bytes().await
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Check the existence of many paths via concurrent `GETFILESTATUS`.
+    ///
+    /// At most `concurrency` requests will be sent at the same time, and
+    /// paths found in the stat cache will not be sent at all. The returned
+    /// map is keyed by the given paths.
+    pub async fn exists_many(
+        &self,
+        paths: &[&str],
+        concurrency: usize,
+    ) -> Result<HashMap<String, bool>> {
+        if concurrency == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "concurrency of exists check must be positive",
+            )
+            .with_operation("WebhdfsBackend::exists_many"));
+        }
+
+        stream::iter(paths)
+            .map(|path| async move {
+                let exists = self.exists(&normalize_path(path)).await?;
+                Ok::<_, Error>((path.to_string(), exists))
+            })
+            .buffer_unordered(concurrency)
+            .try_collect()
+            .await
+    }
+
+    /// Check whether the path exists without parsing the file status.
+    async fn exists(&self, path: &str) -> Result<bool> {
+        if self.get_cached_stat(path).is_some() {
+            return Ok(true);
+        }
+
+        let resp = self.webhdfs_get_file_status(path).await?;
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(true)
+            }
+            StatusCode::NOT_FOUND => {
+                resp.into_body().consume().await?;
+                Ok(false)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn file_status(&self, path: &str) -> Result<FileStatus> {
+        let resp = self.webhdfs_get_file_status(path).await?;
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                Ok(serde_json::from_slice::<FileStatusWrapper>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .file_status)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// List all snapshottable directories of the cluster.
+    ///
+    /// # Notes
+    ///
+    /// The returned paths are absolute paths in HDFS, not relative to `root`.
+    pub async fn list_snapshottable_dirs(&self) -> Result<Vec<SnapshottableDirectoryStatus>> {
+        let resp = self.webhdfs_get_snapshottable_directory_list().await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let dirs = serde_json::from_slice::<SnapshottableDirectoryListWrapper>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .snapshottable_directory_list
+                    .unwrap_or_default();
+
+                Ok(dirs)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Rename the snapshot `old_name` of the directory at `path` to `new_name`.
+    ///
+    /// # Notes
+    ///
+    /// `ErrorKind::Unsupported` will be returned if the directory isn't
+    /// snapshottable, and `ErrorKind::NotFound` if the snapshot doesn't exist.
+    pub async fn rename_snapshot(&self, path: &str, old_name: &str, new_name: &str) -> Result<()> {
+        let path = normalize_path(path);
+        let resp = self
+            .webhdfs_rename_snapshot(&path, old_name, new_name)
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp)
+                .await?
+                .with_operation("WebhdfsBackend::rename_snapshot")
+                .with_context("path", path)
+                .with_context("old_name", old_name)
+                .with_context("new_name", new_name)),
+        }
+    }
+
+    /// Set the namespace and storage space quota of the directory at `path`.
+    ///
+    /// `namespace_quota` limits the number of files and directories, and
+    /// `space_quota` limits the bytes consumed including replicas. `None`
+    /// leaves the quota unchanged, and `-1` clears it.
+    ///
+    /// # Notes
+    ///
+    /// Setting quota requires superuser privilege, `ErrorKind::PermissionDenied`
+    /// will be returned otherwise.
+    pub async fn set_quota(
+        &self,
+        path: &str,
+        namespace_quota: Option<i64>,
+        space_quota: Option<i64>,
+    ) -> Result<()> {
+        for (name, quota) in [
+            ("namespace_quota", namespace_quota),
+            ("space_quota", space_quota),
+        ] {
+            if matches!(quota, Some(v) if v == 0 || v < -1) {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "quota must be positive, or -1 to clear it",
+                )
+                .with_operation("WebhdfsBackend::set_quota")
+                .with_context(name, quota.unwrap_or_default().to_string()));
+            }
+        }
+
+        let path = normalize_path(path);
+        let resp = self
+            .webhdfs_set_quota(&path, namespace_quota, space_quota)
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp)
+                .await?
+                .with_operation("WebhdfsBackend::set_quota")
+                .with_context("path", path)),
+        }
+    }
+
+    /// Replace all ACL entries of `path` with `spec`.
+    ///
+    /// `spec` is a comma separated list of entries like `user:alice:rwx`,
+    /// `group::r-x` or `default:other::---`, the same as `hdfs dfs -setfacl
+    /// --set`. Malformed specs are rejected with `ErrorKind::ConfigInvalid`
+    /// before sending any request.
+    pub async fn set_acl_spec(&self, path: &str, spec: &str) -> Result<()> {
+        validate_acl_spec(spec)?;
+
+        let path = normalize_path(path);
+        let resp = self.webhdfs_set_acl(&path, spec).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp)
+                .await?
+                .with_operation("WebhdfsBackend::set_acl_spec")
+                .with_context("path", path)),
+        }
+    }
+
+    /// Send a request with an arbitrary WebHDFS `op` to `path`.
+    ///
+    /// `params` will be appended to the query string after `op`, and the
+    /// delegation token will be carried if configured. The raw response is
+    /// returned without any status check, so that users could adopt new
+    /// operations of HDFS before they are supported by OpenDAL.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. It could be changed during
+    /// minor updates.
+    pub async fn raw_op(
+        &self,
+        method: Method,
+        path: &str,
+        op: &str,
+        params: &[(&str, &str)],
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, &normalize_path(path));
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            percent_encode_path(op),
+        );
+        for (k, v) in params {
+            url += format!("&{}={}", percent_encode_path(k), percent_encode_path(v)).as_str();
+        }
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::builder()
+            .method(method)
+            .uri(&url)
+            .body(body)
+            .map_err(new_request_build_error)?;
+
+        self.client.send(req).await
+    }
+
+    /// Get xattrs of the given path in one request.
+    ///
+    /// All xattrs will be returned if `names` is empty. Values are decoded
+    /// according to `encoding`, xattrs without value will be mapped to an
+    /// empty vec.
+    pub async fn get_xattrs(
+        &self,
+        path: &str,
+        names: &[&str],
+        encoding: XAttrValueEncoding,
+    ) -> Result<HashMap<String, Vec<u8>>> {
+        let path = normalize_path(path);
+        let resp = self.webhdfs_get_xattrs(&path, names, encoding).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let xattrs = serde_json::from_slice::<XAttrsWrapper>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .xattrs;
+
+                let mut m = HashMap::with_capacity(xattrs.len());
+                for xattr in xattrs {
+                    let value = match xattr.value {
+                        Some(v) => decode_xattr_value(&v, encoding).map_err(|err| {
+                            err.with_operation("WebhdfsBackend::get_xattrs")
+                                .with_context("path", &path)
+                                .with_context("name", &xattr.name)
+                        })?,
+                        None => Vec::new(),
+                    };
+                    m.insert(xattr.name, value);
+                }
+
+                Ok(m)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Set xattr of the given path.
+    ///
+    /// With [`XAttrSetFlag::Create`], an error with `ErrorKind::AlreadyExists`
+    /// will be returned if the xattr exists. With [`XAttrSetFlag::Replace`],
+    /// an error with `ErrorKind::NotFound` will be returned if the xattr
+    /// doesn't exist.
+    pub async fn set_xattr(
+        &self,
+        path: &str,
+        name: &str,
+        value: &[u8],
+        flag: XAttrSetFlag,
+    ) -> Result<()> {
+        let path = normalize_path(path);
+        let resp = self.webhdfs_set_xattr(&path, name, value, flag).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp)
+                .await?
+                .with_operation("WebhdfsBackend::set_xattr")
+                .with_context("path", path)
+                .with_context("name", name)),
+        }
+    }
+
+    /// Set xattr of the given path no matter whether it exists or not.
+    ///
+    /// The xattr will be replaced first, and created if it doesn't exist.
+    pub async fn upsert_xattr(&self, path: &str, name: &str, value: &[u8]) -> Result<()> {
+        match self
+            .set_xattr(path, name, value, XAttrSetFlag::Replace)
+            .await
+        {
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                self.set_xattr(path, name, value, XAttrSetFlag::Create)
+                    .await
+            }
+            res => res,
+        }
+    }
+
+    /// Get the content type of the given path stored by `guess_content_type`.
+    ///
+    /// Returns `None` if the file is created without it.
+    pub async fn content_type(&self, path: &str) -> Result<Option<String>> {
+        // Fetch all xattrs since HDFS fails the request if any of the given
+        // names doesn't exist.
+        let mut xattrs = self
+            .get_xattrs(path, &[], XAttrValueEncoding::Base64)
+            .await?;
+
+        match xattrs.remove(WEBHDFS_CONTENT_TYPE_XATTR) {
+            Some(v) => String::from_utf8(v).map(Some).map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "content type is not valid utf-8")
+                    .with_operation("WebhdfsBackend::content_type")
+                    .with_context("path", path)
+                    .set_source(err)
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Walk the dir recursively with the depth and parent of every entry.
+    ///
+    /// Listing with `recursive` returns a flat list of entries, while the
+    /// walker returns them top-down along with their position in the tree,
+    /// which is useful to compare trees.
+    pub fn walk(&self, path: &str) -> WebhdfsWalker {
+        WebhdfsWalker::new(self.clone(), &normalize_path(path))
+    }
+
+    /// Sync the client's state with the active namenode.
+    ///
+    /// Reads sent after `msync` will observe all writes committed on the
+    /// active namenode before it, even if they are served by an observer.
+    pub async fn msync(&self) -> Result<()> {
+        let resp = self.webhdfs_msync().await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Get the datanode location to read the file at `path` directly.
+    ///
+    /// The location is returned by `OPEN` with `noredirect=true` without
+    /// transferring any data, it carries the delegation token if configured,
+    /// so it could be handed to other clients to download the file like a
+    /// presigned url.
+    pub async fn read_location(&self, path: &str, range: BytesRange) -> Result<String> {
+        let path = normalize_path(path);
+        let resp = self.webhdfs_get_read_location(&path, range).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let location = serde_json::from_slice::<LocationResp>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .location;
+
+                Ok(location)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Get all storage policies of the cluster.
+    pub async fn get_all_storage_policies(&self) -> Result<Vec<BlockStoragePolicy>> {
+        let resp = self.webhdfs_get_all_storage_policies().await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let policies = serde_json::from_slice::<BlockStoragePoliciesWrapper>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .block_storage_policies
+                    .block_storage_policy;
+
+                Ok(policies)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Get the default values of the cluster, like block size and replication.
+    pub async fn get_server_defaults(&self) -> Result<ServerDefaults> {
+        let resp = self.webhdfs_get_server_defaults().await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let defaults = serde_json::from_slice::<ServerDefaultsWrapper>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .fs_server_defaults;
+
+                Ok(defaults)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
+
+/// Decode the xattr value returned by webhdfs.
+///
+/// - `text`: value is enclosed in double quotes.
+/// - `hex`: value is prefixed with `0x`.
+/// - `base64`: value is prefixed with `0s`.
+fn decode_xattr_value(v: &str, encoding: XAttrValueEncoding) -> Result<Vec<u8>> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::Unexpected,
+            "xattr value doesn't match the requested encoding",
+        )
+        .with_context("encoding", encoding.as_str())
+        .with_context("value", v)
+    };
+
+    match encoding {
+        XAttrValueEncoding::Text => {
+            let v = v
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(v);
+            Ok(v.as_bytes().to_vec())
+        }
+        XAttrValueEncoding::Hex => {
+            let v = v.strip_prefix("0x").ok_or_else(invalid)?;
+            if v.len() % 2 != 0 {
+                return Err(invalid());
+            }
+            // Decode over bytes, the server could return non-ASCII chars.
+            let nibble = |b: u8| (b as char).to_digit(16).ok_or_else(invalid);
+            v.as_bytes()
+                .chunks(2)
+                .map(|c| Ok((nibble(c[0])? << 4 | nibble(c[1])?) as u8))
+                .collect()
+        }
+        XAttrValueEncoding::Base64 => {
+            let v = v.strip_prefix("0s").ok_or_else(invalid)?;
+            BASE64_STANDARD
+                .decode(v)
+                .map_err(|e| invalid().set_source(e))
+        }
+    }
+}
+
+#[async_trait]
+impl Accessor for WebhdfsBackend {
+    type Reader = IncomingAsyncBody;
+    type BlockingReader = ();
+    type Writer = oio::OneShotWriter<WebhdfsWriter>;
+    type BlockingWriter = ();
+    type Pager = WebhdfsPager;
+    type BlockingPager = ();
+
+    fn info(&self) -> AccessorInfo {
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Webhdfs)
             .set_root(&self.root)
             .set_native_capability(Capability {
                 stat: true,
 
-                read: true,
-                read_can_next: true,
-                read_with_range: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_if_modified_since: true,
+
+                write: true,
+                create_dir: true,
+                delete: true,
+                rename: true,
+                rename_without_overwrite: true,
+
+                list: true,
+                list_without_recursive: true,
+                list_with_name_prefix: true,
+
+                ..Default::default()
+            });
+        am
+    }
+
+    /// Create a file or directory
+    async fn create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
+        let req = self.webhdfs_create_object_request(
+            path,
+            Some(0),
+            &OpWrite::default(),
+            AsyncBody::Empty,
+        )?;
+
+        let resp = self.send_idempotent(req).await?;
+        self.invalidate_cached_stat(path);
+
+        let status = resp.status();
+
+        // WebHDFS's has a two-step create/append to prevent clients to send out
+        // data before creating it.
+        // According to the redirect policy of `reqwest` HTTP Client we are using,
+        // the redirection should be done automatically.
+        match status {
+            StatusCode::CREATED | StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let resp = serde_json::from_slice::<BooleanResp>(&bs)
+                    .map_err(new_json_deserialize_error)?;
+
+                if resp.boolean {
+                    Ok(RpCreateDir::default())
+                } else {
+                    Err(Error::new(
+                        ErrorKind::Unexpected,
+                        "webhdfs create dir failed",
+                    ))
+                }
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        if self.consistent_read {
+            self.msync().await?;
+        }
+
+        // WebHDFS doesn't support conditional read natively, so we check the
+        // modification time via GETFILESTATUS first to avoid the data transfer.
+        if let Some(since) = args.if_modified_since() {
+            let resp = self.webhdfs_get_file_status(path).await?;
+            if resp.status() != StatusCode::OK {
+                return Err(parse_error(resp).await?);
+            }
+
+            let bs = resp.into_body().bytes().await?;
+            let file_status = serde_json::from_slice::<FileStatusWrapper>(&bs)
+                .map_err(new_json_deserialize_error)?
+                .file_status;
+
+            let last_modified =
+                parse_datetime_from_from_timestamp_millis(file_status.modification_time)?;
+            if last_modified <= since {
+                return Err(Error::new(
+                    ErrorKind::ConditionNotMatch,
+                    "file is not modified since the given time",
+                )
+                .with_operation(Operation::Read)
+                .with_context("last_modified", last_modified.to_rfc3339())
+                .with_context("if_modified_since", since.to_rfc3339()));
+            }
+        }
+
+        let range = args.range();
+        let resp = self.webhdfs_read_file(path, range).await?;
+        match resp.status() {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
+                check_read_range(&range, resp.headers())?;
+                // Some gateways return reads with chunked transfer encoding
+                // and no `Content-Length`, the size will be unknown and the
+                // body will be streamed to EOF.
+                let size = parse_content_length(resp.headers())?;
+                Ok((RpRead::new().with_size(size), resp.into_body()))
+            }
+            // WebHDFS will returns 403 when range is outside of the end.
+            StatusCode::FORBIDDEN => {
+                let (parts, body) = resp.into_parts();
+                let bs = body.bytes().await?;
+                let s = String::from_utf8_lossy(&bs);
+                if s.contains("out of the range") {
+                    Ok((RpRead::new(), IncomingAsyncBody::empty()))
+                } else {
+                    Err(parse_error_msg(parts, &s)?)
+                }
+            }
+            StatusCode::RANGE_NOT_SATISFIABLE => Ok((RpRead::new(), IncomingAsyncBody::empty())),
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        if self.auto_create_root {
+            self.root_creator
+                .get_or_try_init(|| async { self.check_root(true).await })
+                .await?;
+        }
+
+        Ok((
+            RpWrite::default(),
+            oio::OneShotWriter::new(WebhdfsWriter::new(self.clone(), args, path.to_string())),
+        ))
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        // if root exists and is a directory, stat will be ok
+        self.root_checker
+            .get_or_try_init(|| async { self.check_root(false).await })
+            .await?;
+
+        if let Some(meta) = self.get_cached_stat(path) {
+            return Ok(RpStat::new(meta));
+        }
+
+        let resp = self.webhdfs_get_file_status(path).await?;
+        let status = resp.status();
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let mut file_status = serde_json::from_slice::<FileStatusWrapper>(&bs)
+                    .map_err(new_json_deserialize_error)?
+                    .file_status;
+
+                // GETFILESTATUS follows symlinks, this should never happen
+                // unless the gateway doesn't. Follow them with bounded depth.
+                if file_status.ty == FileStatusType::Symlink {
+                    file_status = self.resolve_symlink(path).await?.1;
+                }
+
+                let mut meta = match file_status.ty {
+                    FileStatusType::Directory => Metadata::new(EntryMode::DIR),
+                    FileStatusType::File => Metadata::new(EntryMode::FILE)
+                        .with_content_length(file_status.length)
+                        .with_last_modified(parse_datetime_from_from_timestamp_millis(
+                            file_status.modification_time,
+                        )?),
+                    FileStatusType::Symlink => Metadata::new(EntryMode::Unknown),
+                };
+                if let Some(storage_policy) = file_status.storage_policy_name() {
+                    meta.set_storage_class(&storage_policy);
+                }
+
+                self.set_cached_stat(path, &meta);
+                Ok(RpStat::new(meta))
+            }
+
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        if path == "/" && !self.allow_delete_root {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "delete root is not allowed, enable allow_delete_root to do so",
+            )
+            .with_operation(Operation::Delete)
+            .with_context("service", Scheme::Webhdfs)
+            .with_context("root", &self.root));
+        }
+
+        let resp = self.webhdfs_delete(path).await?;
+        self.invalidate_cached_stat(path);
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(RpDelete::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        // HDFS fails the rename if the parent of destination doesn't exist,
+        // `MKDIRS` creates all missing parents and succeeds if exists.
+        let parent = get_parent(to);
+        if parent != "/" {
+            self.create_dir(parent, OpCreateDir::new()).await?;
+        }
+
+        let resp = self.webhdfs_rename(from, to, args.overwrite()).await?;
+        self.invalidate_cached_stat(from);
+        self.invalidate_cached_stat(to);
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                // The newer rename API returns nothing on success, while the
+                // legacy one returns `false` on failure.
+                if !bs.is_empty() {
+                    let resp = serde_json::from_slice::<BooleanResp>(&bs)
+                        .map_err(new_json_deserialize_error)?;
+                    if !resp.boolean {
+                        return Err(Error::new(ErrorKind::Unexpected, "webhdfs rename failed"));
+                    }
+                }
+                Ok(RpRename::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        if args.recursive() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "WebHDFS doesn't support list with recursive",
+            ));
+        }
+
+        let path = path.trim_end_matches('/');
+
+        if !self.disable_list_batch {
+            let req = self.webhdfs_list_status_batch_request(path, &OpList::default())?;
+            let resp = self.send_idempotent(req).await?;
+            match resp.status() {
+                StatusCode::OK => {
+                    let bs = resp.into_body().bytes().await?;
+                    let directory_listing = serde_json::from_slice::<DirectoryListingWrapper>(&bs)
+                        .map_err(new_json_deserialize_error)?
+                        .directory_listing;
+                    let file_statuses = directory_listing.partial_listing.file_statuses.file_status;
+                    let mut objects = WebhdfsPager::new(self.clone(), path, file_statuses)
+                        .with_name_prefix(args.name_prefix());
+                    objects.set_remaining_entries(directory_listing.remaining_entries);
+                    Ok((RpList::default(), objects))
+                }
+                StatusCode::NOT_FOUND => {
+                    let objects = WebhdfsPager::new(self.clone(), path, vec![]);
+                    Ok((RpList::default(), objects))
+                }
+                _ => Err(parse_error(resp).await?),
+            }
+        } else {
+            let req = self.webhdfs_list_status_request(path)?;
+            let resp = self.send_idempotent(req).await?;
+            match resp.status() {
+                StatusCode::OK => {
+                    let bs = resp.into_body().bytes().await?;
+                    let file_statuses = serde_json::from_slice::<FileStatusesWrapper>(&bs)
+                        .map_err(new_json_deserialize_error)?
+                        .file_statuses
+                        .file_status;
+                    let objects = WebhdfsPager::new(self.clone(), path, file_statuses)
+                        .with_name_prefix(args.name_prefix());
+                    Ok((RpList::default(), objects))
+                }
+                StatusCode::NOT_FOUND => {
+                    let objects = WebhdfsPager::new(self.clone(), path, vec![]);
+                    Ok((RpList::default(), objects))
+                }
+                _ => Err(parse_error(resp).await?),
+            }
+        }
+    }
+}
+
+/// Parse `Retry-After` in seconds, the max duration is capped.
+fn parse_retry_after(headers: &http::HeaderMap) -> Option<Duration> {
+    let secs = headers
+        .get(http::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(WEBHDFS_MAX_RETRY_AFTER))
+}
+
+/// Validate the syntax of an aclspec like `user:alice:rwx,default:group::r-x`.
+///
+/// Each entry is `[default:]<type>:<name>:<permission>`, in which `mask` and
+/// `other` entries must not have a name.
+fn validate_acl_spec(spec: &str) -> Result<()> {
+    let new_error = |entry: &str, msg: &'static str| {
+        Error::new(ErrorKind::ConfigInvalid, msg)
+            .with_operation("WebhdfsBackend::set_acl_spec")
+            .with_context("aclspec", spec)
+            .with_context("entry", entry)
+    };
+
+    if spec.is_empty() {
+        return Err(new_error(spec, "aclspec must not be empty"));
+    }
+
+    for entry in spec.split(',') {
+        let parts = entry.strip_prefix("default:").unwrap_or(entry);
+        let (ty, name, perm) = match parts.split(':').collect::<Vec<_>>()[..] {
+            [ty, name, perm] => (ty, name, perm),
+            _ => {
+                return Err(new_error(
+                    entry,
+                    "acl entry must be in the form of [default:]type:name:permission",
+                ))
+            }
+        };
+
+        match ty {
+            "user" | "group" => {}
+            "mask" | "other" if name.is_empty() => {}
+            "mask" | "other" => {
+                return Err(new_error(
+                    entry,
+                    "acl entry of mask or other must not have name",
+                ))
+            }
+            _ => {
+                return Err(new_error(
+                    entry,
+                    "acl entry type must be one of user, group, mask or other",
+                ))
+            }
+        }
+
+        let valid_perm = perm.len() == 3
+            && perm
+                .chars()
+                .zip(['r', 'w', 'x'])
+                .all(|(c, expected)| c == expected || c == '-');
+        if !valid_perm {
+            return Err(new_error(
+                entry,
+                "acl entry permission must be like rwx, r-x or ---",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check whether the response covers the requested range.
+///
+/// Some gateways ignore `offset` and `length` of `OPEN` and return the whole
+/// file, which must not be returned as if it were the range. Responses
+/// without `Content-Range` and `Content-Length` can't be checked.
+fn check_read_range(range: &BytesRange, headers: &http::HeaderMap) -> Result<()> {
+    if range.is_full() {
+        return Ok(());
+    }
+
+    let offset = range.offset().unwrap_or_default();
+    let ignored = match parse_content_range(headers)? {
+        Some(content_range) => match content_range.range() {
+            Some(r) => {
+                r.start != offset || matches!(range.size(), Some(size) if r.end - r.start > size)
+            }
+            None => false,
+        },
+        // The response could be shorter than the range at the end of file.
+        None => matches!(
+            (parse_content_length(headers)?, range.size()),
+            (Some(length), Some(size)) if length > size
+        ),
+    };
+
+    if ignored {
+        return Err(Error::new(
+            ErrorKind::Unexpected,
+            "server ignored range, the response doesn't match the requested range",
+        )
+        .with_operation(Operation::Read)
+        .with_context("range", range.to_string())
+        .with_context(
+            "content_range",
+            headers
+                .get(http::header::CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default(),
+        )
+        .with_context(
+            "content_length",
+            headers
+                .get(CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::io::Write;
+
+    use anyhow::Result;
+    use http::HeaderMap;
+    use serde_json::json;
+    use wiremock::matchers::any;
+    use wiremock::matchers::body_string;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    const DIR_STATUS: &str = r#"{"FileStatus":{"length":0,"modificationTime":1320173277227,"pathSuffix":"","type":"DIRECTORY"}}"#;
+    const SAFE_MODE_ERROR: &str = r#"{"RemoteException":{"exception":"SafeModeException","javaClassName":"org.apache.hadoop.hdfs.server.namenode.SafeModeException","message":"Cannot create file/file. Name node is in safe mode."}}"#;
+    const FILE_NOT_FOUND_ERROR: &str = r#"{"RemoteException":{"exception":"FileNotFoundException","javaClassName":"java.io.FileNotFoundException","message":"File does not exist: /b"}}"#;
+    const FILE_STATUS: &str = r#"{"FileStatus":{"length":24930,"modificationTime":1320173277227,"pathSuffix":"","type":"FILE"}}"#;
+
+    async fn mount_root(mock_server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(DIR_STATUS))
+            .mount(mock_server)
+            .await;
+    }
+
+    fn new_test_builder(endpoint: &str) -> WebhdfsBuilder {
+        let mut builder = WebhdfsBuilder::default();
+        builder.endpoint(endpoint);
+        builder
+    }
+
+    fn new_test_backend(endpoint: &str) -> Result<WebhdfsBackend> {
+        Ok(new_test_builder(endpoint).build()?)
+    }
+
+    #[tokio::test]
+    async fn test_stat_does_not_create_root() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(FILE_NOT_FOUND_ERROR))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(query_param("op", "MKDIRS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"boolean": true}"#))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.root("/root").auto_create_root();
+        let op = Operator::new(builder)?.finish();
+
+        let err = op.stat("file").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_creates_root_lazily() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/root/"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(FILE_NOT_FOUND_ERROR))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/root/"))
+            .and(query_param("op", "MKDIRS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"boolean": true}"#))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/root/file"))
+            .and(query_param("op", "CREATE"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.root("/root").auto_create_root();
+        let op = Operator::new(builder)?.finish();
+
+        // Root is checked and created only once.
+        op.write("file", "Hello, World!").await?;
+        op.write("file", "Hello, World!").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_root_refused() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/webhdfs/v1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"boolean": true}"#))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        let err = backend
+            .delete("/", OpDelete::new())
+            .await
+            .expect_err("delete root must be refused by default");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_root_allowed() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/webhdfs/v1/"))
+            .and(query_param("op", "DELETE"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"boolean": true}"#))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.allow_delete_root();
+        let backend = builder.build()?;
+        backend.delete("/", OpDelete::new()).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_replication() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "SETREPLICATION"))
+            .and(query_param("replication", "3"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"boolean": true}"#))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/dir"))
+            .and(query_param("op", "SETREPLICATION"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"boolean": false}"#))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+
+        backend.set_replication("file", 3).await?;
+
+        let err = backend
+            .set_replication("dir", 3)
+            .await
+            .expect_err("set replication on dir must fail");
+        assert_eq!(err.kind(), ErrorKind::IsADirectory);
+
+        let err = backend
+            .set_replication("file", 0)
+            .await
+            .expect_err("set replication with 0 must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resolve_symlink_with_cycle() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/a"))
+            .and(query_param("op", "GETFILELINKSTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"FileStatus":{"length":0,"modificationTime":1320173277227,"pathSuffix":"","symlink":"/b","type":"SYMLINK"}}"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/b"))
+            .and(query_param("op", "GETFILELINKSTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"FileStatus":{"length":0,"modificationTime":1320173277227,"pathSuffix":"","symlink":"a","type":"SYMLINK"}}"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/c"))
+            .and(query_param("op", "GETFILELINKSTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"FileStatus":{"length":0,"modificationTime":1320173277227,"pathSuffix":"","symlink":"/file","type":"SYMLINK"}}"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILELINKSTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+
+        let (resolved, status) = backend.resolve_symlink("c").await?;
+        assert_eq!(resolved, "file");
+        assert_eq!(status.ty, FileStatusType::File);
+
+        let err = backend
+            .resolve_symlink("a")
+            .await
+            .expect_err("symlink cycle must be rejected");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.to_string().contains("too many symlink levels"));
+
+        let requests = mock_server
+            .received_requests()
+            .await
+            .expect("request recording must be enabled");
+        // 2 requests for `c`, and bounded requests for the cycle.
+        assert_eq!(requests.len(), 2 + WEBHDFS_MAX_SYMLINK_DEPTH + 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_nofollow() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        mount_root(&mock_server).await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/link"))
+            .and(query_param("op", "GETFILELINKSTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"FileStatus":{"length":0,"modificationTime":1320173277227,"pathSuffix":"","symlink":"/target","type":"SYMLINK"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/link"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+
+        let status = backend.stat_nofollow("link").await?;
+        assert_eq!(status.ty, FileStatusType::Symlink);
+        assert_eq!(status.symlink.as_deref(), Some("/target"));
+
+        let meta = backend.stat("link", OpStat::new()).await?.into_metadata();
+        assert!(meta.mode().is_file());
+        assert_eq!(meta.content_length(), 24930);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_raw_op() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .and(query_param("user.name", "hdfs"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+
+        let resp = backend
+            .raw_op(
+                Method::GET,
+                "file",
+                "GETFILESTATUS",
+                &[("user.name", "hdfs")],
+                AsyncBody::Empty,
+            )
+            .await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let bs = resp.into_body().bytes().await?;
+        let file_status = serde_json::from_slice::<FileStatusWrapper>(&bs)?.file_status;
+        assert_eq!(file_status.ty, FileStatusType::File);
+        assert_eq!(file_status.length, 24930);
+
+        Ok(())
+    }
+
+    const XATTR_EXISTS_ERROR: &str = r#"{"RemoteException":{"exception":"IOException","javaClassName":"java.io.IOException","message":"XAttr: user.key already exists. The REPLACE flag must be specified."}}"#;
+    const XATTR_NOT_EXIST_ERROR: &str = r#"{"RemoteException":{"exception":"IOException","javaClassName":"java.io.IOException","message":"XAttr: user.key does not exist. The CREATE flag must be specified."}}"#;
+
+    #[tokio::test]
+    async fn test_set_xattr() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        // `new` doesn't have the xattr while `existing` has.
+        for (file, flag, status, body) in [
+            ("new", "CREATE", 200, ""),
+            ("new", "REPLACE", 403, XATTR_NOT_EXIST_ERROR),
+            ("existing", "CREATE", 403, XATTR_EXISTS_ERROR),
+            ("existing", "REPLACE", 200, ""),
+        ] {
+            Mock::given(method("PUT"))
+                .and(path(format!("/webhdfs/v1/{file}")))
+                .and(query_param("op", "SETXATTR"))
+                .and(query_param("xattr.name", "user.key"))
+                // base64 of `value`
+                .and(query_param("xattr.value", "0sdmFsdWU="))
+                .and(query_param("flag", flag))
+                .respond_with(ResponseTemplate::new(status).set_body_string(body))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let backend = new_test_backend(&mock_server.uri())?;
+
+        backend
+            .set_xattr("new", "user.key", b"value", XAttrSetFlag::Create)
+            .await?;
+        let err = backend
+            .set_xattr("new", "user.key", b"value", XAttrSetFlag::Replace)
+            .await
+            .expect_err("replace missing xattr must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        backend
+            .set_xattr("existing", "user.key", b"value", XAttrSetFlag::Replace)
+            .await?;
+        let err = backend
+            .set_xattr("existing", "user.key", b"value", XAttrSetFlag::Create)
+            .await
+            .expect_err("create existing xattr must fail");
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_upsert_xattr() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "SETXATTR"))
+            .and(query_param("flag", "REPLACE"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(XATTR_NOT_EXIST_ERROR))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "SETXATTR"))
+            .and(query_param("flag", "CREATE"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        backend.upsert_xattr("file", "user.key", b"value").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_xattrs() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETXATTRS"))
+            .and(query_param("encoding", "hex"))
+            .and(query_param("xattr.name", "user.a1"))
+            .and(query_param("xattr.name", "user.a2"))
+            .and(query_param("xattr.name", "user.a3"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"XAttrs":[{"name":"user.a1","value":"0x313233"},{"name":"user.a2","value":"0x00ff"},{"name":"user.a3","value":null}]}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        let xattrs = backend
+            .get_xattrs(
+                "file",
+                &["user.a1", "user.a2", "user.a3"],
+                XAttrValueEncoding::Hex,
+            )
+            .await?;
+        assert_eq!(xattrs.len(), 3);
+        assert_eq!(xattrs["user.a1"], b"123".to_vec());
+        assert_eq!(xattrs["user.a2"], vec![0x00, 0xff]);
+        assert_eq!(xattrs["user.a3"], Vec::<u8>::new());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_xattr_value_with_invalid_hex() {
+        for v in ["0x1", "0xzz", "0x+f", "0xé1", "0xéé"] {
+            let err = decode_xattr_value(v, XAttrValueEncoding::Hex)
+                .expect_err("decode invalid hex must fail");
+            assert_eq!(err.kind(), ErrorKind::Unexpected, "value: {v}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_with_guess_content_type() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/x.json"))
+            .and(query_param("op", "CREATE"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/x.json"))
+            .and(query_param("op", "SETXATTR"))
+            .and(query_param("xattr.name", "user.contenttype"))
+            // base64 of `application/json`
+            .and(query_param("xattr.value", "0sYXBwbGljYXRpb24vanNvbg=="))
+            .and(query_param("flag", "CREATE"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/x.json"))
+            .and(query_param("op", "GETXATTRS"))
+            .and(query_param("encoding", "base64"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"XAttrs":[{"name":"user.contenttype","value":"0sYXBwbGljYXRpb24vanNvbg=="}]}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let new_builder = || {
+            let mut builder = new_test_builder(&mock_server.uri());
+            builder.guess_content_type();
+            builder
+        };
+        let backend = new_builder().build()?;
+        let op = Operator::new(new_builder())?.finish();
+
+        op.write("x.json", "{}").await?;
+        assert_eq!(
+            backend.content_type("x.json").await?.as_deref(),
+            Some("application/json")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rename_with_overwrite() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/from"))
+            .and(query_param("op", "RENAME"))
+            .and(query_param("destination", "/to"))
+            .and(query_param("renameoptions", "OVERWRITE"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+        op.rename_with("from", "to").overwrite(true).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rename_without_overwrite() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/from"))
+            .and(query_param("op", "RENAME"))
+            .and(query_param("destination", "/to"))
+            .and(query_param("renameoptions", "NONE"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"{"RemoteException":{"exception":"FileAlreadyExistsException","javaClassName":"org.apache.hadoop.fs.FileAlreadyExistsException","message":"rename destination /to already exists."}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+        let err = op
+            .rename_with("from", "to")
+            .overwrite(false)
+            .await
+            .expect_err("rename must fail");
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rename_creates_parent_dirs() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/a/b/"))
+            .and(query_param("op", "MKDIRS"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(r#"{"boolean": true}"#, "application/json"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/from"))
+            .and(query_param("op", "RENAME"))
+            .and(query_param("destination", "/a/b/to"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+        op.rename("from", "a/b/to").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_with_name_prefix() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/dir"))
+            .and(query_param("op", "LISTSTATUS_BATCH"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"DirectoryListing":{"partialListing":{"FileStatuses":{"FileStatus":[
+{"length":1,"modificationTime":1320173277227,"pathSuffix":"log-1","type":"FILE"},
+{"length":2,"modificationTime":1320173277227,"pathSuffix":"data","type":"FILE"},
+{"length":0,"modificationTime":1320173277227,"pathSuffix":"log-dir","type":"DIRECTORY"}
+]}},"remainingEntries":0}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebhdfsBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        let op = Operator::new(builder)?.finish();
+
+        let mut paths: Vec<String> = op
+            .list_with("dir/")
+            .name_prefix("log-")
+            .await?
+            .into_iter()
+            .map(|e| e.path().to_string())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["dir/log-1", "dir/log-dir/"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_with_skip_dir_placeholder() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/dir"))
+            .and(query_param("op", "LISTSTATUS_BATCH"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"DirectoryListing":{"partialListing":{"FileStatuses":{"FileStatus":[
+{"length":0,"modificationTime":1320173277227,"pathSuffix":"sub","type":"FILE"},
+{"length":0,"modificationTime":1320173277227,"pathSuffix":"sub","type":"DIRECTORY"}
+]}},"remainingEntries":0}}"#,
+            ))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let op = Operator::new(new_test_builder(&mock_server.uri()))?.finish();
+        let entries = op.list("dir/").await?;
+        assert_eq!(entries.len(), 2, "placeholder is kept by default");
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.skip_dir_placeholder();
+        let op = Operator::new(builder)?.finish();
+        let entries = op.list("dir/").await?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), "dir/sub/");
+        assert!(entries[0].metadata().mode().is_dir());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_special_chars_in_path() -> Result<()> {
+        // `a b&c#d.txt` encoded.
+        let file_path = "/webhdfs/v1/dir/a%20b%26c%23d.txt";
+
+        let mock_server = MockServer::start().await;
+        mount_root(&mock_server).await;
+        Mock::given(method("PUT"))
+            .and(path(file_path))
+            .and(query_param("op", "CREATE"))
+            .and(body_string("Hello, World!"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(file_path))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"FileStatus":{"length":13,"modificationTime":1320173277227,"pathSuffix":"","type":"FILE"}}"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(file_path))
+            .and(query_param("op", "OPEN"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(file_path))
+            .and(query_param("op", "DELETE"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"boolean": true}"#))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/dir"))
+            .and(query_param("op", "LISTSTATUS_BATCH"))
+            .and(query_param("startAfter", "a b&c#d.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"DirectoryListing":{"partialListing":{"FileStatuses":{"FileStatus":[
+{"length":1,"modificationTime":1320173277227,"pathSuffix":"e+f.txt","type":"FILE"}
+]}},"remainingEntries":0}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/dir"))
+            .and(query_param("op", "LISTSTATUS_BATCH"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"DirectoryListing":{"partialListing":{"FileStatuses":{"FileStatus":[
+{"length":13,"modificationTime":1320173277227,"pathSuffix":"a b&c#d.txt","type":"FILE"}
+]}},"remainingEntries":1}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebhdfsBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        let op = Operator::new(builder)?.finish();
+
+        op.write("dir/a b&c#d.txt", "Hello, World!").await?;
+
+        let meta = op.stat("dir/a b&c#d.txt").await?;
+        assert_eq!(meta.content_length(), 13);
+
+        let bs = op.read("dir/a b&c#d.txt").await?;
+        assert_eq!(bs, b"Hello, World!");
+
+        let mut paths: Vec<String> = op
+            .list("dir/")
+            .await?
+            .into_iter()
+            .map(|e| e.path().to_string())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["dir/a b&c#d.txt", "dir/e+f.txt"]);
+
+        op.delete("dir/a b&c#d.txt").await?;
+
+        Ok(())
+    }
+
+    /// Mount a directory with `total` files which is listed in batches of
+    /// `batch_size`.
+    #[tokio::test]
+    async fn test_walk() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        for (dir, statuses) in [
+            (
+                "tree",
+                r#"{"length":0,"modificationTime":1320173277227,"pathSuffix":"a","type":"DIRECTORY"},
+{"length":1,"modificationTime":1320173277227,"pathSuffix":"f","type":"FILE"}"#,
+            ),
+            (
+                "tree/a",
+                r#"{"length":0,"modificationTime":1320173277227,"pathSuffix":"b","type":"DIRECTORY"},
+{"length":1,"modificationTime":1320173277227,"pathSuffix":"g","type":"FILE"}"#,
+            ),
+            (
+                "tree/a/b",
+                r#"{"length":1,"modificationTime":1320173277227,"pathSuffix":"h","type":"FILE"}"#,
+            ),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/webhdfs/v1/{dir}")))
+                .and(query_param("op", "LISTSTATUS"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                    r#"{{"FileStatuses":{{"FileStatus":[{statuses}]}}}}"#
+                )))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.disable_list_batch();
+        let backend = builder.build()?;
+
+        let mut walker = backend.walk("tree/");
+        let mut entries = vec![];
+        while let Some(page) = walker.next().await? {
+            entries.extend(
+                page.into_iter()
+                    .map(|e| (e.entry.path().to_string(), e.depth, e.parent)),
+            );
+        }
+        entries.sort();
+        assert_eq!(
+            entries,
+            vec![
+                ("tree/a/".to_string(), 1, "tree/".to_string()),
+                ("tree/a/b/".to_string(), 2, "tree/a/".to_string()),
+                ("tree/a/b/h".to_string(), 3, "tree/a/b/".to_string()),
+                ("tree/a/g".to_string(), 2, "tree/a/".to_string()),
+                ("tree/f".to_string(), 1, "tree/".to_string()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    async fn mount_large_dir(mock_server: &MockServer, total: usize, batch_size: usize) {
+        let names: Vec<String> = (0..total).map(|i| format!("file-{i:03}")).collect();
+        // Mount the later batches first, so that they are matched before the
+        // first batch which doesn't have `startAfter`.
+        for (i, batch) in names.chunks(batch_size).enumerate().rev() {
+            let statuses: Vec<String> = batch
+                .iter()
+                .map(|name| {
+                    format!(
+                        r#"{{"length":1,"modificationTime":1320173277227,"pathSuffix":"{name}","type":"FILE"}}"#
+                    )
+                })
+                .collect();
+            let body = format!(
+                r#"{{"DirectoryListing":{{"partialListing":{{"FileStatuses":{{"FileStatus":[{}]}}}},"remainingEntries":{}}}}}"#,
+                statuses.join(","),
+                total - (i * batch_size + batch.len()),
+            );
+
+            let mut mock = Mock::given(method("GET"))
+                .and(path("/webhdfs/v1/dir"))
+                .and(query_param("op", "LISTSTATUS_BATCH"))
+                .and(query_param("batchSize", batch_size.to_string()));
+            if i > 0 {
+                mock = mock.and(query_param(
+                    "startAfter",
+                    names[i * batch_size - 1].as_str(),
+                ));
+            }
+            mock.respond_with(ResponseTemplate::new(200).set_body_string(body))
+                .expect(1)
+                .mount(mock_server)
+                .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_with_batch_size() -> Result<()> {
+        let mut requests = vec![];
+        for batch_size in [10, 100] {
+            let mock_server = MockServer::start().await;
+            mount_large_dir(&mock_server, 100, batch_size).await;
+
+            let mut builder = new_test_builder(&mock_server.uri());
+            builder.list_batch_size(batch_size);
+            let op = Operator::new(builder)?.finish();
+
+            let entries = op.list("dir/").await?;
+            assert_eq!(entries.len(), 100);
+
+            requests.push(
+                mock_server
+                    .received_requests()
+                    .await
+                    .expect("request recording must be enabled")
+                    .len(),
+            );
+        }
+        assert_eq!(requests, vec![10, 1]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_in_safe_mode() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        mount_root(&mock_server).await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(SAFE_MODE_ERROR))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebhdfsBuilder::default();
+        builder
+            .endpoint(&mock_server.uri())
+            .safe_mode_max_retries(1);
+        let op = Operator::new(builder)?.finish();
+
+        let meta = op.stat("file").await?;
+        assert_eq!(meta.content_length(), 24930);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_retry_after() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        mount_root(&mock_server).await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "1"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.safe_mode_max_retries(1);
+        let op = Operator::new(builder)?.finish();
+
+        let start = Instant::now();
+        let meta = op.stat("file").await?;
+        assert_eq!(meta.content_length(), 24930);
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append_is_not_retried() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/webhdfs/v1/log"))
+            .and(query_param("op", "APPEND"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "1"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.safe_mode_max_retries(3);
+        let backend = builder.build()?;
+
+        // A replayed append could duplicate data, so it's never retried.
+        let err = backend
+            .append("log", Bytes::from("line\n"))
+            .await
+            .expect_err("append must fail");
+        assert!(err.is_temporary());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_in_safe_mode() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CREATE"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(SAFE_MODE_ERROR))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebhdfsBuilder::default();
+        builder
+            .endpoint(&mock_server.uri())
+            .safe_mode_max_retries(1);
+        let op = Operator::new(builder)?.finish();
+
+        let err = op
+            .write("file", "Hello, World!")
+            .await
+            .expect_err("write must fail");
+        assert!(err.is_temporary());
+        assert!(err.to_string().contains("cluster in safe mode"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_location() -> Result<()> {
+        let location =
+            "http://datanode.example:9864/webhdfs/v1/file?op=OPEN&namenoderpcaddress=namenode:8020&offset=0&length=10";
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "OPEN"))
+            .and(query_param("noredirect", "true"))
+            .and(query_param("offset", "0"))
+            .and(query_param("length", "10"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(format!(r#"{{"Location":"{location}"}}"#)),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        let got = backend
+            .read_location("file", BytesRange::new(Some(0), Some(10)))
+            .await?;
+        assert_eq!(got, location);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_exists_many() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        for name in ["a", "d"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/webhdfs/v1/{name}")))
+                .and(query_param("op", "GETFILESTATUS"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        for name in ["b", "c"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/webhdfs/v1/{name}")))
+                .and(query_param("op", "GETFILESTATUS"))
+                .respond_with(ResponseTemplate::new(404).set_body_string(FILE_NOT_FOUND_ERROR))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        let got = backend.exists_many(&["a", "b", "c", "d"], 2).await?;
+        assert_eq!(
+            got,
+            HashMap::from([
+                ("a".to_string(), true),
+                ("b".to_string(), false),
+                ("c".to_string(), false),
+                ("d".to_string(), true),
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append_to_file_being_created() -> Result<()> {
+        const BEING_CREATED_ERROR: &str = r#"{"RemoteException":{"exception":"AlreadyBeingCreatedException","javaClassName":"org.apache.hadoop.hdfs.protocol.AlreadyBeingCreatedException","message":"Failed to APPEND_FILE /log for DFSClient_NONMAPREDUCE_1 on 10.0.0.1 because this file lease is currently owned by DFSClient_NONMAPREDUCE_2 on 10.0.0.2"}}"#;
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/webhdfs/v1/log"))
+            .and(query_param("op", "APPEND"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(BEING_CREATED_ERROR))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/webhdfs/v1/log"))
+            .and(query_param("op", "APPEND"))
+            .and(body_string("line\n"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Without retry, the error is returned as temporary.
+        let backend = new_test_backend(&mock_server.uri())?;
+        let err = backend
+            .append("log", Bytes::from("line\n"))
+            .await
+            .expect_err("append to file being created must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.is_temporary());
+        assert!(
+            err.to_string()
+                .contains("file is being written by another client"),
+            "{err}"
+        );
+
+        // With retry, the append succeeds after the lease is released.
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.append_max_retries(1);
+        let backend = builder.build()?;
+        backend.append("log", Bytes::from("line\n")).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compact() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        for (name, content) in [("a", "first"), ("b", "second"), ("c", "third")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/webhdfs/v1/logs/{name}")))
+                .and(query_param("op", "OPEN"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(content))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/webhdfs/v1/logs/compacted"))
+            .and(query_param("op", "APPEND"))
+            .and(body_string("first\nsecond\nthird"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        let compacted = backend
+            .compact("logs/compacted", &["logs/a", "logs/b", "logs/c"], b"\n")
+            .await?;
+        assert_eq!(compacted, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_acl_spec() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "SETACL"))
+            .and(query_param(
+                "aclspec",
+                "user::rwx,user:alice:rw-,group::r-x,mask::rwx,other::---",
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        backend
+            .set_acl_spec(
+                "file",
+                "user::rwx,user:alice:rw-,group::r-x,mask::rwx,other::---",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_acl_spec_with_malformed_spec() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
 
-                write: true,
-                create_dir: true,
-                delete: true,
+        let backend = new_test_backend(&mock_server.uri())?;
+        for spec in [
+            "",
+            "user:alice",
+            "user:alice:rwx:extra",
+            "owner:alice:rwx",
+            "other:bob:r--",
+            "user:alice:rwz",
+            "user:alice:xwr",
+            "default:group::rw",
+            "user:alice:rwx,",
+        ] {
+            let err = backend
+                .set_acl_spec("file", spec)
+                .await
+                .expect_err("malformed aclspec must be rejected");
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{spec}");
+        }
 
-                list: true,
-                list_without_recursive: true,
+        // Valid default entries are accepted.
+        assert!(validate_acl_spec("default:user:alice:r-x,default:mask::r-x").is_ok());
 
-                ..Default::default()
-            });
-        am
+        Ok(())
     }
 
-    /// Create a file or directory
-    async fn create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
-        let req = self.webhdfs_create_object_request(
-            path,
-            Some(0),
-            &OpWrite::default(),
-            AsyncBody::Empty,
-        )?;
+    #[tokio::test]
+    async fn test_rename_snapshot() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/dir"))
+            .and(query_param("op", "RENAMESNAPSHOT"))
+            .and(query_param("oldsnapshotname", "s1"))
+            .and(query_param("snapshotname", "s2"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/dir"))
+            .and(query_param("op", "RENAMESNAPSHOT"))
+            .and(query_param("oldsnapshotname", "missing"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"{"RemoteException":{"exception":"SnapshotException","javaClassName":"org.apache.hadoop.hdfs.protocol.SnapshotException","message":"The snapshot missing does not exist for directory /dir"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/plain"))
+            .and(query_param("op", "RENAMESNAPSHOT"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"{"RemoteException":{"exception":"SnapshotException","javaClassName":"org.apache.hadoop.hdfs.protocol.SnapshotException","message":"Directory is not a snapshottable directory: /plain"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
 
-        let resp = self.client.send(req).await?;
+        let backend = new_test_backend(&mock_server.uri())?;
+        backend.rename_snapshot("dir", "s1", "s2").await?;
 
-        let status = resp.status();
+        let err = backend
+            .rename_snapshot("dir", "missing", "s2")
+            .await
+            .expect_err("rename must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
 
-        // WebHDFS's has a two-step create/append to prevent clients to send out
-        // data before creating it.
-        // According to the redirect policy of `reqwest` HTTP Client we are using,
-        // the redirection should be done automatically.
-        match status {
-            StatusCode::CREATED | StatusCode::OK => {
-                let bs = resp.into_body().bytes().await?;
+        let err = backend
+            .rename_snapshot("plain", "s1", "s2")
+            .await
+            .expect_err("rename must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
 
-                let resp = serde_json::from_slice::<BooleanResp>(&bs)
-                    .map_err(new_json_deserialize_error)?;
+        Ok(())
+    }
 
-                if resp.boolean {
-                    Ok(RpCreateDir::default())
-                } else {
-                    Err(Error::new(
-                        ErrorKind::Unexpected,
-                        "webhdfs create dir failed",
-                    ))
-                }
-            }
-            _ => Err(parse_error(resp).await?),
+    #[tokio::test]
+    async fn test_truncate_on_old_cluster() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "TRUNCATE"))
+            .respond_with(ResponseTemplate::new(400).set_body_string(
+                r#"{"RemoteException":{"exception":"IllegalArgumentException","javaClassName":"java.lang.IllegalArgumentException","message":"Invalid value for webhdfs parameter \"op\": No enum constant org.apache.hadoop.hdfs.web.resources.PostOpParam.Op.TRUNCATE"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        let err = backend
+            .truncate("file", 10, Duration::from_secs(1))
+            .await
+            .expect_err("truncate must fail on old cluster");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert!(err
+            .to_string()
+            .contains("not supported by this HDFS version"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_quota() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/team"))
+            .and(query_param("op", "SETQUOTA"))
+            .and(query_param("namespacequota", "1000"))
+            .and(query_param("storagespacequota", "1073741824"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/denied"))
+            .and(query_param("op", "SETQUOTA"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"{"RemoteException":{"exception":"AccessControlException","javaClassName":"org.apache.hadoop.security.AccessControlException","message":"Access denied for user alice. Superuser privilege is required"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        backend
+            .set_quota("team", Some(1000), Some(1073741824))
+            .await?;
+
+        let err = backend
+            .set_quota("denied", None, Some(1073741824))
+            .await
+            .expect_err("set quota must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        let err = backend
+            .set_quota("team", Some(0), None)
+            .await
+            .expect_err("zero quota must be rejected");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_cache() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        mount_root(&mock_server).await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CREATE"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.stat_cache_ttl(Duration::from_secs(60));
+        let op = Operator::new(builder)?.finish();
+
+        let count_stat_requests = || async {
+            mock_server
+                .received_requests()
+                .await
+                .unwrap_or_default()
+                .iter()
+                .filter(|req| {
+                    req.url.path() == "/webhdfs/v1/file"
+                        && req
+                            .url
+                            .query()
+                            .unwrap_or_default()
+                            .contains("op=GETFILESTATUS")
+                })
+                .count()
+        };
+
+        assert_eq!(op.stat("file").await?.content_length(), 24930);
+        assert_eq!(op.stat("file").await?.content_length(), 24930);
+        assert_eq!(count_stat_requests().await, 1);
+
+        op.write("file", "Hello, World!").await?;
+        op.stat("file").await?;
+        assert_eq!(count_stat_requests().await, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_consistent_read() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/"))
+            .and(query_param("op", "MSYNC"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "OPEN"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebhdfsBuilder::default();
+        builder.endpoint(&mock_server.uri()).consistent_read();
+        let backend = builder.build()?;
+
+        let (_, body) = backend.read("file", OpRead::new()).await?;
+        assert_eq!(body.bytes().await?, "Hello, World!");
+
+        let ops: Vec<String> = mock_server
+            .received_requests()
+            .await
+            .expect("recording must be enabled")
+            .iter()
+            .filter_map(|req| {
+                req.url
+                    .query_pairs()
+                    .find(|(k, _)| k == "op")
+                    .map(|(_, v)| v.to_string())
+            })
+            .collect();
+        assert_eq!(ops, vec!["MSYNC", "OPEN"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_truncate() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "TRUNCATE"))
+            .and(query_param("newlength", "1024"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"boolean": false}"#))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // The first poll reports the truncation is still in progress.
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"FileStatus":{"length":1024,"modificationTime":1320173277227,"pathSuffix":"","type":"FILE"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        backend
+            .truncate("file", 1024, Duration::from_secs(10))
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concat_with_non_full_block_source() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        for (name, length) in [("file", 2048), ("part-1", 1500), ("part-2", 100)] {
+            Mock::given(method("GET"))
+                .and(path(format!("/webhdfs/v1/{name}")))
+                .and(query_param("op", "GETFILESTATUS"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                    r#"{{"FileStatus":{{"length":{length},"blockSize":1024,"modificationTime":1320173277227,"pathSuffix":"","type":"FILE"}}}}"#,
+                )))
+                .mount(&mock_server)
+                .await;
         }
+        Mock::given(method("POST"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CONCAT"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        let err = backend
+            .concat("file", &["part-1", "part-2"])
+            .await
+            .expect_err("concat must fail");
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+        assert!(err.to_string().contains("full blocks"));
+        assert!(err.to_string().contains("part-1"));
+
+        Ok(())
     }
 
-    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let range = args.range();
-        let resp = self.webhdfs_read_file(path, range).await?;
-        match resp.status() {
-            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let size = parse_content_length(resp.headers())?;
-                Ok((RpRead::new().with_size(size), resp.into_body()))
-            }
-            // WebHDFS will returns 403 when range is outside of the end.
-            StatusCode::FORBIDDEN => {
-                let (parts, body) = resp.into_parts();
-                let bs = body.bytes().await?;
-                let s = String::from_utf8_lossy(&bs);
-                if s.contains("out of the range") {
-                    Ok((RpRead::new(), IncomingAsyncBody::empty()))
-                } else {
-                    Err(parse_error_msg(parts, &s)?)
-                }
+    #[tokio::test]
+    async fn test_read_parallel() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"FileStatus":{"length":13,"modificationTime":1320173277227,"pathSuffix":"","type":"FILE"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        for (offset, length, content, delay) in [
+            // The first chunk is the slowest, but still returned first.
+            ("0", "5", "Hello", 200),
+            ("5", "5", ", Wor", 0),
+            ("10", "3", "ld!", 0),
+        ] {
+            Mock::given(method("GET"))
+                .and(path("/webhdfs/v1/file"))
+                .and(query_param("op", "OPEN"))
+                .and(query_param("offset", offset))
+                .and(query_param("length", length))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_string(content)
+                        .set_delay(Duration::from_millis(delay)),
+                )
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let backend = new_test_backend(&mock_server.uri())?;
+        let body = backend.read_parallel("file", 5, 2).await?;
+        assert_eq!(body.bytes().await?, "Hello, World!");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_op_is_the_first_query_param() -> Result<()> {
+        /// Assert that the operation has sent requests, and all of them
+        /// carry `op` first and the delegation token last.
+        async fn check_new_requests(mock_server: &MockServer, checked: &mut usize, name: &str) {
+            let requests = mock_server
+                .received_requests()
+                .await
+                .expect("request recording must be enabled");
+            assert!(requests.len() > *checked, "{name} sent no request");
+            for req in &requests[*checked..] {
+                let query = req.url.query().unwrap_or_default();
+                assert!(query.starts_with("op="), "{name}: {query}");
+                assert!(
+                    query.ends_with("&delegation_token=token"),
+                    "{name}: {query}"
+                );
             }
-            StatusCode::RANGE_NOT_SATISFIABLE => Ok((RpRead::new(), IncomingAsyncBody::empty())),
-            _ => Err(parse_error(resp).await?),
+            *checked = requests.len();
         }
+
+        let mock_server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+            .mount(&mock_server)
+            .await;
+
+        let new_builder = || {
+            let mut builder = new_test_builder(&mock_server.uri());
+            builder.delegation("token");
+            builder.single_hop_create();
+            builder
+        };
+        let backend = new_builder().build()?;
+        let op = Operator::new(new_builder())?.finish();
+        let mut checked = 0;
+
+        // The mocked responses are not valid for every operation, so only
+        // the requests are checked here.
+        let _ = op.write("file", "Hello, World!").await;
+        check_new_requests(&mock_server, &mut checked, "write").await;
+        let _ = op.create_dir("dir/").await;
+        check_new_requests(&mock_server, &mut checked, "create_dir").await;
+        let _ = op.read_with("file").range(1..5).await;
+        check_new_requests(&mock_server, &mut checked, "read").await;
+        let _ = op.stat("file").await;
+        check_new_requests(&mock_server, &mut checked, "stat").await;
+        let _ = op.list("dir/").await;
+        check_new_requests(&mock_server, &mut checked, "list").await;
+        let _ = op.rename("file", "renamed").await;
+        check_new_requests(&mock_server, &mut checked, "rename").await;
+        let _ = op.delete("file").await;
+        check_new_requests(&mock_server, &mut checked, "delete").await;
+        let _ = backend
+            .read_location("file", BytesRange::new(Some(1), Some(4)))
+            .await;
+        check_new_requests(&mock_server, &mut checked, "read_location").await;
+        let _ = backend.stat_nofollow("file").await;
+        check_new_requests(&mock_server, &mut checked, "stat_nofollow").await;
+        let _ = backend.set_replication("file", 2).await;
+        check_new_requests(&mock_server, &mut checked, "set_replication").await;
+        let _ = backend.truncate("file", 1, Duration::from_millis(10)).await;
+        check_new_requests(&mock_server, &mut checked, "truncate").await;
+        let _ = backend.concat("file", &["a", "b"]).await;
+        check_new_requests(&mock_server, &mut checked, "concat").await;
+        let _ = backend
+            .get_xattrs("file", &["user.a"], XAttrValueEncoding::Text)
+            .await;
+        check_new_requests(&mock_server, &mut checked, "get_xattrs").await;
+        let _ = backend
+            .set_xattr("file", "user.a", b"v", XAttrSetFlag::Create)
+            .await;
+        check_new_requests(&mock_server, &mut checked, "set_xattr").await;
+        let _ = backend.list_snapshottable_dirs().await;
+        check_new_requests(&mock_server, &mut checked, "list_snapshottable_dirs").await;
+        let _ = backend.rename_snapshot("dir", "s1", "s2").await;
+        check_new_requests(&mock_server, &mut checked, "rename_snapshot").await;
+        let _ = backend.set_quota("dir", Some(10), None).await;
+        check_new_requests(&mock_server, &mut checked, "set_quota").await;
+        let _ = backend.get_all_storage_policies().await;
+        check_new_requests(&mock_server, &mut checked, "get_all_storage_policies").await;
+        let _ = backend.get_server_defaults().await;
+        check_new_requests(&mock_server, &mut checked, "get_server_defaults").await;
+        let _ = backend.msync().await;
+        check_new_requests(&mock_server, &mut checked, "msync").await;
+
+        Ok(())
     }
 
-    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        Ok((
-            RpWrite::default(),
-            oio::OneShotWriter::new(WebhdfsWriter::new(self.clone(), args, path.to_string())),
-        ))
+    #[tokio::test]
+    async fn test_datanode_connection_close() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "OPEN"))
+            .and(header("connection", "close"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CREATE"))
+            .and(header("connection", "close"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Use a fresh client for each request, the server closes the
+        // connection after every response.
+        let new_op = || -> Result<Operator> {
+            let mut builder = new_test_builder(&mock_server.uri());
+            builder.datanode_connection_close();
+            Ok(Operator::new(builder)?.finish())
+        };
+
+        new_op()?.write("file", "Hello, World!").await?;
+        assert_eq!(new_op()?.read("file").await?, b"Hello, World!");
+
+        Ok(())
     }
 
-    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
-        // if root exists and is a directory, stat will be ok
-        self.root_checker
-            .get_or_try_init(|| async { self.check_root().await })
+    #[tokio::test]
+    async fn test_read_with_range_ignored() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        // The gateway ignores the range and returns the whole file.
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "OPEN"))
+            .and(query_param("offset", "7"))
+            .and(query_param("length", "5"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "OPEN"))
+            .and(query_param("offset", "0"))
+            .and(query_param("length", "5"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_test_backend(&mock_server.uri())?;
+
+        let err = backend
+            .read(
+                "file",
+                OpRead::new().with_range(BytesRange::new(Some(7), Some(5))),
+            )
+            .await
+            .err()
+            .expect("read with ignored range must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.to_string().contains("server ignored range"));
+
+        let (_, body) = backend
+            .read(
+                "file",
+                OpRead::new().with_range(BytesRange::new(Some(0), Some(5))),
+            )
             .await?;
+        assert_eq!(body.bytes().await?, "Hello");
 
-        let resp = self.webhdfs_get_file_status(path).await?;
-        let status = resp.status();
-        match status {
-            StatusCode::OK => {
-                let bs = resp.into_body().bytes().await?;
+        Ok(())
+    }
 
-                let file_status = serde_json::from_slice::<FileStatusWrapper>(&bs)
-                    .map_err(new_json_deserialize_error)?
-                    .file_status;
+    #[tokio::test]
+    async fn test_read_with_if_modified_since() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "OPEN"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
 
-                let meta = match file_status.ty {
-                    FileStatusType::Directory => Metadata::new(EntryMode::DIR),
-                    FileStatusType::File => Metadata::new(EntryMode::FILE)
-                        .with_content_length(file_status.length)
-                        .with_last_modified(parse_datetime_from_from_timestamp_millis(
-                            file_status.modification_time,
-                        )?),
-                };
+        let backend = new_test_backend(&mock_server.uri())?;
 
-                Ok(RpStat::new(meta))
-            }
+        // File is modified at 2011-11-01T18:47:57.227Z.
+        let unchanged = parse_datetime_from_rfc3339("2012-01-01T00:00:00Z")?;
+        let err = backend
+            .read("file", OpRead::new().with_if_modified_since(unchanged))
+            .await
+            .err()
+            .expect("read unchanged file must fail");
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
 
-            _ => Err(parse_error(resp).await?),
-        }
+        let changed = parse_datetime_from_rfc3339("2011-01-01T00:00:00Z")?;
+        let (_, body) = backend
+            .read("file", OpRead::new().with_if_modified_since(changed))
+            .await?;
+        assert_eq!(body.bytes().await?, "Hello, World!");
+
+        Ok(())
     }
 
-    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
-        let resp = self.webhdfs_delete(path).await?;
+    #[tokio::test]
+    async fn test_write_with_datanode_error() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CREATE"))
+            .respond_with(ResponseTemplate::new(307).insert_header(
+                "Location",
+                format!("{}/datanode/file", mock_server.uri()).as_str(),
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datanode/file"))
+            .respond_with(ResponseTemplate::new(500).set_body_string(
+                r#"{"RemoteException":{"exception":"IOException","javaClassName":"java.io.IOException","message":"No space left on device"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
 
-        match resp.status() {
-            StatusCode::OK => {
-                resp.into_body().consume().await?;
-                Ok(RpDelete::default())
-            }
-            _ => Err(parse_error(resp).await?),
-        }
+        let mut builder = WebhdfsBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        let op = Operator::new(builder)?.finish();
+
+        let err = op
+            .write("file", "Hello, World!")
+            .await
+            .expect_err("write must fail");
+        assert!(err.is_temporary());
+        assert!(err
+            .to_string()
+            .contains("remote_error: IOException: No space left on device"));
+
+        Ok(())
     }
 
-    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
-        if args.recursive() {
-            return Err(Error::new(
-                ErrorKind::Unsupported,
-                "WebHDFS doesn't support list with recursive",
-            ));
-        }
+    #[tokio::test]
+    async fn test_write_with_manual_redirect() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CREATE"))
+            .and(query_param("noredirect", "true"))
+            .and(header("x-client", "custom"))
+            .and(body_string(""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "Location": format!("{}/datanode/file", mock_server.uri()),
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datanode/file"))
+            .and(header("x-client", "custom"))
+            .and(body_string("Hello, World!"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
 
-        let path = path.trim_end_matches('/');
+        // Both requests must be sent by the client set by users.
+        let mut headers = HeaderMap::new();
+        headers.insert("x-client", http::HeaderValue::from_static("custom"));
+        let client = HttpClient::build(reqwest::ClientBuilder::new().default_headers(headers))?;
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.manual_redirect().http_client(client);
+        let op = Operator::new(builder)?.finish();
 
-        if !self.disable_list_batch {
-            let req = self.webhdfs_list_status_batch_request(path, &OpList::default())?;
-            let resp = self.client.send(req).await?;
-            match resp.status() {
-                StatusCode::OK => {
-                    let bs = resp.into_body().bytes().await?;
-                    let directory_listing = serde_json::from_slice::<DirectoryListingWrapper>(&bs)
-                        .map_err(new_json_deserialize_error)?
-                        .directory_listing;
-                    let file_statuses = directory_listing.partial_listing.file_statuses.file_status;
-                    let mut objects = WebhdfsPager::new(self.clone(), path, file_statuses);
-                    objects.set_remaining_entries(directory_listing.remaining_entries);
-                    Ok((RpList::default(), objects))
-                }
-                StatusCode::NOT_FOUND => {
-                    let objects = WebhdfsPager::new(self.clone(), path, vec![]);
-                    Ok((RpList::default(), objects))
-                }
-                _ => Err(parse_error(resp).await?),
-            }
-        } else {
-            let req = self.webhdfs_list_status_request(path)?;
-            let resp = self.client.send(req).await?;
-            match resp.status() {
-                StatusCode::OK => {
-                    let bs = resp.into_body().bytes().await?;
-                    let file_statuses = serde_json::from_slice::<FileStatusesWrapper>(&bs)
-                        .map_err(new_json_deserialize_error)?
-                        .file_statuses
-                        .file_status;
-                    let objects = WebhdfsPager::new(self.clone(), path, file_statuses);
-                    Ok((RpList::default(), objects))
-                }
-                StatusCode::NOT_FOUND => {
-                    let objects = WebhdfsPager::new(self.clone(), path, vec![]);
-                    Ok((RpList::default(), objects))
+        op.write("file", "Hello, World!").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append_with_manual_redirect() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "APPEND"))
+            .and(query_param("noredirect", "true"))
+            .and(body_string(""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "Location": format!("{}/datanode/file", mock_server.uri()),
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datanode/file"))
+            .and(body_string("Hello, World!"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.manual_redirect();
+        let backend = builder.build()?;
+
+        backend.append("file", Bytes::from("Hello, World!")).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_single_hop_create() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CREATE"))
+            .and(query_param("data", "true"))
+            .and(body_string("Hello, World!"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.single_hop_create();
+        let op = Operator::new(builder)?.finish();
+
+        op.write("file", "Hello, World!").await?;
+
+        let requests = mock_server
+            .received_requests()
+            .await
+            .expect("request recording must be enabled");
+        assert_eq!(requests.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_created_location() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CREATE"))
+            .and(query_param("noredirect", "true"))
+            .and(body_string(""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "Location": format!("{}/datanode/file", mock_server.uri()),
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datanode/file"))
+            .and(body_string("Hello, World!"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .insert_header("Location", "webhdfs://namenode:9870/file"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = new_test_builder(&mock_server.uri());
+        builder.manual_redirect();
+        let op = Operator::new(builder)?.finish();
+
+        // The `Location` of the created file is only logged for debugging.
+        op.write("file", "Hello, World!").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_proxy() -> Result<()> {
+        // The mock server acts as the proxy, requests to namenode and
+        // datanode are told apart by their `Host` header.
+        let proxy = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CREATE"))
+            .and(header("host", "namenode.example:9870"))
+            .and(query_param("noredirect", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "Location": "http://datanode.example:9864/webhdfs/v1/file?op=CREATE",
+            })))
+            .expect(1)
+            .mount(&proxy)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "CREATE"))
+            .and(header("host", "datanode.example:9864"))
+            .and(body_string("Hello, World!"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&proxy)
+            .await;
+
+        let mut builder = WebhdfsBuilder::default();
+        builder
+            .endpoint("http://namenode.example:9870")
+            .proxy(&proxy.uri())
+            .manual_redirect();
+        let op = Operator::new(builder)?.finish();
+
+        op.write("file", "Hello, World!").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_chunked() -> Result<()> {
+        // wiremock always sets `Content-Length`, so we serve a chunked
+        // response via a raw tcp listener instead.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let endpoint = format!("http://{}", listener.local_addr()?);
+        let server = std::thread::spawn(move || -> std::io::Result<()> {
+            let (mut stream, _) = listener.accept()?;
+
+            // Consume the request head, the request has no body.
+            let mut head = Vec::new();
+            let mut buf = [0; 1024];
+            while !head.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf)?;
+                if n == 0 {
+                    break;
                 }
-                _ => Err(parse_error(resp).await?),
+                head.extend_from_slice(&buf[..n]);
             }
-        }
+
+            stream.write_all(
+                b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+                  7\r\nHello, \r\n6\r\nWorld!\r\n0\r\n\r\n",
+            )
+        });
+
+        let backend = new_test_backend(&endpoint)?;
+        let (rp, body) = backend.read("file", OpRead::new()).await?;
+        assert_eq!(rp.size(), None);
+        assert_eq!(body.bytes().await?, "Hello, World!");
+
+        server.join().expect("server must not panic")?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_http_client() -> Result<()> {
+        let mock_server = MockServer::start().await;
+        mount_root(&mock_server).await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/file"))
+            .and(query_param("op", "GETFILESTATUS"))
+            .and(header("x-opendal-test", "pooled"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FILE_STATUS))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-opendal-test", "pooled".parse()?);
+        let client = HttpClient::build(
+            reqwest::ClientBuilder::new()
+                .default_headers(headers)
+                .pool_max_idle_per_host(8)
+                .pool_idle_timeout(Duration::from_secs(30)),
+        )?;
+
+        let mut builder = WebhdfsBuilder::default();
+        builder.endpoint(&mock_server.uri()).http_client(client);
+        let op = Operator::new(builder)?.finish();
+
+        let meta = op.stat("file").await?;
+        assert_eq!(meta.content_length(), 24930);
+
+        Ok(())
     }
 }
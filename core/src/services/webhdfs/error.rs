@@ -46,6 +46,61 @@ pub(super) async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Err
     parse_error_msg(parts, &s)
 }
 
+/// Parse the error returned while writing data.
+///
+/// Datanode's error like `No space left on device` will be carried in the
+/// context so that users could know why the write failed.
+pub(super) async fn parse_write_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    let (parts, body) = resp.into_parts();
+    let bs = body.bytes().await?;
+    let s = String::from_utf8_lossy(&bs);
+
+    let remote_error = match serde_json::from_str::<WebHdfsErrorWrapper>(&s) {
+        Ok(wh_error) => format!(
+            "{}: {}",
+            wh_error.remote_exception.exception, wh_error.remote_exception.message
+        ),
+        Err(_) => s.trim().to_string(),
+    };
+
+    Ok(parse_error_msg(parts, &s)?.with_context("remote_error", remote_error))
+}
+
+/// Check whether the error is returned because namenode is in safe mode.
+///
+/// Namenode could return `SafeModeException` directly, or wrap it in a
+/// `RetriableException` while it's starting up.
+pub(super) fn is_safe_mode_error(body: &str) -> bool {
+    match serde_json::from_str::<WebHdfsErrorWrapper>(body) {
+        Ok(wh_error) => {
+            let e = wh_error.remote_exception;
+            e.exception == "SafeModeException"
+                || (e.exception == "RetriableException" && e.message.contains("safe mode"))
+        }
+        Err(_) => false,
+    }
+}
+
+/// Check whether the error is returned because the file is open for write
+/// by another client, which happens while appending to a file under
+/// construction. The lease will be released after the other client closes
+/// the file or the lease expires.
+pub(super) fn is_being_created_error(body: &str) -> bool {
+    match serde_json::from_str::<WebHdfsErrorWrapper>(body) {
+        Ok(wh_error) => wh_error.remote_exception.exception == "AlreadyBeingCreatedException",
+        Err(_) => false,
+    }
+}
+
+/// Check whether the error is returned because the `op` is unknown to
+/// namenode, which means the cluster is too old to support it.
+///
+/// For example, `TRUNCATE` is only available since HDFS 2.7:
+/// `Invalid value for webhdfs parameter "op": No enum constant ...PostOpParam.Op.TRUNCATE`
+fn is_unsupported_op_error(e: &WebHdfsError) -> bool {
+    e.exception == "IllegalArgumentException" && e.message.contains("parameter \"op\"")
+}
+
 pub(super) fn parse_error_msg(parts: Parts, body: &str) -> Result<Error> {
     let (kind, retryable) = match parts.status {
         StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
@@ -60,9 +115,66 @@ pub(super) fn parse_error_msg(parts: Parts, body: &str) -> Result<Error> {
         _ => (ErrorKind::Unexpected, false),
     };
 
-    let message = match serde_json::from_str::<WebHdfsErrorWrapper>(body) {
-        Ok(wh_error) => format!("{:?}", wh_error.remote_exception),
-        Err(_) => body.to_owned(),
+    let (kind, message) = match serde_json::from_str::<WebHdfsErrorWrapper>(body) {
+        Ok(wh_error) => {
+            // HDFS returns `403` for all `IOException`, we need to check the
+            // exception to know what happened.
+            let e = &wh_error.remote_exception;
+            let unsupported_op = is_unsupported_op_error(e);
+            let kind = match e.exception.as_str() {
+                _ if unsupported_op => ErrorKind::Unsupported,
+                "FileAlreadyExistsException" => ErrorKind::AlreadyExists,
+                "AccessControlException" => ErrorKind::PermissionDenied,
+                // The file is leased by another writer, which is temporary
+                // instead of a permission issue.
+                "AlreadyBeingCreatedException" => ErrorKind::Unexpected,
+                // `SETXATTR` with a flag that doesn't match the xattr's state.
+                "IOException" if e.message.starts_with("XAttr: ") => {
+                    if e.message.contains("already exists") {
+                        ErrorKind::AlreadyExists
+                    } else if e.message.contains("does not exist") {
+                        ErrorKind::NotFound
+                    } else {
+                        kind
+                    }
+                }
+                // Snapshot operations on a dir that isn't snapshottable or
+                // with a snapshot that doesn't exist.
+                "SnapshotException" => {
+                    if e.message.contains("not a snapshottable directory") {
+                        ErrorKind::Unsupported
+                    } else if e.message.contains("already exists") {
+                        ErrorKind::AlreadyExists
+                    } else {
+                        ErrorKind::NotFound
+                    }
+                }
+                _ => kind,
+            };
+            let message = if unsupported_op {
+                format!(
+                    "operation is not supported by this HDFS version, please upgrade the cluster: {:?}",
+                    wh_error.remote_exception
+                )
+            } else {
+                format!("{:?}", wh_error.remote_exception)
+            };
+            (kind, message)
+        }
+        Err(_) => (kind, body.to_owned()),
+    };
+
+    // Namenode will leave safe mode later, so it's retryable.
+    let (retryable, message) = if is_safe_mode_error(body) {
+        (true, format!("cluster in safe mode: {message}"))
+    } else if is_being_created_error(body) {
+        // The other writer will close the file later, so it's retryable.
+        (
+            true,
+            format!("file is being written by another client: {message}"),
+        )
+    } else {
+        (retryable, message)
     };
 
     let mut err = Error::new(kind, &message);
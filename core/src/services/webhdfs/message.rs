@@ -24,6 +24,12 @@ pub(super) struct BooleanResp {
     pub boolean: bool,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(super) struct LocationResp {
+    pub location: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub(super) struct FileStatusWrapper {
@@ -61,29 +67,259 @@ pub(super) struct FileStatuses {
     pub file_status: Vec<FileStatus>,
 }
 
+/// FileStatus is the status of a file or directory returned by
+/// `GETFILESTATUS` and `LISTSTATUS`.
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FileStatus {
+    /// Length of the file in bytes, `0` for directories.
     pub length: u64,
+    /// Modification time in milliseconds since the epoch.
     pub modification_time: i64,
 
+    /// The name relative to the listed dir, empty for `GETFILESTATUS`.
     pub path_suffix: String,
+    /// The type of this entry.
     #[serde(rename = "type")]
     pub ty: FileStatusType,
+    /// Block size of the file, `0` for directories.
+    #[serde(default)]
+    pub block_size: u64,
+
+    /// The owner of this entry.
+    #[serde(default)]
+    pub owner: String,
+    /// The group of this entry.
+    #[serde(default)]
+    pub group: String,
+    /// The permission in octal, like `755`.
+    #[serde(default)]
+    pub permission: String,
+    /// The target of the symlink, only returned for `SYMLINK`.
+    #[serde(default)]
+    pub symlink: Option<String>,
+    /// The id of the storage policy, `0` means it's not specified and
+    /// inherited from the parent.
+    #[serde(default)]
+    pub storage_policy: u8,
+}
+
+impl FileStatus {
+    /// The name of the storage policy if it's specified.
+    ///
+    /// Built-in policies are mapped to their names, the id will be returned
+    /// for others which could be looked up via `GETALLSTORAGEPOLICIES`.
+    pub fn storage_policy_name(&self) -> Option<String> {
+        let name = match self.storage_policy {
+            0 => return None,
+            1 => "PROVIDED",
+            2 => "COLD",
+            5 => "WARM",
+            7 => "HOT",
+            10 => "ONE_SSD",
+            12 => "ALL_SSD",
+            15 => "LAZY_PERSIST",
+            id => return Some(id.to_string()),
+        };
+        Some(name.to_string())
+    }
 }
 
+/// FileStatusType is the type of entry in [`FileStatus`].
 #[derive(Debug, Default, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum FileStatusType {
+    /// The entry is a directory.
     Directory,
+    /// The entry is a file.
     #[default]
     File,
+    /// The entry is a symlink.
+    Symlink,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(super) struct SnapshottableDirectoryListWrapper {
+    /// HDFS returns `null` if there is no snapshottable directory.
+    pub snapshottable_directory_list: Option<Vec<SnapshottableDirectoryStatus>>,
+}
+
+/// SnapshottableDirectoryStatus is the status of a snapshottable directory
+/// returned by `GETSNAPSHOTTABLEDIRECTORYLIST`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshottableDirectoryStatus {
+    /// The status of this directory, carries `owner`, `group` and `permission`.
+    pub dir_status: FileStatus,
+    /// The full path of this directory's parent.
+    pub parent_full_path: String,
+    /// The number of snapshots taken on this directory.
+    pub snapshot_number: u32,
+    /// The max number of snapshots allowed on this directory.
+    pub snapshot_quota: u32,
+}
+
+impl SnapshottableDirectoryStatus {
+    /// The full path of this snapshottable directory.
+    pub fn path(&self) -> String {
+        format!(
+            "{}/{}",
+            self.parent_full_path.trim_end_matches('/'),
+            self.dir_status.path_suffix
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(super) struct BlockStoragePoliciesWrapper {
+    pub block_storage_policies: BlockStoragePolicies,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(super) struct BlockStoragePolicies {
+    pub block_storage_policy: Vec<BlockStoragePolicy>,
+}
+
+/// BlockStoragePolicy is a storage policy of the cluster returned by
+/// `GETALLSTORAGEPOLICIES`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockStoragePolicy {
+    /// The id of this policy.
+    pub id: u8,
+    /// The name of this policy, like `HOT`, `COLD` and `ALL_SSD`.
+    pub name: String,
+    /// The storage types used by replicas, like `DISK` and `ARCHIVE`.
+    pub storage_types: Vec<String>,
+    /// The fallback storage types used while creating files.
+    #[serde(default)]
+    pub creation_fallbacks: Vec<String>,
+    /// The fallback storage types used while replicating.
+    #[serde(default)]
+    pub replication_fallbacks: Vec<String>,
+    /// Whether new files should be copied on create.
+    #[serde(default)]
+    pub copy_on_create_file: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(super) struct ServerDefaultsWrapper {
+    pub fs_server_defaults: ServerDefaults,
+}
+
+/// ServerDefaults is the default values of the cluster returned by
+/// `GETSERVERDEFAULTS`, which could be used to tune writes.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ServerDefaults {
+    /// The default block size in bytes.
+    pub block_size: u64,
+    /// The default bytes per checksum.
+    pub bytes_per_checksum: u32,
+    /// The default write packet size in bytes.
+    pub write_packet_size: u32,
+    /// The default replication factor.
+    pub replication: u16,
+    /// The default buffer size used while reading and writing files.
+    pub file_buffer_size: u32,
+    /// Whether data transfer is encrypted.
+    pub encrypt_data_transfer: bool,
+    /// The interval in minutes to checkpoint trash, `0` means trash is disabled.
+    pub trash_interval: u64,
+    /// The id of the checksum type, like `2` for `CRC32C`.
+    pub checksum_type: u8,
+    /// The uri of the key provider for transparent encryption.
+    pub key_provider_uri: String,
+    /// The id of the default storage policy.
+    pub default_storage_policy_id: u8,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(super) struct XAttrsWrapper {
+    #[serde(rename = "XAttrs")]
+    pub xattrs: Vec<XAttr>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct XAttr {
+    pub name: String,
+    /// HDFS returns `null` if the xattr doesn't have a value.
+    pub value: Option<String>,
+}
+
+/// XAttrValueEncoding is the encoding of xattr values returned by `GETXATTRS`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum XAttrValueEncoding {
+    /// Values are returned as quoted strings, only suitable for text values.
+    #[default]
+    Text,
+    /// Values are returned as hex strings prefixed with `0x`.
+    Hex,
+    /// Values are returned as base64 strings prefixed with `0s`.
+    Base64,
+}
+
+impl XAttrValueEncoding {
+    pub(super) fn as_str(&self) -> &'static str {
+        match self {
+            XAttrValueEncoding::Text => "text",
+            XAttrValueEncoding::Hex => "hex",
+            XAttrValueEncoding::Base64 => "base64",
+        }
+    }
+}
+
+/// XAttrSetFlag decides how `SETXATTR` treats the existing xattr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XAttrSetFlag {
+    /// Create a new xattr, fails with `AlreadyExists` if it exists.
+    Create,
+    /// Replace an existing xattr, fails with `NotFound` if it doesn't exist.
+    Replace,
+}
+
+impl XAttrSetFlag {
+    pub(super) fn as_str(&self) -> &'static str {
+        match self {
+            XAttrSetFlag::Create => "CREATE",
+            XAttrSetFlag::Replace => "REPLACE",
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_xattrs() {
+        let json = r#"
+{
+  "XAttrs": [
+    {
+      "name": "user.a1",
+      "value": "0x313233"
+    },
+    {
+      "name": "user.a2",
+      "value": null
+    }
+  ]
+}
+"#;
+        let xattrs: XAttrsWrapper = serde_json::from_str(json).expect("must success");
+        assert_eq!(xattrs.xattrs.len(), 2);
+        assert_eq!(xattrs.xattrs[0].name, "user.a1");
+        assert_eq!(xattrs.xattrs[0].value.as_deref(), Some("0x313233"));
+        assert_eq!(xattrs.xattrs[1].name, "user.a2");
+        assert_eq!(xattrs.xattrs[1].value, None);
+    }
+
     #[test]
     fn test_file_status() {
         let json = r#"
@@ -110,6 +346,143 @@ mod test {
         assert_eq!(status.file_status.ty, FileStatusType::Directory);
     }
 
+    #[test]
+    fn test_file_status_with_storage_policy() {
+        let json = r#"
+{
+  "FileStatus":
+  {
+    "accessTime"      : 1320171722771,
+    "blockSize"       : 33554432,
+    "group"           : "supergroup",
+    "length"          : 24930,
+    "modificationTime": 1320171722771,
+    "owner"           : "webuser",
+    "pathSuffix"      : "",
+    "permission"      : "644",
+    "replication"     : 1,
+    "storagePolicy"   : 2,
+    "type"            : "FILE"
+  }
+}
+"#;
+        let status: FileStatusWrapper = serde_json::from_str(json).expect("must success");
+        assert_eq!(status.file_status.storage_policy, 2);
+        assert_eq!(
+            status.file_status.storage_policy_name().as_deref(),
+            Some("COLD")
+        );
+
+        // Policies that are not specified are inherited from parents.
+        let status = FileStatus::default();
+        assert_eq!(status.storage_policy_name(), None);
+    }
+
+    #[test]
+    fn test_snapshottable_directory_list() {
+        let json = r#"
+{
+  "SnapshottableDirectoryList":
+  [
+    {
+      "dirStatus":
+      {
+        "accessTime":0,
+        "blockSize":0,
+        "childrenNum":0,
+        "fileId":16386,
+        "group":"hadoop",
+        "length":0,
+        "modificationTime":1520761889225,
+        "owner":"random",
+        "pathSuffix":"bar",
+        "permission":"755",
+        "replication":0,
+        "storagePolicy":0,
+        "type":"DIRECTORY"
+      },
+      "parentFullPath":"/",
+      "snapshotNumber":1,
+      "snapshotQuota":65536
+    }
+  ]
+}
+"#;
+        let dirs = serde_json::from_str::<SnapshottableDirectoryListWrapper>(json)
+            .expect("must success")
+            .snapshottable_directory_list
+            .unwrap_or_default();
+
+        assert_eq!(dirs.len(), 1);
+        assert_eq!(dirs[0].path(), "/bar");
+        assert_eq!(dirs[0].dir_status.owner, "random");
+        assert_eq!(dirs[0].dir_status.group, "hadoop");
+        assert_eq!(dirs[0].dir_status.permission, "755");
+        assert_eq!(dirs[0].dir_status.ty, FileStatusType::Directory);
+        assert_eq!(dirs[0].snapshot_number, 1);
+        assert_eq!(dirs[0].snapshot_quota, 65536);
+    }
+
+    #[test]
+    fn test_block_storage_policies() {
+        let json = r#"
+{
+  "BlockStoragePolicies": {
+    "BlockStoragePolicy": [
+      {
+        "copyOnCreateFile": false,
+        "creationFallbacks": [],
+        "id": 2,
+        "name": "COLD",
+        "replicationFallbacks": [],
+        "storageTypes": ["ARCHIVE"]
+      },
+      {
+        "copyOnCreateFile": false,
+        "creationFallbacks": ["DISK", "ARCHIVE"],
+        "id": 5,
+        "name": "WARM",
+        "replicationFallbacks": ["DISK", "ARCHIVE"],
+        "storageTypes": ["DISK", "ARCHIVE"]
+      },
+      {
+        "copyOnCreateFile": false,
+        "creationFallbacks": [],
+        "id": 7,
+        "name": "HOT",
+        "replicationFallbacks": ["ARCHIVE"],
+        "storageTypes": ["DISK"]
+      },
+      {
+        "copyOnCreateFile": true,
+        "creationFallbacks": ["DISK"],
+        "id": 15,
+        "name": "LAZY_PERSIST",
+        "replicationFallbacks": ["DISK"],
+        "storageTypes": ["RAM_DISK", "DISK"]
+      }
+    ]
+  }
+}
+"#;
+        let policies = serde_json::from_str::<BlockStoragePoliciesWrapper>(json)
+            .expect("must success")
+            .block_storage_policies
+            .block_storage_policy;
+
+        assert_eq!(policies.len(), 4);
+        assert_eq!(policies[0].id, 2);
+        assert_eq!(policies[0].name, "COLD");
+        assert_eq!(policies[0].storage_types, vec!["ARCHIVE"]);
+        assert!(policies[0].creation_fallbacks.is_empty());
+        assert_eq!(policies[1].name, "WARM");
+        assert_eq!(policies[1].creation_fallbacks, vec!["DISK", "ARCHIVE"]);
+        assert_eq!(policies[2].replication_fallbacks, vec!["ARCHIVE"]);
+        assert_eq!(policies[3].name, "LAZY_PERSIST");
+        assert_eq!(policies[3].storage_types, vec!["RAM_DISK", "DISK"]);
+        assert!(policies[3].copy_on_create_file);
+    }
+
     #[tokio::test]
     async fn test_list_empty() {
         let json = r#"
@@ -246,4 +619,37 @@ mod test {
             "bazfile"
         );
     }
+    #[test]
+    fn test_server_defaults() {
+        let json = r#"
+{
+  "FsServerDefaults": {
+    "replication": 3,
+    "encryptDataTransfer": false,
+    "defaultStoragePolicyId": 7,
+    "writePacketSize": 65536,
+    "fileBufferSize": 4096,
+    "checksumType": 2,
+    "trashInterval": 10080,
+    "keyProviderUri": "",
+    "blockSize": 134217728,
+    "bytesPerChecksum": 512
+  }
+}
+"#;
+        let defaults = serde_json::from_str::<ServerDefaultsWrapper>(json)
+            .expect("must success")
+            .fs_server_defaults;
+
+        assert_eq!(defaults.block_size, 134217728);
+        assert_eq!(defaults.replication, 3);
+        assert_eq!(defaults.bytes_per_checksum, 512);
+        assert_eq!(defaults.write_packet_size, 65536);
+        assert_eq!(defaults.file_buffer_size, 4096);
+        assert!(!defaults.encrypt_data_transfer);
+        assert_eq!(defaults.trash_interval, 10080);
+        assert_eq!(defaults.checksum_type, 2);
+        assert_eq!(defaults.key_provider_uri, "");
+        assert_eq!(defaults.default_storage_policy_id, 7);
+    }
 }
@@ -16,9 +16,19 @@
 // under the License.
 
 mod backend;
+pub use backend::WebhdfsBackend;
 pub use backend::WebhdfsBuilder as Webhdfs;
 
 mod error;
 mod message;
+pub use message::BlockStoragePolicy;
+pub use message::FileStatus;
+pub use message::FileStatusType;
+pub use message::ServerDefaults;
+pub use message::SnapshottableDirectoryStatus;
+pub use message::XAttrSetFlag;
+pub use message::XAttrValueEncoding;
 mod pager;
+pub use pager::WebhdfsWalkEntry;
+pub use pager::WebhdfsWalker;
 mod writer;
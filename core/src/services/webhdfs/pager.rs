@@ -15,6 +15,9 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
 use async_trait::async_trait;
 use http::StatusCode;
 
@@ -23,6 +26,7 @@ use super::error::parse_error;
 use super::message::DirectoryListingWrapper;
 use super::message::FileStatus;
 use super::message::FileStatusType;
+use crate::raw::oio::Page as _;
 use crate::raw::*;
 use crate::*;
 
@@ -32,6 +36,10 @@ pub struct WebhdfsPager {
     statuses: Vec<FileStatus>,
     batch_start_after: Option<String>,
     remaining_entries: u32,
+    /// Only entries whose name starts with it will be returned.
+    name_prefix: Option<String>,
+    /// Names of directories listed so far, used to skip placeholders.
+    dir_names: HashSet<String>,
 }
 
 impl WebhdfsPager {
@@ -42,9 +50,20 @@ impl WebhdfsPager {
             batch_start_after: statuses.last().map(|f| f.path_suffix.clone()),
             statuses,
             remaining_entries: 0,
+            name_prefix: None,
+            dir_names: HashSet::new(),
         }
     }
 
+    /// Filter the entries by name prefix while listing.
+    ///
+    /// HDFS doesn't support filtering natively, so we filter every batch as
+    /// it's returned to avoid holding the whole directory in memory.
+    pub(super) fn with_name_prefix(mut self, name_prefix: Option<&str>) -> Self {
+        self.name_prefix = name_prefix.map(String::from);
+        self
+    }
+
     pub(super) fn set_remaining_entries(&mut self, remaining_entries: u32) {
         self.remaining_entries = remaining_entries;
     }
@@ -56,45 +75,51 @@ impl oio::Page for WebhdfsPager {
     ///
     /// Note: default list status with batch, calling next will query for next batch if `remaining_entries` > 0.
     async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
-        if self.statuses.is_empty() && self.remaining_entries == 0 {
+        // Return buffered statuses first, the next batch is only needed
+        // after they are consumed.
+        if !self.statuses.is_empty() {
+            return self.webhdfs_get_next_list_statuses();
+        }
+        if self.remaining_entries == 0 || self.backend.disable_list_batch {
             return Ok(None);
         }
+        // Batches could be of any size, stop if the previous one is empty
+        // since there is nothing to continue after.
+        let Some(start_after) = self.batch_start_after.clone() else {
+            self.remaining_entries = 0;
+            return Ok(None);
+        };
+
+        let args = OpList::with_start_after(OpList::default(), &start_after);
+        let req = self
+            .backend
+            .webhdfs_list_status_batch_request(&self.path, &args)?;
+        let resp = self.backend.send_idempotent(req).await?;
 
-        return match self.backend.disable_list_batch {
-            true => self.webhdfs_get_next_list_statuses(),
-            false => {
-                let args = OpList::with_start_after(
-                    OpList::default(),
-                    &self.batch_start_after.clone().unwrap(),
-                );
-                let req = self
-                    .backend
-                    .webhdfs_list_status_batch_request(&self.path, &args)?;
-                let resp = self.backend.client.send(req).await?;
-
-                match resp.status() {
-                    StatusCode::OK => {
-                        let bs = resp.into_body().bytes().await?;
-                        let directory_listing =
-                            serde_json::from_slice::<DirectoryListingWrapper>(&bs)
-                                .map_err(new_json_deserialize_error)?;
-                        let file_statuses = directory_listing
-                            .directory_listing
-                            .partial_listing
-                            .file_statuses
-                            .file_status;
-                        self.remaining_entries =
-                            directory_listing.directory_listing.remaining_entries;
-                        self.batch_start_after =
-                            file_statuses.last().map(|f| f.path_suffix.clone());
-                        self.statuses.extend(file_statuses);
-                        self.webhdfs_get_next_list_statuses()
-                    }
-                    StatusCode::NOT_FOUND => self.webhdfs_get_next_list_statuses(),
-                    _ => Err(parse_error(resp).await?),
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let directory_listing = serde_json::from_slice::<DirectoryListingWrapper>(&bs)
+                    .map_err(new_json_deserialize_error)?;
+                let file_statuses = directory_listing
+                    .directory_listing
+                    .partial_listing
+                    .file_statuses
+                    .file_status;
+                self.remaining_entries = directory_listing.directory_listing.remaining_entries;
+                self.batch_start_after = file_statuses.last().map(|f| f.path_suffix.clone());
+                if file_statuses.is_empty() {
+                    self.remaining_entries = 0;
                 }
+                self.statuses.extend(file_statuses);
+                self.webhdfs_get_next_list_statuses()
             }
-        };
+            StatusCode::NOT_FOUND => {
+                self.remaining_entries = 0;
+                Ok(None)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
     }
 }
 
@@ -103,21 +128,53 @@ impl WebhdfsPager {
     fn webhdfs_get_next_list_statuses(&mut self) -> Result<Option<Vec<oio::Entry>>> {
         let mut entries = Vec::with_capacity(self.statuses.len());
 
+        if self.backend.skip_dir_placeholder {
+            self.dir_names.extend(
+                self.statuses
+                    .iter()
+                    .filter(|status| status.ty == FileStatusType::Directory)
+                    .map(|status| status.path_suffix.clone()),
+            );
+        }
+        // Statuses are sorted by name, so the largest one is the last.
+        let last_name = self.statuses.last().map(|s| s.path_suffix.clone());
+
         while let Some(status) = self.statuses.pop() {
+            if let Some(prefix) = &self.name_prefix {
+                if !status.path_suffix.starts_with(prefix.as_str()) {
+                    continue;
+                }
+            }
+
+            if self.backend.skip_dir_placeholder
+                && status.ty == FileStatusType::File
+                && status.length == 0
+                && self
+                    .dir_names
+                    .contains(status.path_suffix.trim_end_matches('/'))
+            {
+                continue;
+            }
+
             let mut path = if self.path.is_empty() {
                 status.path_suffix.to_string()
             } else {
                 format!("{}/{}", self.path, status.path_suffix)
             };
 
-            let meta = match status.ty {
+            let mut meta = match status.ty {
                 FileStatusType::Directory => Metadata::new(EntryMode::DIR),
                 FileStatusType::File => Metadata::new(EntryMode::FILE)
                     .with_content_length(status.length)
                     .with_last_modified(parse_datetime_from_from_timestamp_millis(
                         status.modification_time,
                     )?),
+                // GETFILESTATUS follows symlinks, but LISTSTATUS could return them.
+                FileStatusType::Symlink => Metadata::new(EntryMode::Unknown),
             };
+            if let Some(storage_policy) = status.storage_policy_name() {
+                meta.set_storage_class(&storage_policy);
+            }
 
             if meta.mode().is_file() {
                 path = path.trim_end_matches('/').to_string();
@@ -128,6 +185,84 @@ impl WebhdfsPager {
             let entry = oio::Entry::new(&path, meta);
             entries.push(entry);
         }
+
+        // Placeholder of dir `a` is named `a/` which sorts after `a`, so only
+        // dirs whose placeholder could be in the following batches are kept
+        // to avoid growing without bound on huge directories.
+        if let Some(last_name) = last_name {
+            self.dir_names
+                .retain(|name| format!("{name}/").as_str() > last_name.as_str());
+        }
+
         Ok(Some(entries))
     }
 }
+
+/// Entry returned by [`WebhdfsWalker`] with its position in the tree.
+#[derive(Debug, Clone)]
+pub struct WebhdfsWalkEntry {
+    /// The listed entry.
+    pub entry: Entry,
+    /// Depth of the entry, children of the walked dir are at depth 1.
+    pub depth: usize,
+    /// Path of the dir containing the entry, ends with `/`.
+    pub parent: String,
+}
+
+/// WebhdfsWalker lists dirs recursively in a top-down way and annotates every
+/// entry with its depth and parent.
+///
+/// All entries of a dir are returned before the entries of its sub dirs.
+pub struct WebhdfsWalker {
+    backend: WebhdfsBackend,
+    /// Dirs to be listed with their depth.
+    dirs: VecDeque<(String, usize)>,
+    /// Pager of the dir being listed with the dir's path and the depth of its
+    /// children.
+    pager: Option<(WebhdfsPager, String, usize)>,
+}
+
+impl WebhdfsWalker {
+    pub(super) fn new(backend: WebhdfsBackend, path: &str) -> Self {
+        Self {
+            backend,
+            dirs: VecDeque::from([(path.to_string(), 0)]),
+            pager: None,
+        }
+    }
+
+    /// Returns the next page of entries, `None` if all dirs have been walked.
+    pub async fn next(&mut self) -> Result<Option<Vec<WebhdfsWalkEntry>>> {
+        loop {
+            if self.pager.is_none() {
+                let Some((path, depth)) = self.dirs.pop_front() else {
+                    return Ok(None);
+                };
+                let (_, pager) = self.backend.list(&path, OpList::new()).await?;
+                self.pager = Some((pager, path, depth + 1));
+            }
+
+            let (pager, parent, depth) = self.pager.as_mut().expect("pager must be set");
+            let Some(entries) = pager.next().await? else {
+                self.pager = None;
+                continue;
+            };
+            if entries.is_empty() {
+                continue;
+            }
+
+            let mut walked = Vec::with_capacity(entries.len());
+            for oe in entries {
+                if oe.mode().is_dir() {
+                    self.dirs.push_back((oe.path().to_string(), *depth));
+                }
+                walked.push(WebhdfsWalkEntry {
+                    entry: oe.into_entry(),
+                    depth: *depth,
+                    parent: parent.clone(),
+                });
+            }
+            return Ok(Some(walked));
+        }
+    }
+}
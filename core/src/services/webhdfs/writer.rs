@@ -17,9 +17,13 @@
 
 use async_trait::async_trait;
 use http::StatusCode;
+use log::debug;
+use log::warn;
 
 use super::backend::WebhdfsBackend;
-use super::error::parse_error;
+use super::backend::WEBHDFS_CONTENT_TYPE_XATTR;
+use super::error::parse_write_error;
+use super::message::XAttrSetFlag;
 use crate::raw::oio::WriteBuf;
 use crate::raw::*;
 use crate::*;
@@ -43,22 +47,63 @@ impl oio::OneShotWrite for WebhdfsWriter {
     async fn write_once(&self, bs: &dyn WriteBuf) -> Result<()> {
         let bs = bs.bytes(bs.remaining());
 
-        let req = self.backend.webhdfs_create_object_request(
-            &self.path,
-            Some(bs.len()),
-            &self.op,
-            AsyncBody::Bytes(bs),
-        )?;
+        let (datanode, resp) = if self.backend.manual_redirect && !self.backend.single_hop_create {
+            let (datanode, resp) = self
+                .backend
+                .webhdfs_create_object_with_manual_redirect(&self.path, &self.op, bs)
+                .await?;
+            (Some(datanode), resp)
+        } else {
+            let req = self.backend.webhdfs_create_object_request(
+                &self.path,
+                Some(bs.len()),
+                &self.op,
+                AsyncBody::Bytes(bs),
+            )?;
 
-        let resp = self.backend.client.send(req).await?;
+            (None, self.backend.send_idempotent(req).await?)
+        };
+        self.backend.invalidate_cached_stat(&self.path);
 
         let status = resp.status();
+        let location = parse_location(resp.headers())?.map(String::from);
+        debug!(
+            "webhdfs created {} with status {status}, location {location:?}, datanode {datanode:?}",
+            self.path
+        );
+
         match status {
             StatusCode::CREATED | StatusCode::OK => {
                 resp.into_body().consume().await?;
-                Ok(())
             }
-            _ => Err(parse_error(resp).await?),
+            _ => return Err(parse_write_error(resp).await?),
         }
+
+        if self.backend.guess_content_type {
+            let content_type = self
+                .op
+                .content_type()
+                .or_else(|| mime_guess::from_path(&self.path).first_raw());
+            if let Some(content_type) = content_type {
+                // The file is just created, so the xattr can't exist.
+                //
+                // The content type is best effort: the file has been written
+                // already, and xattrs could be disabled on the cluster.
+                if let Err(e) = self
+                    .backend
+                    .set_xattr(
+                        &self.path,
+                        WEBHDFS_CONTENT_TYPE_XATTR,
+                        content_type.as_bytes(),
+                        XAttrSetFlag::Create,
+                    )
+                    .await
+                {
+                    warn!("set content type of webhdfs file {} failed: {e}", self.path);
+                }
+            }
+        }
+
+        Ok(())
     }
 }
@@ -51,6 +51,8 @@ pub struct Capability {
     pub stat_with_if_match: bool,
     /// If operator supports stat with if none match.
     pub stat_with_if_none_match: bool,
+    /// If operator supports stat with version.
+    pub stat_with_version: bool,
 
     /// If operator supports read.
     pub read: bool,
@@ -64,12 +66,18 @@ pub struct Capability {
     pub read_with_if_match: bool,
     /// If operator supports read with if none match.
     pub read_with_if_none_match: bool,
+    /// If operator supports read with if modified since.
+    pub read_with_if_modified_since: bool,
     /// if operator supports read with override cache control.
     pub read_with_override_cache_control: bool,
     /// if operator supports read with override content disposition.
     pub read_with_override_content_disposition: bool,
     /// if operator supports read with override content type.
     pub read_with_override_content_type: bool,
+    /// If operator supports read with identity encoding.
+    pub read_with_identity_encoding: bool,
+    /// If operator supports read with version.
+    pub read_with_version: bool,
 
     /// If operator supports write.
     pub write: bool,
@@ -85,6 +93,8 @@ pub struct Capability {
     pub write_with_content_disposition: bool,
     /// If operator supports write with cache control.
     pub write_with_cache_control: bool,
+    /// If operator supports write with if match.
+    pub write_with_if_match: bool,
     /// write_multi_max_size is the max size that services support in write_multi.
     ///
     /// For example, AWS S3 supports 5GiB as max in write_multi.
@@ -110,9 +120,19 @@ pub struct Capability {
 
     /// If operator supports copy.
     pub copy: bool,
+    /// If operator supports copy with content type.
+    pub copy_with_content_type: bool,
+    /// If operator supports copy with cache control.
+    pub copy_with_cache_control: bool,
+    /// If operator supports copy with source version.
+    pub copy_with_source_version: bool,
+    /// If operator supports copy with metadata reset instead of preserved.
+    pub copy_with_reset_metadata: bool,
 
     /// If operator supports rename.
     pub rename: bool,
+    /// If operator supports rename without overwrite.
+    pub rename_without_overwrite: bool,
 
     /// If operator supports list.
     pub list: bool,
@@ -124,6 +144,12 @@ pub struct Capability {
     pub list_with_recursive: bool,
     /// If backend supports list without recursive.
     pub list_without_recursive: bool,
+    /// If backend supports list with soft deleted.
+    pub list_with_soft_deleted: bool,
+    /// If backend supports list with name prefix.
+    pub list_with_name_prefix: bool,
+    /// If backend supports list with entry mode.
+    pub list_with_entry_mode: bool,
 
     /// If operator supports presign.
     pub presign: bool,
@@ -40,11 +40,23 @@ pub struct Metadata {
     content_disposition: Option<String>,
     content_length: Option<u64>,
     content_md5: Option<String>,
+    content_crc32c: Option<String>,
     content_range: Option<BytesContentRange>,
     content_type: Option<String>,
     etag: Option<String>,
     last_modified: Option<DateTime<Utc>>,
     version: Option<String>,
+    /// Only a few services support soft delete, keep the times boxed so
+    /// that they don't grow every entry.
+    soft_delete: Option<Box<SoftDeleteTimes>>,
+    storage_class: Option<String>,
+}
+
+/// Times of a soft deleted entry.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+struct SoftDeleteTimes {
+    soft_delete_time: Option<DateTime<Utc>>,
+    hard_delete_time: Option<DateTime<Utc>>,
 }
 
 impl Metadata {
@@ -65,12 +77,15 @@ impl Metadata {
             cache_control: None,
             content_length: None,
             content_md5: None,
+            content_crc32c: None,
             content_type: None,
             content_range: None,
             last_modified: None,
             etag: None,
             content_disposition: None,
             version: None,
+            soft_delete: None,
+            storage_class: None,
         }
     }
 
@@ -239,6 +254,36 @@ impl Metadata {
         self
     }
 
+    /// Base64 encoded CRC32C of this entry, in big-endian byte order.
+    ///
+    /// # Panics
+    ///
+    /// This value is only available when calling on result of `read` with
+    /// [`Metakey::ContentCrc32c`], otherwise it will panic.
+    pub fn content_crc32c(&self) -> Option<&str> {
+        debug_assert!(
+            self.metakey.contains(Metakey::ContentCrc32c)
+                || self.metakey.contains(Metakey::Complete),
+            "visiting not set metadata: content_crc32c, maybe a bug"
+        );
+
+        self.content_crc32c.as_deref()
+    }
+
+    /// Set content CRC32C of this entry.
+    pub fn set_content_crc32c(&mut self, v: &str) -> &mut Self {
+        self.content_crc32c = Some(v.to_string());
+        self.metakey |= Metakey::ContentCrc32c;
+        self
+    }
+
+    /// Set content CRC32C of this entry.
+    pub fn with_content_crc32c(mut self, v: String) -> Self {
+        self.content_crc32c = Some(v);
+        self.metakey |= Metakey::ContentCrc32c;
+        self
+    }
+
     /// Content Type of this entry.
     ///
     /// Content Type is defined by [RFC 9110](https://httpwg.org/specs/rfc9110.html#field.content-type).
@@ -510,6 +555,117 @@ impl Metadata {
         self.metakey |= Metakey::Version;
         self
     }
+
+    /// Soft delete time of this entry.
+    ///
+    /// Soft delete time is the time when this entry was soft deleted, which is
+    /// reported by services that support soft delete like GCS.
+    ///
+    /// # Panics
+    ///
+    /// This value is only available when calling on result of `list` with
+    /// [`Metakey::SoftDeleteTime`], otherwise it will panic.
+    pub fn soft_delete_time(&self) -> Option<DateTime<Utc>> {
+        debug_assert!(
+            self.metakey.contains(Metakey::SoftDeleteTime)
+                || self.metakey.contains(Metakey::Complete),
+            "visiting not set metadata: soft_delete_time, maybe a bug"
+        );
+
+        self.soft_delete.as_ref().and_then(|v| v.soft_delete_time)
+    }
+
+    /// Set soft delete time of this entry.
+    pub fn with_soft_delete_time(mut self, v: DateTime<Utc>) -> Self {
+        self.soft_delete
+            .get_or_insert_with(Default::default)
+            .soft_delete_time = Some(v);
+        self.metakey |= Metakey::SoftDeleteTime;
+        self
+    }
+
+    /// Set soft delete time of this entry.
+    pub fn set_soft_delete_time(&mut self, v: DateTime<Utc>) -> &mut Self {
+        self.soft_delete
+            .get_or_insert_with(Default::default)
+            .soft_delete_time = Some(v);
+        self.metakey |= Metakey::SoftDeleteTime;
+        self
+    }
+
+    /// Hard delete time of this entry.
+    ///
+    /// Hard delete time is the time after which a soft deleted entry will be
+    /// permanently deleted and can't be restored anymore.
+    ///
+    /// # Panics
+    ///
+    /// This value is only available when calling on result of `list` with
+    /// [`Metakey::HardDeleteTime`], otherwise it will panic.
+    pub fn hard_delete_time(&self) -> Option<DateTime<Utc>> {
+        debug_assert!(
+            self.metakey.contains(Metakey::HardDeleteTime)
+                || self.metakey.contains(Metakey::Complete),
+            "visiting not set metadata: hard_delete_time, maybe a bug"
+        );
+
+        self.soft_delete.as_ref().and_then(|v| v.hard_delete_time)
+    }
+
+    /// Set hard delete time of this entry.
+    pub fn with_hard_delete_time(mut self, v: DateTime<Utc>) -> Self {
+        self.soft_delete
+            .get_or_insert_with(Default::default)
+            .hard_delete_time = Some(v);
+        self.metakey |= Metakey::HardDeleteTime;
+        self
+    }
+
+    /// Set hard delete time of this entry.
+    pub fn set_hard_delete_time(&mut self, v: DateTime<Utc>) -> &mut Self {
+        self.soft_delete
+            .get_or_insert_with(Default::default)
+            .hard_delete_time = Some(v);
+        self.metakey |= Metakey::HardDeleteTime;
+        self
+    }
+
+    /// Storage class of this entry.
+    ///
+    /// Storage class describes the storage tier that keeps this entry. Most
+    /// services tier data in the same way, like `StorageClass` of S3, OSS and
+    /// COS, `storageClass` of GCS and the storage policy of HDFS. So it's kept
+    /// as the service specific name, like `STANDARD`, `COLDLINE` or `COLD`,
+    /// instead of being mapped to a common set of tiers that can't cover
+    /// all of them.
+    ///
+    /// # Panics
+    ///
+    /// This value is only available when calling on result of `stat` or `list` with
+    /// [`Metakey::StorageClass`], otherwise it will panic.
+    pub fn storage_class(&self) -> Option<&str> {
+        debug_assert!(
+            self.metakey.contains(Metakey::StorageClass)
+                || self.metakey.contains(Metakey::Complete),
+            "visiting not set metadata: storage_class, maybe a bug"
+        );
+
+        self.storage_class.as_deref()
+    }
+
+    /// Set storage class of this entry.
+    pub fn with_storage_class(mut self, v: String) -> Self {
+        self.storage_class = Some(v);
+        self.metakey |= Metakey::StorageClass;
+        self
+    }
+
+    /// Set storage class of this entry.
+    pub fn set_storage_class(&mut self, v: &str) -> &mut Self {
+        self.storage_class = Some(v.to_string());
+        self.metakey |= Metakey::StorageClass;
+        self
+    }
 }
 
 flags! {
@@ -551,5 +707,13 @@ flags! {
         LastModified,
         /// Key for version.
         Version,
+        /// Key for soft delete time.
+        SoftDeleteTime,
+        /// Key for hard delete time.
+        HardDeleteTime,
+        /// Key for storage class.
+        StorageClass,
+        /// Key for content crc32c.
+        ContentCrc32c,
     }
 }
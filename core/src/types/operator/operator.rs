@@ -490,41 +490,79 @@ impl Operator {
     /// # }
     /// ```
     pub async fn copy(&self, from: &str, to: &str) -> Result<()> {
-        let from = normalize_path(from);
-
-        if !validate_path(&from, EntryMode::FILE) {
-            return Err(
-                Error::new(ErrorKind::IsADirectory, "from path is a directory")
-                    .with_operation("Operator::copy")
-                    .with_context("service", self.info().scheme())
-                    .with_context("from", from),
-            );
-        }
+        self.copy_with(from, to).await
+    }
 
+    /// Copy a file from `from` to `to` with extra options.
+    ///
+    /// # Notes
+    ///
+    /// - `from` and `to` must be a file.
+    /// - `to` will be overwritten if it exists.
+    /// - If `from` and `to` are the same, an `IsSameFile` error will occur.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use opendal::Operator;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.copy_with("path/to/file", "path/to/file2")
+    ///     .content_type("text/plain")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn copy_with(&self, from: &str, to: &str) -> FutureCopy {
+        let from = normalize_path(from);
         let to = normalize_path(to);
 
-        if !validate_path(&to, EntryMode::FILE) {
-            return Err(
-                Error::new(ErrorKind::IsADirectory, "to path is a directory")
-                    .with_operation("Operator::copy")
-                    .with_context("service", self.info().scheme())
-                    .with_context("to", to),
-            );
-        }
+        let fut = FutureCopy(OperatorFuture::new(
+            self.inner().clone(),
+            from,
+            (OpCopy::default(), to),
+            |inner, from, (args, to)| {
+                let fut = async move {
+                    if !validate_path(&from, EntryMode::FILE) {
+                        return Err(Error::new(
+                            ErrorKind::IsADirectory,
+                            "from path is a directory",
+                        )
+                        .with_operation("Operator::copy")
+                        .with_context("service", inner.info().scheme().into_static())
+                        .with_context("from", from));
+                    }
 
-        if from == to {
-            return Err(
-                Error::new(ErrorKind::IsSameFile, "from and to paths are same")
-                    .with_operation("Operator::copy")
-                    .with_context("service", self.info().scheme())
-                    .with_context("from", from)
-                    .with_context("to", to),
-            );
-        }
+                    if !validate_path(&to, EntryMode::FILE) {
+                        return Err(
+                            Error::new(ErrorKind::IsADirectory, "to path is a directory")
+                                .with_operation("Operator::copy")
+                                .with_context("service", inner.info().scheme().into_static())
+                                .with_context("to", to),
+                        );
+                    }
 
-        self.inner().copy(&from, &to, OpCopy::new()).await?;
+                    if from == to {
+                        return Err(Error::new(
+                            ErrorKind::IsSameFile,
+                            "from and to paths are same",
+                        )
+                        .with_operation("Operator::copy")
+                        .with_context("service", inner.info().scheme().into_static())
+                        .with_context("from", from)
+                        .with_context("to", to));
+                    }
 
-        Ok(())
+                    inner.copy(&from, &to, args).await?;
+
+                    Ok(())
+                };
+                Box::pin(fut)
+            },
+        ));
+        fut
     }
 
     /// Rename a file from `from` to `to`.
@@ -548,41 +586,78 @@ impl Operator {
     /// # }
     /// ```
     pub async fn rename(&self, from: &str, to: &str) -> Result<()> {
-        let from = normalize_path(from);
-
-        if !validate_path(&from, EntryMode::FILE) {
-            return Err(
-                Error::new(ErrorKind::IsADirectory, "from path is a directory")
-                    .with_operation("Operator::move_")
-                    .with_context("service", self.info().scheme())
-                    .with_context("from", from),
-            );
-        }
+        self.rename_with(from, to).await
+    }
 
+    /// Rename a file from `from` to `to` with extra options.
+    ///
+    /// # Notes
+    ///
+    /// - `from` and `to` must be a file.
+    /// - If `from` and `to` are the same, an `IsSameFile` error will occur.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use opendal::Operator;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.rename_with("path/to/file", "path/to/file2")
+    ///     .overwrite(false)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rename_with(&self, from: &str, to: &str) -> FutureRename {
+        let from = normalize_path(from);
         let to = normalize_path(to);
 
-        if !validate_path(&to, EntryMode::FILE) {
-            return Err(
-                Error::new(ErrorKind::IsADirectory, "to path is a directory")
-                    .with_operation("Operator::move_")
-                    .with_context("service", self.info().scheme())
-                    .with_context("to", to),
-            );
-        }
+        let fut = FutureRename(OperatorFuture::new(
+            self.inner().clone(),
+            from,
+            (OpRename::default(), to),
+            |inner, from, (args, to)| {
+                let fut = async move {
+                    if !validate_path(&from, EntryMode::FILE) {
+                        return Err(Error::new(
+                            ErrorKind::IsADirectory,
+                            "from path is a directory",
+                        )
+                        .with_operation("Operator::move_")
+                        .with_context("service", inner.info().scheme().into_static())
+                        .with_context("from", from));
+                    }
 
-        if from == to {
-            return Err(
-                Error::new(ErrorKind::IsSameFile, "from and to paths are same")
-                    .with_operation("Operator::move_")
-                    .with_context("service", self.info().scheme())
-                    .with_context("from", from)
-                    .with_context("to", to),
-            );
-        }
+                    if !validate_path(&to, EntryMode::FILE) {
+                        return Err(
+                            Error::new(ErrorKind::IsADirectory, "to path is a directory")
+                                .with_operation("Operator::move_")
+                                .with_context("service", inner.info().scheme().into_static())
+                                .with_context("to", to),
+                        );
+                    }
 
-        self.inner().rename(&from, &to, OpRename::new()).await?;
+                    if from == to {
+                        return Err(Error::new(
+                            ErrorKind::IsSameFile,
+                            "from and to paths are same",
+                        )
+                        .with_operation("Operator::move_")
+                        .with_context("service", inner.info().scheme().into_static())
+                        .with_context("from", from)
+                        .with_context("to", to));
+                    }
 
-        Ok(())
+                    inner.rename(&from, &to, args).await?;
+
+                    Ok(())
+                };
+                Box::pin(fut)
+            },
+        ));
+        fut
     }
 
     /// Write multiple bytes into path.
@@ -694,7 +769,7 @@ impl Operator {
         let fut = FutureWrite(OperatorFuture::new(
             self.inner().clone(),
             path,
-            (OpWrite::default(), bs),
+            (OpWrite::default().with_content_length(bs.len() as u64), bs),
             |inner, path, (args, mut bs)| {
                 let fut = async move {
                     if !validate_path(&path, EntryMode::FILE) {
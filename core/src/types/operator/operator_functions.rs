@@ -167,6 +167,14 @@ impl FunctionWriter {
         self
     }
 
+    /// Set the total content length that will be written.
+    ///
+    /// Services like GCS could use it to validate the upload early.
+    pub fn content_length(mut self, v: u64) -> Self {
+        self.0 = self.0.map_args(|args| args.with_content_length(v));
+        self
+    }
+
     /// Set the content type of option
     pub fn content_type(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_content_type(v));
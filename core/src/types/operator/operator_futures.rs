@@ -27,6 +27,8 @@ use std::task::Poll;
 use std::time::Duration;
 
 use bytes::Bytes;
+use chrono::DateTime;
+use chrono::Utc;
 use flagset::FlagSet;
 use futures::future::BoxFuture;
 use futures::Future;
@@ -138,6 +140,15 @@ impl FutureStat {
         self.0 = self.0.map_args(|args| args.with_version(v));
         self
     }
+
+    /// Set the max retries for this operation.
+    ///
+    /// It overrides the `max_times` of `RetryLayer` for this operation, and
+    /// takes no effect if `RetryLayer` is not used.
+    pub fn max_retries(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_max_retries(v));
+        self
+    }
 }
 
 impl Future for FutureStat {
@@ -294,11 +305,39 @@ impl FutureRead {
         self
     }
 
+    /// Set the If-Modified-Since for this operation.
+    ///
+    /// Read will return `ConditionNotMatch` if the file has not been modified
+    /// since the given time.
+    pub fn if_modified_since(mut self, v: DateTime<Utc>) -> Self {
+        self.0 = self.0.map_args(|args| args.with_if_modified_since(v));
+        self
+    }
+
     /// Set the version for this operation.
     pub fn version(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_version(v));
         self
     }
+
+    /// Set the max retries for this operation.
+    ///
+    /// It overrides the `max_times` of `RetryLayer` for this operation, and
+    /// takes no effect if `RetryLayer` is not used.
+    pub fn max_retries(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_max_retries(v));
+        self
+    }
+
+    /// Read the stored bytes exactly, without any transcoding.
+    ///
+    /// Services like GCS will send `Accept-Encoding: identity` and return an
+    /// error instead of the decompressed content if the object is stored with
+    /// `Content-Encoding: gzip` and transcoded.
+    pub fn identity_encoding(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_identity_encoding(v));
+        self
+    }
 }
 
 impl Future for FutureRead {
@@ -356,6 +395,45 @@ impl FutureReader {
         self.0 = self.0.map_args(|args| args.with_if_none_match(v));
         self
     }
+
+    /// Set the If-Modified-Since for this operation.
+    ///
+    /// Read will return `ConditionNotMatch` if the file has not been modified
+    /// since the given time.
+    pub fn if_modified_since(mut self, v: DateTime<Utc>) -> Self {
+        self.0 = self.0.map_args(|args| args.with_if_modified_since(v));
+        self
+    }
+
+    /// Set the max retries for this operation.
+    ///
+    /// It overrides the `max_times` of `RetryLayer` for this operation, and
+    /// takes no effect if `RetryLayer` is not used.
+    pub fn max_retries(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_max_retries(v));
+        self
+    }
+
+    /// Read the stored bytes exactly, without any transcoding.
+    ///
+    /// Services like GCS will send `Accept-Encoding: identity` and return an
+    /// error instead of the decompressed content if the object is stored with
+    /// `Content-Encoding: gzip` and transcoded.
+    pub fn identity_encoding(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_identity_encoding(v));
+        self
+    }
+
+    /// Return the metadata advertised by services along with the content,
+    /// which could be got via [`Reader::metadata`].
+    ///
+    /// The read request will be sent while creating the reader instead of
+    /// at the first read. Services that don't advertise metadata while
+    /// reading will return all fields as `None`.
+    pub fn return_metadata(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_return_metadata(v));
+        self
+    }
 }
 
 impl Future for FutureReader {
@@ -420,6 +498,24 @@ impl FutureWrite {
             .map_args(|(args, bs)| (args.with_cache_control(v), bs));
         self
     }
+
+    /// Set the If-Match for this operation.
+    ///
+    /// Write will return `ConditionNotMatch` if the etag of the existing
+    /// file doesn't match.
+    pub fn if_match(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|(args, bs)| (args.with_if_match(v), bs));
+        self
+    }
+
+    /// Set the max retries for this operation.
+    ///
+    /// It overrides the `max_times` of `RetryLayer` for this operation, and
+    /// takes no effect if `RetryLayer` is not used.
+    pub fn max_retries(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|(args, bs)| (args.with_max_retries(v), bs));
+        self
+    }
 }
 
 impl Future for FutureWrite {
@@ -468,6 +564,14 @@ impl FutureWriter {
         self
     }
 
+    /// Set the total content length that will be written.
+    ///
+    /// Services like GCS could use it to validate the upload early.
+    pub fn content_length(mut self, v: u64) -> Self {
+        self.0 = self.0.map_args(|args| args.with_content_length(v));
+        self
+    }
+
     /// Set the content type of option
     pub fn content_type(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_content_type(v));
@@ -485,6 +589,24 @@ impl FutureWriter {
         self.0 = self.0.map_args(|args| args.with_cache_control(v));
         self
     }
+
+    /// Set the If-Match for this operation.
+    ///
+    /// Write will return `ConditionNotMatch` if the etag of the existing
+    /// file doesn't match.
+    pub fn if_match(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_if_match(v));
+        self
+    }
+
+    /// Set the max retries for this operation.
+    ///
+    /// It overrides the `max_times` of `RetryLayer` for this operation, and
+    /// takes no effect if `RetryLayer` is not used.
+    pub fn max_retries(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_max_retries(v));
+        self
+    }
 }
 
 impl Future for FutureWriter {
@@ -561,6 +683,39 @@ impl FutureList {
         self.0 = self.0.map_args(|args| args.with_metakey(v));
         self
     }
+
+    /// The soft_deleted is used to list soft deleted entries only.
+    ///
+    /// Services like GCS will return the soft delete time and hard delete
+    /// time of entries, which could be fetched via [`Metakey::SoftDeleteTime`]
+    /// and [`Metakey::HardDeleteTime`].
+    ///
+    /// Default to `false`.
+    pub fn soft_deleted(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_soft_deleted(v));
+        self
+    }
+
+    /// The name_prefix is used to only return entries whose name starts with it.
+    ///
+    /// The name is the last component of entry's path, like `b` of `a/b`.
+    ///
+    /// Returns `ErrorKind::Unsupported` if the service doesn't support it.
+    pub fn name_prefix(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_name_prefix(v));
+        self
+    }
+
+    /// The entry_mode is used to only return entries of this mode.
+    ///
+    /// For example, `EntryMode::FILE` returns files only and `EntryMode::DIR`
+    /// returns dirs only. Entries of all modes are returned by default.
+    ///
+    /// Returns `ErrorKind::Unsupported` if the service doesn't support it.
+    pub fn entry_mode(mut self, v: EntryMode) -> Self {
+        self.0 = self.0.map_args(|args| args.with_entry_mode(v));
+        self
+    }
 }
 
 impl Future for FutureList {
@@ -616,6 +771,39 @@ impl FutureLister {
         self.0 = self.0.map_args(|args| args.with_metakey(v));
         self
     }
+
+    /// The soft_deleted is used to list soft deleted entries only.
+    ///
+    /// Services like GCS will return the soft delete time and hard delete
+    /// time of entries, which could be fetched via [`Metakey::SoftDeleteTime`]
+    /// and [`Metakey::HardDeleteTime`].
+    ///
+    /// Default to `false`.
+    pub fn soft_deleted(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_soft_deleted(v));
+        self
+    }
+
+    /// The name_prefix is used to only return entries whose name starts with it.
+    ///
+    /// The name is the last component of entry's path, like `b` of `a/b`.
+    ///
+    /// Returns `ErrorKind::Unsupported` if the service doesn't support it.
+    pub fn name_prefix(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_name_prefix(v));
+        self
+    }
+
+    /// The entry_mode is used to only return entries of this mode.
+    ///
+    /// For example, `EntryMode::FILE` returns files only and `EntryMode::DIR`
+    /// returns dirs only. Entries of all modes are returned by default.
+    ///
+    /// Returns `ErrorKind::Unsupported` if the service doesn't support it.
+    pub fn entry_mode(mut self, v: EntryMode) -> Self {
+        self.0 = self.0.map_args(|args| args.with_entry_mode(v));
+        self
+    }
 }
 
 impl Future for FutureLister {
@@ -625,3 +813,78 @@ impl Future for FutureLister {
         self.0.poll_unpin(cx)
     }
 }
+
+/// Future that generated by [`Operator::copy_with`].
+///
+/// Users can add more options by public functions provided by this struct.
+pub struct FutureCopy(pub(crate) OperatorFuture<(OpCopy, String), ()>);
+
+impl FutureCopy {
+    /// Set the content type that overrides the destination's.
+    pub fn content_type(mut self, v: &str) -> Self {
+        self.0 = self
+            .0
+            .map_args(|(args, to)| (args.with_content_type(v), to));
+        self
+    }
+
+    /// Set the cache control that overrides the destination's.
+    pub fn cache_control(mut self, v: &str) -> Self {
+        self.0 = self
+            .0
+            .map_args(|(args, to)| (args.with_cache_control(v), to));
+        self
+    }
+
+    /// Set the version of source to copy from.
+    ///
+    /// Services that support it will copy this exact version instead of the
+    /// latest one.
+    pub fn source_version(mut self, v: &str) -> Self {
+        self.0 = self
+            .0
+            .map_args(|(args, to)| (args.with_source_version(v), to));
+        self
+    }
+
+    /// Reset the destination's metadata to defaults instead of preserving
+    /// the source's.
+    pub fn reset_metadata(mut self, v: bool) -> Self {
+        self.0 = self
+            .0
+            .map_args(|(args, to)| (args.with_reset_metadata(v), to));
+        self
+    }
+}
+
+impl Future for FutureCopy {
+    type Output = Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.poll_unpin(cx)
+    }
+}
+
+/// Future that generated by [`Operator::rename_with`].
+///
+/// Users can add more options by public functions provided by this struct.
+pub struct FutureRename(pub(crate) OperatorFuture<(OpRename, String), ()>);
+
+impl FutureRename {
+    /// Set whether to overwrite the destination if it exists.
+    ///
+    /// If `false`, services that support it will return an `AlreadyExists`
+    /// error if the destination exists.
+    pub fn overwrite(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|(args, to)| (args.with_overwrite(v), to));
+        self
+    }
+}
+
+impl Future for FutureRename {
+    type Output = Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.poll_unpin(cx)
+    }
+}
@@ -56,6 +56,7 @@ use crate::*;
 pub struct Reader {
     inner: oio::Reader,
     seek_state: SeekState,
+    metadata: Option<Metadata>,
 }
 
 impl Reader {
@@ -67,13 +68,24 @@ impl Reader {
     /// We don't want to expose those details to users so keep this function
     /// in crate only.
     pub(crate) async fn create(acc: FusedAccessor, path: &str, op: OpRead) -> Result<Self> {
-        let (_, r) = acc.read(path, op).await?;
+        let return_metadata = op.return_metadata();
+        let (rp, r) = acc.read(path, op).await?;
 
         Ok(Reader {
             inner: r,
             seek_state: SeekState::Init,
+            metadata: return_metadata.then(|| rp.into_metadata()),
         })
     }
+
+    /// Get the metadata advertised by services along with the content,
+    /// like content type, etag and checksums.
+    ///
+    /// It's only available if the reader is created with
+    /// [`FutureReader::return_metadata`].
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_ref()
+    }
 }
 
 impl oio::Read for Reader {
@@ -144,6 +144,43 @@ impl Scheme {
         self.into()
     }
 
+    /// Check if this scheme is an object storage service.
+    ///
+    /// Object storage services store objects in flat namespace and address
+    /// them by key, like s3 and gcs.
+    pub fn is_object_store(&self) -> bool {
+        matches!(
+            self,
+            Scheme::Azblob
+                | Scheme::Cos
+                | Scheme::Gcs
+                | Scheme::Obs
+                | Scheme::Oss
+                | Scheme::S3
+                | Scheme::Swift
+        )
+    }
+
+    /// Check if this scheme supports presign.
+    ///
+    /// It's a quick check without building the operator, and keeps the same
+    /// with the `presign` capability returned by services' `info()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use opendal::Scheme;
+    ///
+    /// assert!(Scheme::S3.supports_presign());
+    /// assert!(!Scheme::Memory.supports_presign());
+    /// ```
+    pub fn supports_presign(&self) -> bool {
+        matches!(
+            self,
+            Scheme::Cos | Scheme::Gcs | Scheme::Obs | Scheme::Oss | Scheme::S3
+        )
+    }
+
     /// Get all enabled schemes.
     ///
     /// OpenDAL could be compiled with different features, which will enable different schemes.
@@ -388,3 +425,25 @@ impl From<Scheme> for String {
         v.into_static().to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_object_store() {
+        assert!(Scheme::Gcs.is_object_store());
+        assert!(Scheme::S3.is_object_store());
+        assert!(!Scheme::Webhdfs.is_object_store());
+        assert!(!Scheme::Redis.is_object_store());
+    }
+
+    #[test]
+    fn test_supports_presign() {
+        assert!(Scheme::Gcs.supports_presign());
+        assert!(Scheme::S3.supports_presign());
+        assert!(!Scheme::Webhdfs.supports_presign());
+        assert!(!Scheme::Redis.supports_presign());
+        assert!(!Scheme::Custom("custom").supports_presign());
+    }
+}
@@ -72,6 +72,7 @@ use crate::*;
 /// - Services that doesn't support append will return [`ErrorKind::Unsupported`] error when
 ///   creating writer with `append` enabled.
 pub struct Writer {
+    rp: RpWrite,
     inner: oio::Writer,
 }
 
@@ -84,9 +85,18 @@ impl Writer {
     /// We don't want to expose those details to users so keep this function
     /// in crate only.
     pub(crate) async fn create(acc: FusedAccessor, path: &str, op: OpWrite) -> Result<Self> {
-        let (_, w) = acc.write(path, op).await?;
+        let (rp, w) = acc.write(path, op).await?;
 
-        Ok(Writer { inner: w })
+        Ok(Writer { rp, inner: w })
+    }
+
+    /// Get the metadata of the written file returned by services, like
+    /// etag, version and checksums.
+    ///
+    /// It's only available after the writer is closed, and `None` if the
+    /// service doesn't return it.
+    pub fn metadata(&self) -> Option<Metadata> {
+        self.rp.metadata()
     }
 
     /// Write into inner writer.